@@ -0,0 +1,42 @@
+#![feature(test)]
+
+extern crate fux_delaunay;
+extern crate test;
+
+use fux_delaunay::types::*;
+use fux_delaunay::c_api::*;
+use test::Bencher;
+
+fn nodes() -> Vec<Point2> {
+    deserialize_data2("tests/data/300x300_truktura_500.json")
+        .expect("failed to open file.")
+        .into_iter()
+        .map(|p| Point2::new(p.x, p.y))
+        .collect()
+}
+
+#[bench]
+fn truktura_500_incremental(b: &mut Bencher) {
+    let nodes = nodes();
+    b.iter(|| Triangulation2::new_with_options(&nodes, &BuildOptions::default()));
+}
+
+#[bench]
+fn truktura_500_incremental_hilbert(b: &mut Bencher) {
+    let nodes = nodes();
+    let options = BuildOptions {
+        insertion_order: InsertionOrder::Hilbert,
+        ..BuildOptions::default()
+    };
+    b.iter(|| Triangulation2::new_with_options(&nodes, &options));
+}
+
+#[bench]
+fn truktura_500_divide_and_conquer(b: &mut Bencher) {
+    let nodes = nodes();
+    let options = BuildOptions {
+        algorithm: BuildAlgorithm::DivideAndConquer,
+        ..BuildOptions::default()
+    };
+    b.iter(|| Triangulation2::new_with_options(&nodes, &options));
+}
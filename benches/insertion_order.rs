@@ -0,0 +1,71 @@
+#![feature(test)]
+
+extern crate fux_delaunay;
+extern crate test;
+
+use fux_delaunay::types::*;
+use fux_delaunay::c_api::*;
+use test::Bencher;
+
+//3d construction of the whole set takes seconds, a part of it is enough to compare the orders.
+const NODES3: usize = 2000;
+
+fn nodes2() -> Vec<Point2> {
+    deserialize_data2("tests/data/300x300_truktura_500.json")
+        .expect("failed to open file.")
+        .into_iter()
+        .map(|p| Point2::new(p.x, p.y))
+        .collect()
+}
+
+fn nodes3() -> Vec<Point3> {
+    deserialize_data3("tests/data/data_100X100X100.json")
+        .expect("failed to open file.")
+        .into_iter()
+        .take(NODES3)
+        .map(|p| Point3::new(p.x, p.y, p.z))
+        .collect()
+}
+
+fn options(insertion_order: InsertionOrder) -> BuildOptions {
+    BuildOptions {
+        insertion_order: insertion_order,
+        ..BuildOptions::default()
+    }
+}
+
+#[bench]
+fn truktura_500_lexicographic(b: &mut Bencher) {
+    let nodes = nodes2();
+    b.iter(|| Triangulation2::new_with_options(&nodes, &options(InsertionOrder::Lexicographic)));
+}
+
+#[bench]
+fn truktura_500_hilbert(b: &mut Bencher) {
+    let nodes = nodes2();
+    b.iter(|| Triangulation2::new_with_options(&nodes, &options(InsertionOrder::Hilbert)));
+}
+
+#[bench]
+fn truktura_500_morton(b: &mut Bencher) {
+    let nodes = nodes2();
+    b.iter(|| Triangulation2::new_with_options(&nodes, &options(InsertionOrder::Morton)));
+}
+
+#[bench]
+fn data_100x100x100_lexicographic(b: &mut Bencher) {
+    let nodes = nodes3();
+    b.iter(|| Triangulation3::new_with_options(&nodes, &options(InsertionOrder::Lexicographic)));
+}
+
+#[bench]
+fn data_100x100x100_hilbert(b: &mut Bencher) {
+    let nodes = nodes3();
+    b.iter(|| Triangulation3::new_with_options(&nodes, &options(InsertionOrder::Hilbert)));
+}
+
+#[bench]
+fn data_100x100x100_morton(b: &mut Bencher) {
+    let nodes = nodes3();
+    b.iter(|| Triangulation3::new_with_options(&nodes, &options(InsertionOrder::Morton)));
+}
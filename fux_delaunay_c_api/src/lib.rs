@@ -20,7 +20,7 @@ fn it_works2() {
         let elements: &[CApiElement3] = from_raw_parts(triangulation.elements, triangulation.element_count as usize);
 
         assert_eq!(2, elements.len());
-        assert_eq!(CApiElement3 { v: [0, 3, 2] }, elements[0]);
+        assert_eq!(CApiElement3 { v: [2, 0, 3] }, elements[0]);
         assert_eq!(CApiElement3 { v: [0, 1, 3] }, elements[1]);
     }
 }
@@ -1,4 +1,5 @@
 use types::T3Index;
+use types::N2Index;
 
 use types::Triangle;
 use types::Point2;
@@ -9,6 +10,10 @@ use math;
 pub enum LocationResult {
     InElement(T3Index),
     OnEdge(T3Index, usize),
+    //point coincides with the given node of the element.
+    OnVertex(T3Index, N2Index),
+    //point lies outside of the triangulation, past the given border edge of the element.
+    Outside(T3Index, usize),
 }
 
 #[inline]
@@ -17,48 +22,115 @@ pub fn locate_element_containing(start_lookup_at: T3Index,
                                  nodes: &Vec<Point2>,
                                  p: &Point2)
                                  -> LocationResult {
-    let mut ele_index = start_lookup_at;
+    locate_counting_visited(start_lookup_at, elements, nodes, p).0
+}
 
-    loop {
-        let ele: &Triangle = &elements[ele_index.0];
+//same as locate_element_containing, also returns the number of elements the walk went through.
+//each element tests its edges from a different one, which keeps the walk from going around the
+//same elements forever in a triangulation which is not delaunay, for example after constraints
+//were inserted. should the walk still pass more elements than there are, all of them are
+//tested one by one.
+pub fn locate_counting_visited(start_lookup_at: T3Index,
+                               elements: &Vec<Triangle>,
+                               nodes: &Vec<Point2>,
+                               p: &Point2)
+                               -> (LocationResult, usize) {
+    let mut ele_index = start_lookup_at;
+    let mut visited = 0;
+
+    while visited < elements.len() {
+        visited += 1;
+
+        let first_edge = (ele_index.0 + visited) % 3;
+        match step(&elements[ele_index.0], ele_index, nodes, p, first_edge) {
+            Step::Found(location_result) => return (location_result, visited),
+            Step::Cross(edge_index) => {
+                match elements[ele_index.0].get_neighbor_from_index(edge_index) {
+                    Some(neighbor_index) => ele_index = neighbor_index,
+                    None => return (LocationResult::Outside(ele_index, edge_index), visited),
+                }
+            }
+        }
+    }
 
-        let mut current_edge = 0;
-        let mut on_edge_found: Option<usize> = None;
+    let (location_result, scanned) = locate_by_scan(elements, nodes, p);
+    (location_result, visited + scanned)
+}
 
-        loop {
-            //println!("locating: curr index is: {:?} {:?}", ele_index, ele);
-            //println!("current_edge: {:?}", current_edge);
-            if current_edge == 3 {
-                break;
+//tests the elements one by one with the same exact predicates as the walk, together with the
+//number of tested elements. a point outside of all of them is beyond a border edge which has it
+//on its outer side.
+pub fn locate_by_scan(elements: &Vec<Triangle>,
+                      nodes: &Vec<Point2>,
+                      p: &Point2)
+                      -> (LocationResult, usize) {
+    let mut outside = None;
+
+    for (index, ele) in elements.iter().enumerate() {
+        match step(ele, T3Index(index), nodes, p, 0) {
+            Step::Found(location_result) => return (location_result, index + 1),
+            Step::Cross(_) if outside.is_none() => {
+                outside = (0..3)
+                    .find(|&edge_index| {
+                        let edge = ele.edges_as_points_tuples(nodes)[edge_index];
+                        ele.get_neighbor_from_index(edge_index).is_none() &&
+                        math::side_of_line(edge.0, edge.1, p) == math::PointLiesOnLineSide::Left
+                    })
+                    .map(|edge_index| (T3Index(index), edge_index));
             }
+            Step::Cross(_) => (),
+        }
+    }
 
-            let edge = ele.edges_as_points_tuples(nodes)[current_edge];
+    let (ele_index, edge_index) = outside.unwrap_or((T3Index(0), 0));
+    (LocationResult::Outside(ele_index, edge_index), elements.len())
+}
 
-            match math::side_of_line(edge.0, edge.1, p) {
-                math::PointLiesOnLineSide::Left => {
-                    assert!(ele.get_neighbor_from_index(current_edge).is_some());
+enum Step {
+    Found(LocationResult),
+    //point lies on the outer side of the edge.
+    Cross(usize),
+}
 
-                    ele_index = ele.get_neighbor_from_index(current_edge).unwrap();
-                    break;
-                }
-                math::PointLiesOnLineSide::OnLine => {
-                    on_edge_found = Some(current_edge);
+//where the point lies towards the element, its edges are tested starting from first_edge.
+fn step(ele: &Triangle,
+        ele_index: T3Index,
+        nodes: &Vec<Point2>,
+        p: &Point2,
+        first_edge: usize)
+        -> Step {
+    let mut on_edge_found: Option<usize> = None;
+
+    for current_edge in (first_edge..first_edge + 3).map(|e| e % 3) {
+        let edge = ele.edges_as_points_tuples(nodes)[current_edge];
+
+        match math::side_of_line(edge.0, edge.1, p) {
+            math::PointLiesOnLineSide::Left => return Step::Cross(current_edge),
+            math::PointLiesOnLineSide::OnLine => {
+                if let Some(previous_edge) = on_edge_found {
+                    //two edges meet only in their common node.
+                    let node = ele.get_edge(current_edge).0;
+                    let node = if node == ele.get_edge(previous_edge).1 {
+                        node
+                    } else {
+                        ele.get_edge(previous_edge).0
+                    };
+
+                    return Step::Found(LocationResult::OnVertex(ele_index, node));
                 }
-                math::PointLiesOnLineSide::Right => (),
-            }
-            current_edge += 1;
-        }
 
-        if current_edge == 3 {
-            if let Some(on_edge_found) = on_edge_found {
-                return LocationResult::OnEdge(ele_index, on_edge_found);
-            }
-            if ele.is_point_inside(&nodes, &p) {
-                return LocationResult::InElement(ele_index);
-            } else {
+                on_edge_found = Some(current_edge);
             }
+            math::PointLiesOnLineSide::Right => (),
         }
     }
+
+    match on_edge_found {
+        Some(on_edge_found) => Step::Found(LocationResult::OnEdge(ele_index, on_edge_found)),
+        //no edge has the point on its left, so it is inside. is_point_inside is not used here
+        //as its epsilon rejects points in long, thin triangles.
+        None => Step::Found(LocationResult::InElement(ele_index)),
+    }
 }
 
 #[cfg(test)]
@@ -104,4 +176,57 @@ mod tests {
                                              triangulation.nodes(),
                                              &Point2::new(0.5, 0.)));
     }
+
+    #[test]
+    fn locating_existing_node() {
+        let pts = vec![Point2::new(0.0, 0.0),
+                       Point2::new(1.0, 0.0),
+                       Point2::new(0.0, 1.0),
+                       Point2::new(1.0, 1.0)];
+
+        let t0 = Triangle::new(&pts, N2Index(0), N2Index(1), N2Index(2));
+        let t1 = Triangle::new(&pts, N2Index(1), N2Index(2), N2Index(3));
+
+        let triangulation = Triangulation2::new_from_prebuilt_triangulation(pts.clone(),
+                                                                            vec![t0, t1]);
+
+        for i in 0..3 {
+            assert_eq!(LocationResult::OnVertex(T3Index(0), N2Index(i)),
+                       locate_element_containing(T3Index(0),
+                                                 triangulation.elements(),
+                                                 triangulation.nodes(),
+                                                 &pts[i]));
+        }
+
+        assert_eq!(LocationResult::OnVertex(T3Index(1), N2Index(3)),
+                   locate_element_containing(T3Index(0),
+                                             triangulation.elements(),
+                                             triangulation.nodes(),
+                                             &pts[3]));
+    }
+
+    #[test]
+    fn locating_outside_of_triangulation() {
+        let pts = vec![Point2::new(0.0, 0.0),
+                       Point2::new(1.0, 0.0),
+                       Point2::new(0.0, 1.0),
+                       Point2::new(1.0, 1.0)];
+
+        let t0 = Triangle::new(&pts, N2Index(0), N2Index(1), N2Index(2));
+        let t1 = Triangle::new(&pts, N2Index(1), N2Index(2), N2Index(3));
+
+        let triangulation = Triangulation2::new_from_prebuilt_triangulation(pts.clone(),
+                                                                            vec![t0, t1]);
+
+        assert_eq!(LocationResult::Outside(T3Index(0), 2usize),
+                   locate_element_containing(T3Index(0),
+                                             triangulation.elements(),
+                                             triangulation.nodes(),
+                                             &Point2::new(0.5, -1.)));
+        assert_eq!(LocationResult::Outside(T3Index(1), 2usize),
+                   locate_element_containing(T3Index(0),
+                                             triangulation.elements(),
+                                             triangulation.nodes(),
+                                             &Point2::new(2., 0.5)));
+    }
 }
@@ -1,28 +1,48 @@
 use types::*;
+use std::collections::VecDeque;
 
 pub fn try_flip(triangulation: &mut Triangulation2,
                 bottom_node_index: N2Index,
                 bottom_element_index: T3Index)
                 -> Option<(T3Index, T3Index)> {
-    let (common1, common2, top_element_index) = {
+    let top_element_index = {
         let tr: &Triangle = &triangulation.elements()[bottom_element_index.0];
         let (left_common_node, right_common_node) = tr.get_others_two_nodes(bottom_node_index);
-        let neighbor_opt = tr.get_neighor_for_nodes(left_common_node, right_common_node);
-        if neighbor_opt.is_none() {
+        if tr.is_constrained_for_nodes(left_common_node, right_common_node) {
             return None;
         }
 
-        (left_common_node, right_common_node, neighbor_opt.unwrap())
+        match tr.get_neighor_for_nodes(left_common_node, right_common_node) {
+            Some(neighbor_index) => neighbor_index,
+            None => return None,
+        }
     };
 
     {
-        let node = &triangulation.nodes()[bottom_node_index.0];
         let neighbor = &triangulation.elements()[top_element_index.0];
-        if !triangulation.is_inside_circumcircle(neighbor, node) {
+        if !triangulation.is_node_inside_circumcircle(neighbor, bottom_node_index) {
             return None;
         }
     }
 
+    Some(flip(triangulation, bottom_node_index, bottom_element_index))
+}
+
+//replaces the edge opposite of the bottom node with the other diagonal of the two elements
+//sharing it. the edge must have a neighbor and the two elements must form a convex quad.
+pub fn flip(triangulation: &mut Triangulation2,
+            bottom_node_index: N2Index,
+            bottom_element_index: T3Index)
+            -> (T3Index, T3Index) {
+    let (common1, common2, top_element_index) = {
+        let tr: &Triangle = &triangulation.elements()[bottom_element_index.0];
+        let (left_common_node, right_common_node) = tr.get_others_two_nodes(bottom_node_index);
+        let neighbor_index = tr.get_neighor_for_nodes(left_common_node, right_common_node)
+            .expect("flip invoked on a border edge.");
+
+        (left_common_node, right_common_node, neighbor_index)
+    };
+
     let top_node_index = {
         let neighbor: &Triangle = &triangulation.elements()[top_element_index.0];
         neighbor.get_other_last_node(common1, common2)
@@ -36,10 +56,18 @@ pub fn try_flip(triangulation: &mut Triangulation2,
          top_triangle.get_neighor_for_nodes(common2, top_node_index))
     };
 
+    let (c1_bottom_constrained, c2_top_constrained) = {
+        let top_triangle: &Triangle = &triangulation.elements()[top_element_index.0];
+        let bottom_triangle: &Triangle = &triangulation.elements()[bottom_element_index.0];
+
+        (bottom_triangle.is_constrained_for_nodes(common1, bottom_node_index),
+         top_triangle.is_constrained_for_nodes(common2, top_node_index))
+    };
+
     perform_swap_update_connections(triangulation,
                                     bottom_element_index,
                                     top_element_index,
-                                    c2_top_neighbor_index,
+                                    (c2_top_neighbor_index, c2_top_constrained),
                                     common1,
                                     common2,
                                     bottom_node_index,
@@ -47,33 +75,99 @@ pub fn try_flip(triangulation: &mut Triangulation2,
     perform_swap_update_connections(triangulation,
                                     top_element_index,
                                     bottom_element_index,
-                                    c1_bottom_neighbor_index,
+                                    (c1_bottom_neighbor_index, c1_bottom_constrained),
                                     common2,
                                     common1,
                                     top_node_index,
                                     bottom_node_index);
 
-    Some((top_element_index, bottom_element_index))
+    (top_element_index, bottom_element_index)
 }
 
 pub fn propagating_flip(triangulation: &mut Triangulation2,
                         bottom_node_index: N2Index,
                         bottom_element_index: T3Index) {
-    if let Some((left_ele, right_ele)) =
-        try_flip(triangulation, bottom_node_index, bottom_element_index) {
-        propagating_flip(triangulation, bottom_node_index, left_ele);
-        propagating_flip(triangulation, bottom_node_index, right_ele);
+    propagate_flips(triangulation,
+                    bottom_node_index,
+                    &[bottom_element_index],
+                    FlipWorkList::Stack);
+}
+
+//flips the edges opposite of the node in the elements around it until all of them are locally
+//delaunay. elements changed by a flip are put into the work list instead of being handled
+//recursively, so the depth of the call stack does not grow with the number of flips.
+pub fn propagate_flips(triangulation: &mut Triangulation2,
+                       bottom_node_index: N2Index,
+                       elements: &[T3Index],
+                       work_list: FlipWorkList)
+                       -> FlipStatistics {
+    let mut statistics = FlipStatistics::default();
+    //stack is filled in reverse, so that the elements are handled in the given order.
+    let mut to_check: VecDeque<T3Index> = match work_list {
+        FlipWorkList::Stack => elements.iter().rev().cloned().collect(),
+        FlipWorkList::Queue => elements.iter().cloned().collect(),
+    };
+    statistics.max_work_list_len = to_check.len();
+
+    loop {
+        let ele_index = match work_list {
+            FlipWorkList::Stack => to_check.pop_back(),
+            FlipWorkList::Queue => to_check.pop_front(),
+        };
+        let ele_index = match ele_index {
+            Some(ele_index) => ele_index,
+            None => return statistics,
+        };
+
+        if let Some((left_ele, right_ele)) =
+            try_flip(triangulation, bottom_node_index, ele_index) {
+            statistics.flips += 1;
+            match work_list {
+                FlipWorkList::Stack => {
+                    to_check.push_back(right_ele);
+                    to_check.push_back(left_ele);
+                }
+                FlipWorkList::Queue => {
+                    to_check.push_back(left_ele);
+                    to_check.push_back(right_ele);
+                }
+            }
+            statistics.max_work_list_len = statistics.max_work_list_len.max(to_check.len());
+        }
+    }
+}
+
+//flips edges of the elements and of the elements changed by the flips until all of them are
+//locally delaunay. constrained edges are kept.
+pub fn flip_until_delaunay(triangulation: &mut Triangulation2, elements: Vec<T3Index>) {
+    let mut to_check = elements;
+
+    while let Some(ele_index) = to_check.pop() {
+        for edge_index in 0..3 {
+            let opposite_node = {
+                let ele: &Triangle = &triangulation.elements()[ele_index.0];
+                let (n1, n2) = ele.get_edge(edge_index);
+                ele.get_other_last_node(n1, n2)
+            };
+
+            if let Some((first, second)) = try_flip(triangulation, opposite_node, ele_index) {
+                to_check.push(first);
+                to_check.push(second);
+                break;
+            }
+        }
     }
 }
 
 fn perform_swap_update_connections(triangulation: &mut Triangulation2,
                                    element_to_swap_index: T3Index,
                                    element_swapping_with: T3Index,
-                                   changing_neighborhood_element_index: Option<T3Index>,
+                                   changing_edge: (Option<T3Index>, bool),
                                    common_node_being_swapped_out: N2Index,
                                    common_node: N2Index,
                                    last_element_node_index: N2Index,
                                    node_being_swapped_in: N2Index) {
+    let (changing_neighborhood_element_index, changing_edge_constrained) = changing_edge;
     {
         let element_being_swapped: &mut Triangle = &mut triangulation.elements_mut()
                                                             [element_to_swap_index.0];
@@ -86,6 +180,12 @@ fn perform_swap_update_connections(triangulation: &mut Triangulation2,
                                               Some(element_swapping_with));
 
         element_being_swapped.swap_node(common_node_being_swapped_out, node_being_swapped_in);
+        element_being_swapped.set_constrained_for_nodes(node_being_swapped_in,
+                                                        common_node,
+                                                        changing_edge_constrained);
+        element_being_swapped.set_constrained_for_nodes(last_element_node_index,
+                                                        node_being_swapped_in,
+                                                        false);
     }
     {
         if let Some(changing_neighborhood_element_index) = changing_neighborhood_element_index {
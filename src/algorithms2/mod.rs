@@ -1,2 +1,3 @@
 pub mod element_locators;
 pub mod lawson_flipping;
+pub mod nearest_node;
@@ -0,0 +1,399 @@
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::BTreeSet;
+use std::f64;
+
+use types::T3Index;
+use types::N2Index;
+
+use types::Triangle;
+use types::Point2;
+
+use math::distance2_squared;
+use math::distance2_to_segment_squared;
+use math::distance2_to_triangle_squared;
+
+//node nearest to p, see nodes_nearest_first.
+pub fn find_nearest_node(start_lookup_at: T3Index,
+                         elements: &Vec<Triangle>,
+                         nodes: &Vec<Point2>,
+                         p: &Point2,
+                         restricted: bool)
+                         -> N2Index {
+    find_k_nearest_nodes(start_lookup_at, elements, nodes, p, 1, restricted)[0]
+}
+
+//the k nodes nearest to p, nearest first.
+pub fn find_k_nearest_nodes(start_lookup_at: T3Index,
+                            elements: &Vec<Triangle>,
+                            nodes: &Vec<Point2>,
+                            p: &Point2,
+                            k: usize,
+                            restricted: bool)
+                            -> Vec<N2Index> {
+    nodes_nearest_first(start_lookup_at,
+                        elements,
+                        nodes,
+                        p,
+                        k,
+                        f64::INFINITY,
+                        restricted)
+}
+
+//nodes whose distance to p is at most radius, nearest first.
+pub fn find_nodes_within_radius(start_lookup_at: T3Index,
+                                elements: &Vec<Triangle>,
+                                nodes: &Vec<Point2>,
+                                p: &Point2,
+                                radius: f64,
+                                restricted: bool)
+                                -> Vec<N2Index> {
+    nodes_nearest_first(start_lookup_at,
+                        elements,
+                        nodes,
+                        p,
+                        usize::max_value(),
+                        radius * radius,
+                        restricted)
+}
+
+//at most k nodes of the elements not further from p than the square root of
+//max_distance_squared, nearest first. elements are taken by their distance to p from a queue of
+//the neighbors of the elements taken so far. the elements touching a disc around p are connected
+//when the triangulation covers a convex area, so no node in the disc is missed once the next
+//element lies outside of it, whether the triangulation is delaunay or not. the border of a
+//restricted triangulation can cut the disc into parts, so when the disc reaches the border the
+//search is repeated from all elements with a border edge in the disc.
+fn nodes_nearest_first(start_lookup_at: T3Index,
+                       elements: &Vec<Triangle>,
+                       nodes: &Vec<Point2>,
+                       p: &Point2,
+                       k: usize,
+                       max_distance_squared: f64,
+                       restricted: bool)
+                       -> Vec<N2Index> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let search = search_from(&[start_lookup_at], elements, nodes, p, k, max_distance_squared);
+    if !restricted || search.border_distance > search.reach {
+        return search.nearest;
+    }
+
+    let mut starts = vec![start_lookup_at];
+    for (ele_index, ele) in elements.iter().enumerate() {
+        let at_border = (0..3).any(|i| {
+            ele.get_neighbor_from_index(i).is_none() &&
+            edge_distance(ele, i, nodes, p) <= search.reach
+        });
+        if at_border {
+            starts.push(T3Index(ele_index));
+        }
+    }
+
+    search_from(&starts, elements, nodes, p, k, max_distance_squared).nearest
+}
+
+//nodes found from the given elements, the squared distance up to which all elements connected to
+//the first one were taken and the squared distance of the nearest border edge of the taken
+//elements.
+struct Search {
+    nearest: Vec<N2Index>,
+    reach: f64,
+    border_distance: f64,
+}
+
+fn search_from(starts: &[T3Index],
+               elements: &Vec<Triangle>,
+               nodes: &Vec<Point2>,
+               p: &Point2,
+               k: usize,
+               max_distance_squared: f64)
+               -> Search {
+    let first_distance = element_distance(&elements[starts[0].0], nodes, p);
+    let mut queued = BTreeSet::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts.iter() {
+        if queued.insert(*start) {
+            queue.push(Reverse(Candidate {
+                distance: element_distance(&elements[start.0], nodes, p),
+                index: *start,
+            }));
+        }
+    }
+
+    //the farthest of the nodes found so far is on top, so it is dropped when a nearer one comes.
+    let mut found_nodes = BTreeSet::new();
+    let mut found: BinaryHeap<Candidate<N2Index>> = BinaryHeap::new();
+    let reach = |found: &BinaryHeap<Candidate<N2Index>>| {
+        let bound = match found.peek() {
+            Some(farthest) if found.len() == k => farthest.distance.min(max_distance_squared),
+            _ => max_distance_squared,
+        };
+        //the disc has to reach into the first element, else the elements touching it are not
+        //connected to the first one.
+        bound.max(first_distance * (1. + 1e-9))
+    };
+    let mut border_distance = f64::INFINITY;
+
+    while let Some(Reverse(candidate)) = queue.pop() {
+        if candidate.distance > reach(&found) {
+            break;
+        }
+
+        let ele = &elements[candidate.index.0];
+        for node in ele.nodes().iter() {
+            let distance = distance2_squared(&nodes[node.0], p);
+            if distance <= max_distance_squared && found_nodes.insert(*node) {
+                found.push(Candidate {
+                    distance: distance,
+                    index: *node,
+                });
+                if found.len() > k {
+                    found.pop();
+                }
+            }
+        }
+
+        for i in 0..3 {
+            match ele.get_neighbor_from_index(i) {
+                Some(neighbor_index) => {
+                    if queued.insert(neighbor_index) {
+                        queue.push(Reverse(Candidate {
+                            distance: element_distance(&elements[neighbor_index.0], nodes, p),
+                            index: neighbor_index,
+                        }));
+                    }
+                }
+                None => border_distance = border_distance.min(edge_distance(ele, i, nodes, p)),
+            }
+        }
+    }
+
+    Search {
+        reach: reach(&found),
+        nearest: found.into_sorted_vec().into_iter().map(|c| c.index).collect(),
+        border_distance: border_distance,
+    }
+}
+
+fn element_distance(ele: &Triangle, nodes: &Vec<Point2>, p: &Point2) -> f64 {
+    distance2_to_triangle_squared(p, ele.a(nodes), ele.b(nodes), ele.c(nodes))
+}
+
+fn edge_distance(ele: &Triangle, edge_index: usize, nodes: &Vec<Point2>, p: &Point2) -> f64 {
+    let (n1, n2) = ele.get_edge(edge_index);
+    distance2_to_segment_squared(p, &nodes[n1.0], &nodes[n2.0])
+}
+
+//element or node with its squared distance to p, ordered by the distance.
+struct Candidate<T> {
+    distance: f64,
+    index: T,
+}
+
+impl<T: Ord> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Candidate<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Candidate<T> {}
+
+impl<T: Ord> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Candidate<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Candidate<T> {
+    fn cmp(&self, other: &Candidate<T>) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+//elements sharing the node, found by crossing the edges which contain it.
+pub fn elements_around_node(start_at: T3Index,
+                            elements: &Vec<Triangle>,
+                            node: N2Index)
+                            -> Vec<T3Index> {
+    let mut around = vec![start_at];
+    let mut position = 0;
+
+    while position < around.len() {
+        let ele = &elements[around[position].0];
+        position += 1;
+
+        for edge_index in 0..3 {
+            let (n1, n2) = ele.get_edge(edge_index);
+            if n1 != node && n2 != node {
+                continue;
+            }
+
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(edge_index) {
+                if !around.contains(&neighbor_index) {
+                    around.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    around
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Triangulation2;
+    use types::DuplicatePolicy;
+    use types::triangulation2_test_utils::random_points;
+    use types::triangulation2_test_utils::randomly_constrained;
+
+    //squared distances of the nodes used by the elements to p, nearest first.
+    fn brute_force(triangulation: &Triangulation2, p: &Point2) -> Vec<f64> {
+        let used: BTreeSet<N2Index> = triangulation.elements()
+            .iter()
+            .flat_map(|e| e.nodes().iter().cloned())
+            .collect();
+        let mut distances: Vec<f64> = used.iter()
+            .map(|n| distance2_squared(&triangulation.nodes()[n.0], p))
+            .collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        distances
+    }
+
+    fn assert_nearest_as_brute_force(triangulation: &Triangulation2, p: &Point2) {
+        let distances = brute_force(triangulation, p);
+        let distance = |n: &N2Index| distance2_squared(&triangulation.nodes()[n.0], p);
+
+        assert_eq!(distances[0], distance(&triangulation.nearest_node(p)));
+        assert_eq!(distances[..12].to_vec(),
+                   triangulation.k_nearest(p, 12).iter().map(&distance).collect::<Vec<f64>>());
+
+        let radius = (0.5 * (distances[3] + distances[4])).sqrt();
+        let within = find_nodes_within_radius(T3Index(0),
+                                              triangulation.elements(),
+                                              triangulation.nodes(),
+                                              p,
+                                              radius,
+                                              triangulation.is_restricted());
+        assert_eq!(distances[..4].to_vec(),
+                   within.iter().map(&distance).collect::<Vec<f64>>());
+    }
+
+    #[test]
+    fn finding_nearest_node_from_far_element() {
+        let mut nodes = Vec::new();
+        for i in 0..36 {
+            nodes.push(Point2::new((i % 6) as f64 + 0.01 * (i / 6) as f64,
+                                   (i / 6) as f64 + 0.02 * (i % 6) as f64));
+        }
+
+        let triangulation = Triangulation2::new(&nodes);
+        let elements = triangulation.elements();
+
+        for (index, node) in nodes.iter().enumerate() {
+            let p = Point2::new(node.x + 0.1, node.y - 0.1);
+            for start in 0..elements.len() {
+                assert_eq!(N2Index(index),
+                           find_nearest_node(T3Index(start), elements, &nodes, &p, false));
+            }
+        }
+
+        for q in random_points(13, 20) {
+            let p = Point2::new(12. * q.x - 3., 12. * q.y - 3.);
+            let radius = 1.5;
+            let within: Vec<usize> = (0..nodes.len())
+                .filter(|n| distance2_squared(&nodes[*n], &p) <= radius * radius)
+                .collect();
+            for start in 0..elements.len() {
+                let mut found: Vec<usize> =
+                    find_nodes_within_radius(T3Index(start), elements, &nodes, &p, radius, false)
+                        .iter()
+                        .map(|n| n.0)
+                        .collect();
+                found.sort();
+                assert_eq!(within, found);
+            }
+        }
+    }
+
+    #[test]
+    fn finding_k_nearest_nodes() {
+        let nodes = random_points(11, 500);
+        let triangulation = Triangulation2::new(&nodes);
+
+        for q in random_points(12, 50) {
+            let p = Point2::new(2. * q.x - 0.5, 2. * q.y - 0.5);
+            let mut by_distance: Vec<usize> = (0..nodes.len()).collect();
+            by_distance.sort_by(|a, b| {
+                distance2_squared(&nodes[*a], &p)
+                    .partial_cmp(&distance2_squared(&nodes[*b], &p))
+                    .unwrap()
+            });
+
+            let nearest = find_k_nearest_nodes(T3Index(0),
+                                               triangulation.elements(),
+                                               triangulation.nodes(),
+                                               &p,
+                                               20,
+                                               false);
+            assert_eq!(by_distance[..20].to_vec(),
+                       nearest.iter().map(|n| n.0).collect::<Vec<usize>>());
+        }
+
+        let all = find_k_nearest_nodes(T3Index(0),
+                                       triangulation.elements(),
+                                       triangulation.nodes(),
+                                       &Point2::new(0.5, 0.5),
+                                       1000,
+                                       false);
+        assert_eq!(nodes.len(), all.len());
+        assert!(find_k_nearest_nodes(T3Index(0),
+                                     triangulation.elements(),
+                                     triangulation.nodes(),
+                                     &Point2::new(0.5, 0.5),
+                                     0,
+                                     false)
+            .is_empty());
+    }
+
+    #[test]
+    fn finding_nearest_nodes_after_constraints() {
+        let mut triangulation = randomly_constrained(20);
+
+        for q in random_points(21, 100) {
+            let p = Point2::new(140. * q.x - 20., 140. * q.y - 20.);
+            assert_nearest_as_brute_force(&triangulation, &p);
+        }
+
+        triangulation.set_duplicate_policy(DuplicatePolicy::MergeWithin(0.01));
+        for node_index in (0..300).filter(|i| i % 7 == 0) {
+            let node = triangulation.nodes()[node_index];
+            let p = Point2::new(node.x + 0.005, node.y - 0.005);
+            assert_eq!(N2Index(node_index), triangulation.insert_node(&p));
+        }
+    }
+
+    #[test]
+    fn finding_nearest_nodes_after_flips() {
+        let mut triangulation = Triangulation2::new(&random_points(22, 300));
+        for ele_index in 0..triangulation.elements().len() {
+            let _ = triangulation.try_flip_edge(T3Index(ele_index), ele_index % 3);
+        }
+        let flipped = (0..triangulation.elements().len())
+            .filter(|e| !triangulation.is_locally_delaunay(T3Index(*e), 0))
+            .count();
+        assert!(flipped > 50);
+
+        for q in random_points(23, 100) {
+            let p = Point2::new(1.4 * q.x - 0.2, 1.4 * q.y - 0.2);
+            assert_nearest_as_brute_force(&triangulation, &p);
+        }
+    }
+}
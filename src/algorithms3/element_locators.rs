@@ -1,16 +1,21 @@
 use types::T4Index;
+use types::N3Index;
 
 use types::Tetrahedron;
 use types::Point3;
 
 use math;
-use std::f64;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum LocationResult {
     InElement(T4Index),
-    OnFaces(T4Index, usize, usize),
+    //point lies on the edge between the given nodes of the element.
+    OnEdge(T4Index, N3Index, N3Index),
     OnFace(T4Index, usize),
+    //point lies outside of the triangulation, past the given border face of the element.
+    Outside(T4Index, usize),
+    //point coincides with the given node of the element.
+    OnVertex(T4Index, N3Index),
 }
 
 
@@ -20,74 +25,143 @@ pub fn locate_element_containing(start_lookup_at: T4Index,
                                  nodes: &[Point3],
                                  p: &Point3)
                                  -> LocationResult {
-    let mut ele_index = start_lookup_at;
-
-    loop {
-        let ele: &Tetrahedron = &elements[ele_index.0];
-
-        let mut current_face = 0;
-        let mut on_face_found: Option<usize> = None;
-        let mut on_faces_found: Option<(usize, usize)> = None;
-
-
-        loop {
-            println!("am i cycling here? {:?} ele_index {:?}, p1{:?} p2{:?} p3{:?} p4{:?} p{:?}",
-                     current_face,
-                     ele_index,
-                     elements[ele_index.0].a(nodes),
-                     elements[ele_index.0].b(nodes),
-                     elements[ele_index.0].c(nodes),
-                     elements[ele_index.0].d(nodes),
-                     p);
-            ::std::thread::sleep_ms(1);
-            if current_face == 4 {
-                break;
-            }
+    locate_counting_visited(start_lookup_at, elements, nodes, p).0
+}
 
-            let edge = ele.faces_as_points_tuples(nodes)[current_face];
-
-            match math::side_of_plane(edge.0, edge.1, edge.2, p) {
-                math::SideOfPlane::Left => {
-                    assert!(ele.get_neighbor_from_index(current_face).is_some());
-                    println!("skipping to {:?} from {:?}. jumping from {:?} to {:?}",
-                             ele.faces_as_indices_tuples()[current_face],
-                             ele,
-                             ele_index.0,
-                             ele.get_neighbor_from_index(current_face).unwrap());
-                    ele_index = ele.get_neighbor_from_index(current_face).unwrap();
-                    break;
-                }
-                math::SideOfPlane::OnPlane => {
-                    if on_faces_found.is_some() {
-                        panic!("found node on more than 2 planes.");
-                    }
-                    if let Some(on_face_found) = on_face_found {
-                        on_faces_found = Some((on_face_found, current_face));
-                    } else {
-                        on_face_found = Some(current_face)
-                    }
+//same as locate_element_containing, also returns the number of elements the walk went through.
+//each element tests its faces from a different one, which keeps the walk from going around the
+//same elements forever in a triangulation which is not delaunay, for example after flips. should
+//the walk still pass more elements than there are, all of them are tested one by one.
+pub fn locate_counting_visited(start_lookup_at: T4Index,
+                               elements: &[Tetrahedron],
+                               nodes: &[Point3],
+                               p: &Point3)
+                               -> (LocationResult, usize) {
+    let mut ele_index = start_lookup_at;
+    let mut visited = 0;
+
+    while visited < elements.len() {
+        visited += 1;
+
+        let first_face = (ele_index.0 + visited) % 4;
+        match step(&elements[ele_index.0], ele_index, nodes, p, first_face) {
+            Step::Found(location_result) => return (location_result, visited),
+            Step::Cross(face_index) => {
+                match elements[ele_index.0].get_neighbor_from_index(face_index) {
+                    Some(neighbor_index) => ele_index = neighbor_index,
+                    None => return (LocationResult::Outside(ele_index, face_index), visited),
                 }
-                math::SideOfPlane::Right => (),
             }
-            current_face += 1;
         }
+    }
+
+    let (location_result, scanned) = locate_by_scan(elements, nodes, p);
+    (location_result, visited + scanned)
+}
 
-        if current_face == 4 {
-            if let Some((face1, face2)) = on_faces_found {
-                return LocationResult::OnFaces(ele_index,
-                                               on_faces_found.unwrap().0,
-                                               on_faces_found.unwrap().1);
+//tests the elements one by one with the same exact predicates as the walk, together with the
+//number of tested elements. a point outside of all of them is beyond a border face which has it
+//on its outer side.
+pub fn locate_by_scan(elements: &[Tetrahedron],
+                      nodes: &[Point3],
+                      p: &Point3)
+                      -> (LocationResult, usize) {
+    let mut outside = None;
+
+    for (index, ele) in elements.iter().enumerate() {
+        match step(ele, T4Index(index), nodes, p, 0) {
+            Step::Found(location_result) => return (location_result, index + 1),
+            Step::Cross(_) if outside.is_none() => {
+                outside = (0..4)
+                    .find(|&face_index| {
+                        let face = ele.faces_as_points_tuples(nodes)[face_index];
+                        ele.get_neighbor_from_index(face_index).is_none() &&
+                        math::side_of_plane(face.0, face.1, face.2, p) == math::SideOfPlane::Left
+                    })
+                    .map(|face_index| (T4Index(index), face_index));
             }
+            Step::Cross(_) => (),
+        }
+    }
+
+    let (ele_index, face_index) = outside.unwrap_or((T4Index(0), 0));
+    (LocationResult::Outside(ele_index, face_index), elements.len())
+}
 
-            if let Some(on_edge_found) = on_face_found {
-                return LocationResult::OnFace(ele_index, on_edge_found);
+enum Step {
+    Found(LocationResult),
+    //point lies on the outer side of the face.
+    Cross(usize),
+}
+
+//where the point lies towards the element, its faces are tested starting from first_face.
+fn step(ele: &Tetrahedron,
+        ele_index: T4Index,
+        nodes: &[Point3],
+        p: &Point3,
+        first_face: usize)
+        -> Step {
+    let mut on_faces = [0; 2];
+    let mut on_faces_count = 0;
+
+    for current_face in (first_face..first_face + 4).map(|f| f % 4) {
+        let face = ele.faces_as_points_tuples(nodes)[current_face];
+
+        match math::side_of_plane(face.0, face.1, face.2, p) {
+            math::SideOfPlane::Left => return Step::Cross(current_face),
+            math::SideOfPlane::OnPlane => {
+                if on_faces_count == 2 {
+                    //three faces meet only in their common node.
+                    let node = common_node(ele, [on_faces[0], on_faces[1], current_face]);
+                    return Step::Found(LocationResult::OnVertex(ele_index, node));
+                }
+                on_faces[on_faces_count] = current_face;
+                on_faces_count += 1;
             }
+            math::SideOfPlane::Right => (),
+        }
+    }
 
-            return LocationResult::InElement(ele_index);
+    match on_faces_count {
+        //two faces meet in their common edge.
+        2 => {
+            let (n1, n2) = common_edge(ele, on_faces[0], on_faces[1]);
+            Step::Found(LocationResult::OnEdge(ele_index, n1, n2))
         }
+        1 => Step::Found(LocationResult::OnFace(ele_index, on_faces[0])),
+        _ => Step::Found(LocationResult::InElement(ele_index)),
     }
 }
 
+#[inline]
+fn common_node(ele: &Tetrahedron, face_indexes: [usize; 3]) -> N3Index {
+    let faces = ele.faces_as_indices_tuples();
+
+    *ele.nodes()
+        .iter()
+        .find(|n| {
+            face_indexes.iter().all(|&face_index| {
+                let face = faces[face_index];
+                face.0 == **n || face.1 == **n || face.2 == **n
+            })
+        })
+        .expect("Three faces of a tetrahedron always share a node.")
+}
+
+//nodes of the edge shared by two faces, the smaller index first.
+#[inline]
+fn common_edge(ele: &Tetrahedron, face1: usize, face2: usize) -> (N3Index, N3Index) {
+    let faces = ele.faces_as_indices_tuples();
+    let (first, second) = (faces[face1], faces[face2]);
+    let first = [first.0, first.1, first.2];
+    let mut common = first.iter()
+        .cloned()
+        .filter(|n| *n == second.0 || *n == second.1 || *n == second.2);
+
+    let n1 = common.next().expect("Two faces of a tetrahedron always share an edge.");
+    let n2 = common.next().expect("Two faces of a tetrahedron always share an edge.");
+    if n1 < n2 { (n1, n2) } else { (n2, n1) }
+}
 
 #[cfg(test)]
 mod tests {
@@ -98,6 +172,8 @@ mod tests {
     use types::Triangulation3;
     use types::triangulation3_initiation::create_initial_tetra_set;
     use types::triangulation3_test_utils::get_example_initial_point_set;
+    use types::triangulation3_test_utils::random_points;
+    use types::triangulation3_test_utils::randomly_flipped;
     use std::collections::BTreeSet;
     use super::*;
 
@@ -115,6 +191,20 @@ mod tests {
 
         assert_eq!(LocationResult::InElement(T4Index(0)),
                    locate_element_containing(T4Index(0), &eles, &pts, &point_inside));
+
+        let face = eles[0].faces_as_points_tuples(&pts)[1];
+        let face_center = Point3::new((face.0.x + face.1.x + face.2.x) / 3.,
+                                      (face.0.y + face.1.y + face.2.y) / 3.,
+                                      (face.0.z + face.1.z + face.2.z) / 3.);
+        let point_outside = point_inside + (face_center - point_inside) * 2.;
+
+        assert_eq!(LocationResult::Outside(T4Index(0), 1),
+                   locate_element_containing(T4Index(0), &eles, &pts, &point_outside));
+
+        for i in 0..4 {
+            assert_eq!(LocationResult::OnVertex(T4Index(0), N3Index(i)),
+                       locate_element_containing(T4Index(0), &eles, &pts, &pts[i]));
+        }
     }
 
     #[test]
@@ -178,9 +268,10 @@ mod tests {
                                   .len() {
                 let e = &triangulation.elements()[elem_index];
                 let face = e.faces_as_points_tuples(triangulation.nodes())[face_index];
-                let x = (face.0.x + face.1.x + face.2.x) / 3.;
-                let y = (face.0.y + face.1.y + face.2.y) / 3.;
-                let z = (face.0.z + face.1.z + face.2.z) / 3.;
+                //thirds are rounded off the face, these weights keep the point on it exactly.
+                let x = (2. * face.0.x + face.1.x + face.2.x) / 4.;
+                let y = (2. * face.0.y + face.1.y + face.2.y) / 4.;
+                let z = (2. * face.0.z + face.1.z + face.2.z) / 4.;
 
                 let face_center = Point3::new(x, y, z);
 
@@ -210,9 +301,9 @@ mod tests {
 
                     let edge_center = Point3::new(x, y, z);
 
-                    assert_eq!(LocationResult::OnFaces(T4Index(elem_index),
-                                                       face_index_second,
-                                                       face_index),
+                    assert_eq!(LocationResult::OnEdge(T4Index(elem_index),
+                                                      common_nodes.0,
+                                                      common_nodes.1),
                                locate_element_containing(T4Index(elem_index),
                                                          triangulation.elements(),
                                                          triangulation.nodes(),
@@ -222,6 +313,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn locating_in_flipped_triangulation() {
+        let triangulation = randomly_flipped(3, 300);
+        let (elements, nodes) = (triangulation.elements(), triangulation.nodes());
+
+        for p in random_points(4, 300) {
+            let scanned = locate_by_scan(elements, nodes, &p).0;
+            for start in [0, elements.len() / 2, elements.len() - 1].iter() {
+                match (&scanned, locate_element_containing(T4Index(*start), elements, nodes, &p)) {
+                    //points outside of the hull can be beyond several border faces.
+                    (&LocationResult::Outside(_, _), LocationResult::Outside(_, _)) => (),
+                    (scanned, walked) => assert_eq!(*scanned, walked),
+                }
+            }
+        }
+    }
+
     fn find_common_nodes(face_1: (N3Index, N3Index, N3Index),
                          face_2: (N3Index, N3Index, N3Index))
                          -> (N3Index, N3Index) {
@@ -1,3 +1,4 @@
 pub mod element_locators;
 pub mod sort_3;
 pub mod convex_hull;
+pub mod nearest_node;
@@ -0,0 +1,398 @@
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::BTreeSet;
+use std::f64;
+
+use types::T4Index;
+use types::N3Index;
+
+use types::Tetrahedron;
+use types::Point3;
+
+use math::distance3_squared;
+use math::distance3_to_triangle_squared;
+use math::distance3_to_tetrahedron_squared;
+
+//node nearest to p, see nodes_nearest_first.
+pub fn find_nearest_node(start_lookup_at: T4Index,
+                         elements: &Vec<Tetrahedron>,
+                         nodes: &Vec<Point3>,
+                         p: &Point3,
+                         restricted: bool)
+                         -> N3Index {
+    find_k_nearest_nodes(start_lookup_at, elements, nodes, p, 1, restricted)[0]
+}
+
+//the k nodes nearest to p, nearest first.
+pub fn find_k_nearest_nodes(start_lookup_at: T4Index,
+                            elements: &Vec<Tetrahedron>,
+                            nodes: &Vec<Point3>,
+                            p: &Point3,
+                            k: usize,
+                            restricted: bool)
+                            -> Vec<N3Index> {
+    nodes_nearest_first(start_lookup_at,
+                        elements,
+                        nodes,
+                        p,
+                        k,
+                        f64::INFINITY,
+                        restricted)
+}
+
+//nodes whose distance to p is at most radius, nearest first.
+pub fn find_nodes_within_radius(start_lookup_at: T4Index,
+                                elements: &Vec<Tetrahedron>,
+                                nodes: &Vec<Point3>,
+                                p: &Point3,
+                                radius: f64,
+                                restricted: bool)
+                                -> Vec<N3Index> {
+    nodes_nearest_first(start_lookup_at,
+                        elements,
+                        nodes,
+                        p,
+                        usize::max_value(),
+                        radius * radius,
+                        restricted)
+}
+
+//at most k nodes of the elements not further from p than the square root of
+//max_distance_squared, nearest first. elements are taken by their distance to p from a queue of
+//the neighbors of the elements taken so far. the elements touching a ball around p are connected
+//when the triangulation covers a convex volume, so no node in the ball is missed once the next
+//element lies outside of it, whether the triangulation is delaunay or not. the border of a
+//restricted triangulation can cut the ball into parts, so when the ball reaches the border the
+//search is repeated from all elements with a border face in the ball.
+fn nodes_nearest_first(start_lookup_at: T4Index,
+                       elements: &Vec<Tetrahedron>,
+                       nodes: &Vec<Point3>,
+                       p: &Point3,
+                       k: usize,
+                       max_distance_squared: f64,
+                       restricted: bool)
+                       -> Vec<N3Index> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let search = search_from(&[start_lookup_at], elements, nodes, p, k, max_distance_squared);
+    if !restricted || search.border_distance > search.reach {
+        return search.nearest;
+    }
+
+    let mut starts = vec![start_lookup_at];
+    for (ele_index, ele) in elements.iter().enumerate() {
+        let at_border = (0..4).any(|i| {
+            ele.get_neighbor_from_index(i).is_none() &&
+            face_distance(ele, i, nodes, p) <= search.reach
+        });
+        if at_border {
+            starts.push(T4Index(ele_index));
+        }
+    }
+
+    search_from(&starts, elements, nodes, p, k, max_distance_squared).nearest
+}
+
+//nodes found from the given elements, the squared distance up to which all elements connected to
+//the first one were taken and the squared distance of the nearest border face of the taken
+//elements.
+struct Search {
+    nearest: Vec<N3Index>,
+    reach: f64,
+    border_distance: f64,
+}
+
+fn search_from(starts: &[T4Index],
+               elements: &Vec<Tetrahedron>,
+               nodes: &Vec<Point3>,
+               p: &Point3,
+               k: usize,
+               max_distance_squared: f64)
+               -> Search {
+    let first_distance = element_distance(&elements[starts[0].0], nodes, p);
+    let mut queued = BTreeSet::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts.iter() {
+        if queued.insert(*start) {
+            queue.push(Reverse(Candidate {
+                distance: element_distance(&elements[start.0], nodes, p),
+                index: *start,
+            }));
+        }
+    }
+
+    //the farthest of the nodes found so far is on top, so it is dropped when a nearer one comes.
+    let mut found_nodes = BTreeSet::new();
+    let mut found: BinaryHeap<Candidate<N3Index>> = BinaryHeap::new();
+    let reach = |found: &BinaryHeap<Candidate<N3Index>>| {
+        let bound = match found.peek() {
+            Some(farthest) if found.len() == k => farthest.distance.min(max_distance_squared),
+            _ => max_distance_squared,
+        };
+        //the ball has to reach into the first element, else the elements touching it are not
+        //connected to the first one.
+        bound.max(first_distance * (1. + 1e-9))
+    };
+    let mut border_distance = f64::INFINITY;
+
+    while let Some(Reverse(candidate)) = queue.pop() {
+        if candidate.distance > reach(&found) {
+            break;
+        }
+
+        let ele = &elements[candidate.index.0];
+        for node in ele.nodes().iter() {
+            let distance = distance3_squared(&nodes[node.0], p);
+            if distance <= max_distance_squared && found_nodes.insert(*node) {
+                found.push(Candidate {
+                    distance: distance,
+                    index: *node,
+                });
+                if found.len() > k {
+                    found.pop();
+                }
+            }
+        }
+
+        for i in 0..4 {
+            match ele.get_neighbor_from_index(i) {
+                Some(neighbor_index) => {
+                    if queued.insert(neighbor_index) {
+                        queue.push(Reverse(Candidate {
+                            distance: element_distance(&elements[neighbor_index.0], nodes, p),
+                            index: neighbor_index,
+                        }));
+                    }
+                }
+                None => border_distance = border_distance.min(face_distance(ele, i, nodes, p)),
+            }
+        }
+    }
+
+    Search {
+        reach: reach(&found),
+        nearest: found.into_sorted_vec().into_iter().map(|c| c.index).collect(),
+        border_distance: border_distance,
+    }
+}
+
+fn element_distance(ele: &Tetrahedron, nodes: &Vec<Point3>, p: &Point3) -> f64 {
+    distance3_to_tetrahedron_squared(p, ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes))
+}
+
+fn face_distance(ele: &Tetrahedron, face_index: usize, nodes: &Vec<Point3>, p: &Point3) -> f64 {
+    let (n1, n2, n3) = ele.faces_as_indices_tuples()[face_index];
+    distance3_to_triangle_squared(p, &nodes[n1.0], &nodes[n2.0], &nodes[n3.0])
+}
+
+//element or node with its squared distance to p, ordered by the distance.
+struct Candidate<T> {
+    distance: f64,
+    index: T,
+}
+
+impl<T: Ord> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Candidate<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Candidate<T> {}
+
+impl<T: Ord> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Candidate<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Candidate<T> {
+    fn cmp(&self, other: &Candidate<T>) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+//elements sharing the node, found by crossing the faces which contain it.
+pub fn elements_around_node(start_at: T4Index,
+                            elements: &Vec<Tetrahedron>,
+                            node: N3Index)
+                            -> Vec<T4Index> {
+    let mut around = vec![start_at];
+    let mut position = 0;
+
+    while position < around.len() {
+        let ele = &elements[around[position].0];
+        position += 1;
+
+        for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+            if face.0 != node && face.1 != node && face.2 != node {
+                continue;
+            }
+
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(face_index) {
+                if !around.contains(&neighbor_index) {
+                    around.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    around
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Triangulation3;
+    use types::triangulation3_test_utils::random_points;
+    use types::triangulation3_test_utils::randomly_flipped;
+
+    #[test]
+    fn finding_nearest_node_from_far_element() {
+        let mut nodes = Vec::new();
+        for i in 0..27 {
+            nodes.push(Point3::new((i % 3) as f64 + 0.01 * (i / 9) as f64,
+                                   ((i / 3) % 3) as f64 + 0.02 * (i % 3) as f64,
+                                   (i / 9) as f64 + 0.03 * ((i / 3) % 3) as f64));
+        }
+
+        let triangulation = Triangulation3::new(&nodes);
+        let elements = triangulation.elements();
+
+        for (index, node) in nodes.iter().enumerate() {
+            let p = Point3::new(node.x + 0.1, node.y - 0.1, node.z + 0.05);
+            for start in 0..elements.len() {
+                assert_eq!(N3Index(index),
+                           find_nearest_node(T4Index(start), elements, &nodes, &p, false));
+            }
+        }
+    }
+
+    fn brute_force(nodes: &[Point3], p: &Point3) -> Vec<usize> {
+        let mut by_distance: Vec<usize> = (0..nodes.len()).collect();
+        by_distance.sort_by(|a, b| {
+            distance3_squared(&nodes[*a], p)
+                .partial_cmp(&distance3_squared(&nodes[*b], p))
+                .unwrap()
+        });
+        by_distance
+    }
+
+    //nearest nodes of the triangulation have the same distances to p as the nodes used by the
+    //elements sorted by their distance.
+    fn assert_nearest_as_brute_force(triangulation: &Triangulation3, p: &Point3) {
+        let used: BTreeSet<N3Index> = triangulation.elements()
+            .iter()
+            .flat_map(|e| e.nodes().iter().cloned())
+            .collect();
+        let distance = |n: &N3Index| distance3_squared(&triangulation.nodes()[n.0], p);
+        let mut distances: Vec<f64> = used.iter().map(&distance).collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(distances[0], distance(&triangulation.nearest_node(p)));
+        assert_eq!(distances[..12].to_vec(),
+                   triangulation.k_nearest(p, 12).iter().map(&distance).collect::<Vec<f64>>());
+
+        let radius = (0.5 * (distances[5] + distances[6])).sqrt();
+        assert_eq!(distances[..6].to_vec(),
+                   triangulation.nodes_within_radius(p, radius)
+                       .iter()
+                       .map(&distance)
+                       .collect::<Vec<f64>>());
+    }
+
+    #[test]
+    fn finding_k_nearest_nodes_and_nodes_within_radius() {
+        let nodes = random_points(5, 300);
+        let triangulation = Triangulation3::new(&nodes);
+        let elements = triangulation.elements();
+
+        for (q, r) in random_points(6, 50).into_iter().zip(random_points(7, 50)) {
+            let p = Point3::new(2. * q.x - 0.5, 2. * q.y - 0.5, 2. * q.z - 0.5);
+            let by_distance = brute_force(&nodes, &p);
+            let indexes = |found: Vec<N3Index>| found.iter().map(|n| n.0).collect::<Vec<usize>>();
+
+            assert_eq!(by_distance[0],
+                       find_nearest_node(T4Index(0), elements, &nodes, &p, false).0);
+            assert_eq!(by_distance[..15].to_vec(),
+                       indexes(find_k_nearest_nodes(T4Index(0), elements, &nodes, &p, 15, false)));
+
+            let radius = 0.3 * r.x + distance3_squared(&nodes[by_distance[0]], &p).sqrt();
+            let within: Vec<usize> = by_distance.iter()
+                .cloned()
+                .filter(|n| distance3_squared(&nodes[*n], &p) <= radius * radius)
+                .collect();
+            let found = find_nodes_within_radius(T4Index(0), elements, &nodes, &p, radius, false);
+            assert_eq!(within, indexes(found));
+        }
+
+        let all = find_k_nearest_nodes(T4Index(0), elements, &nodes, &nodes[0], 1000, false);
+        assert_eq!(nodes.len(), all.len());
+        assert!(find_k_nearest_nodes(T4Index(0), elements, &nodes, &nodes[0], 0, false).is_empty());
+        let far = Point3::new(9., 9., 9.);
+        assert!(find_nodes_within_radius(T4Index(0), elements, &nodes, &far, 1., false).is_empty());
+    }
+
+    #[test]
+    fn finding_nearest_nodes_after_flip23() {
+        let triangulation = randomly_flipped(8, 300);
+
+        for q in random_points(9, 100) {
+            let p = Point3::new(1.4 * q.x - 0.2, 1.4 * q.y - 0.2, 1.4 * q.z - 0.2);
+            assert_nearest_as_brute_force(&triangulation, &p);
+        }
+    }
+
+    #[test]
+    fn finding_nearest_nodes_after_flip44() {
+        //nodes lie in planes of constant z, so the edges in the planes can be flipped in them.
+        let nodes: Vec<Point3> = random_points(10, 150)
+            .iter()
+            .enumerate()
+            .map(|(i, q)| Point3::new(i as f64 % 6. + 0.6 * q.x,
+                                      (i / 6) as f64 % 5. + 0.6 * q.y,
+                                      (i / 30) as f64))
+            .collect();
+        let mut triangulation = Triangulation3::new(&nodes);
+
+        let edges = [(0, 1, 2, 3),
+                     (0, 2, 1, 3),
+                     (0, 3, 1, 2),
+                     (1, 2, 0, 3),
+                     (1, 3, 0, 2),
+                     (2, 3, 0, 1)];
+        let mut flipped = 0;
+        for ele_index in 0..triangulation.elements().len() {
+            let ele = triangulation.elements()[ele_index].clone();
+            let n = ele.nodes();
+            for &(i, j, k, l) in edges.iter() {
+                //ring around the edge goes from k over l to the node across their face.
+                let across = match ele.get_neighbor_for_indices(n[i], n[j], n[l]) {
+                    Some(neighbor_index) => neighbor_index,
+                    None => continue,
+                };
+                let opposite = *triangulation.elements()[across.0]
+                    .nodes()
+                    .iter()
+                    .find(|m| ![n[i], n[j], n[l]].contains(m))
+                    .unwrap();
+                let edge = (n[i], n[j]);
+                if triangulation.try_flip44(T4Index(ele_index), edge, (n[k], opposite)).is_ok() {
+                    flipped += 1;
+                    break;
+                }
+            }
+        }
+        assert!(flipped > 10);
+
+        for q in random_points(11, 100) {
+            let p = Point3::new(7. * q.x - 0.5, 6. * q.y - 0.5, 6. * q.z - 0.5);
+            assert_nearest_as_brute_force(&triangulation, &p);
+        }
+    }
+}
@@ -1,22 +1,26 @@
 use types::Triangulation2;
 
+use std::io;
 use std::io::{Write, BufWriter};
 use std::fs::File;
 
-fn write_2d_to_abaqus_format_impl<W: Write>(buf: BufWriter<W>, triangulation: &Triangulation2) {
+fn write_2d_to_abaqus_format_impl<W: Write>(buf: BufWriter<W>,
+                                            triangulation: &Triangulation2)
+                                            -> io::Result<()> {
     AbaqusWriter {
             writer: buf,
             triangulation: triangulation,
         }
-        .write();
+        .write()
 }
 
-pub fn write_2d_to_abaqus_format(path_to_file: &str, triangulation: &Triangulation2) {
-    let f = File::create(path_to_file).expect(&
-        format!("write_to_abaqus_format failed while opening file: {}", path_to_file));
+pub fn write_2d_to_abaqus_format(path_to_file: &str,
+                                 triangulation: &Triangulation2)
+                                 -> io::Result<()> {
+    let f = File::create(path_to_file)?;
 
     let buf = BufWriter::new(f);
-    write_2d_to_abaqus_format_impl(buf, triangulation);
+    write_2d_to_abaqus_format_impl(buf, triangulation)
 }
 
 
@@ -26,62 +30,67 @@ struct AbaqusWriter<'a, W: Write> {
 }
 
 impl<'a, W: Write> AbaqusWriter<'a, W> {
-    fn write(mut self) {
-        self.write_header();
-        self.write_nodes();
-        self.write_elements();
-        self.write_elset();
-        self.write_footer();
+    fn write(mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.write_nodes()?;
+        self.write_elements()?;
+        self.write_elset()?;
+        self.write_footer()?;
+        self.writer.flush()
     }
 
-    fn write_header(&mut self) {
-        let _ = self.writer.write("*Part, name=PART-1\n".as_bytes());
+    fn write_header(&mut self) -> io::Result<()> {
+        self.writer.write_all("*Part, name=PART-1\n".as_bytes())
     }
 
-    fn write_nodes(&mut self) {
-        let _ = self.writer.write("*Node\n".as_bytes());
+    fn write_nodes(&mut self) -> io::Result<()> {
+        self.writer.write_all("*Node\n".as_bytes())?;
         for i in 0..self.triangulation.nodes().len() {
             let node = &self.triangulation.nodes()[i];
-            let _ = self.writer.write(format!("{},\t{},\t{}\n", i + 1, node.x, node.y).as_bytes());
+            self.writer.write_all(format!("{},\t{},\t{}\n", i + 1, node.x, node.y).as_bytes())?;
         }
+
+        Ok(())
     }
 
-    fn write_elements(&mut self) {
-        let _ = self.writer.write("*Element, type=CPE3\n".as_bytes());
+    fn write_elements(&mut self) -> io::Result<()> {
+        self.writer.write_all("*Element, type=CPE3\n".as_bytes())?;
         for i in 0..self.triangulation.elements().len() {
             let element = &self.triangulation.elements()[i];
             //abaqus uses ccw order instead of cw, writing nodes in order [cab] is required.
-            let _ = self.writer.write(format!("{},\t{},\t{},\t{}\n",
-                                              i + 1,
-                                              element.index_c().0 + 1,
-                                              element.index_b().0 + 1,
-                                              element.index_a().0 + 1)
-                                              .as_bytes());
+            self.writer.write_all(format!("{},\t{},\t{},\t{}\n",
+                                          i + 1,
+                                          element.index_c().0 + 1,
+                                          element.index_b().0 + 1,
+                                          element.index_a().0 + 1)
+                                      .as_bytes())?;
         }
+
+        Ok(())
     }
 
-    fn write_elset(&mut self) {
-        let _ = self.writer.write("*Elset, elset=M_1\n".as_bytes());
+    fn write_elset(&mut self) -> io::Result<()> {
+        self.writer.write_all("*Elset, elset=M_1\n".as_bytes())?;
 
         let eles = &self.triangulation.elements();
         let mut written = 0;
         while written < eles.len() {
             if written > 0 {
-                let _ = self.writer.write(",".as_bytes());
+                self.writer.write_all(",".as_bytes())?;
             }
             if written % 10 == 0 && written != 0 {
-                let _ = self.writer.write("\n".as_bytes());
+                self.writer.write_all("\n".as_bytes())?;
             }
 
-            let _ = self.writer.write((written + 1).to_string().as_bytes());
+            self.writer.write_all((written + 1).to_string().as_bytes())?;
             written += 1;
         }
 
-        let _ = self.writer.write("\n".as_bytes());
+        self.writer.write_all("\n".as_bytes())
     }
 
-    fn write_footer(&mut self) {
-        let _ = self.writer.write("*Solid Section, elset=M_1, material=M_1
+    fn write_footer(&mut self) -> io::Result<()> {
+        self.writer.write_all("*Solid Section, elset=M_1, material=M_1
 1.,
 *End Part
 **
@@ -94,7 +103,7 @@ impl<'a, W: Write> AbaqusWriter<'a, W> {
 *End Instance
 **
 *End Assembly\n"
-                                          .as_bytes());
+                                  .as_bytes())
     }
 }
 
@@ -116,7 +125,8 @@ mod tests {
         //let tr
         let mut s = String::new();
 
-        write_2d_to_abaqus_format_impl(BufWriter::new(unsafe { s.as_mut_vec() }), &triangulation);
+        write_2d_to_abaqus_format_impl(BufWriter::new(unsafe { s.as_mut_vec() }), &triangulation)
+            .unwrap();
 
         let expected_file = "*Part, name=PART-1
 *Node
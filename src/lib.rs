@@ -7,6 +7,8 @@ extern crate libc;
 
 #[cfg(test)]
 extern crate quickcheck;
+#[cfg(test)]
+extern crate rand;
 
 extern crate cgmath;
 
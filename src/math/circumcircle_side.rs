@@ -1,7 +1,6 @@
 use types::Point2;
-use cgmath::Matrix2;
-use cgmath::SquareMatrix;
-use std::mem;
+use math::incircle;
+use math::orient2d;
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum CircleSide {
@@ -9,90 +8,78 @@ pub enum CircleSide {
     Outside,
 }
 
+//points lying exactly on the circle are treated as outside of it. a degenerate triangle with
+//p, q and r on one line has no circle, every point is outside of it.
+#[inline]
 pub fn circumcircle_side(p: &Point2, q: &Point2, r: &Point2, t: &Point2) -> CircleSide {
-    //TODO the algorithm is taken from CGAL for now. rewrite.
-    let qpx = q.x - p.x;
-    let qpy = q.y - p.y;
-    let rpx = r.x - p.x;
-    let rpy = r.y - p.y;
-    let tpx = t.x - p.x;
-    let tpy = t.y - p.y;
-
-    let tqx = t.x - q.x;
-    let tqy = t.y - q.y;
-    let rqx = r.x - q.x;
-    let rqy = r.y - q.y;
-
-    let det = Matrix2::new(qpx * tpy - qpy * tpx,
-                           tpx * tqx + tpy * tqy,
-                           qpx * rpy - qpy * rpx,
-                           rpx * rqx + rpy * rqy)
-            .determinant();
-
-    let mut maxx = qpx.abs();
-    let mut maxy = qpy.abs();
-
-    let arpx = rpx.abs();
-    let arpy = rpy.abs();
-
-    let atqx = tqx.abs();
-    let atqy = tqy.abs();
-
-    let atpx = tpx.abs();
-    let atpy = tpy.abs();
-
-    let arqx = rqx.abs();
-    let arqy = rqy.abs();
-
-    if maxx < arpx {
-        maxx = arpx
-    };
-    if maxx < atpx {
-        maxx = atpx
-    };
-    if maxx < atqx {
-        maxx = atqx
-    };
-    if maxx < arqx {
-        maxx = arqx
-    };
-
-    if maxy < arpy {
-        maxy = arpy
-    };
-    if maxy < atpy {
-        maxy = atpy
-    };
-    if maxy < atqy {
-        maxy = atqy
-    };
-    if maxy < arqy {
-        maxy = arqy
-    };
-
-    if maxx > maxy {
-        mem::swap(&mut maxx, &mut maxy)
-    };
-
-    if maxx < 1e-73 {
-        if maxx == 0. {
-            //should be on boundary here, treat this case as OK.
+    //incircle expects counterclockwise order, triangles are kept clockwise.
+    let side = incircle(p, q, r, t) * orientation_sign(p, q, r);
+
+    if side > 0. {
+        CircleSide::Inside
+    } else {
+        CircleSide::Outside
+    }
+}
+
+//same as circumcircle_side, but points on the circle are decided as if the lifted coordinate
+//x^2 + y^2 of every point was raised by an infinitesimal growing with its index, so the result
+//only depends on the indexes and never on the order in which the points are given.
+pub fn circumcircle_side_perturbed(p: &Point2,
+                                   q: &Point2,
+                                   r: &Point2,
+                                   t: &Point2,
+                                   indexes: [usize; 4])
+                                   -> CircleSide {
+    let orientation = orientation_sign(p, q, r);
+    if orientation == 0. {
+        return CircleSide::Outside;
+    }
+
+    let side = incircle(p, q, r, t) * orientation;
+
+    if side > 0. {
+        return CircleSide::Inside;
+    } else if side < 0. {
+        return CircleSide::Outside;
+    }
+
+    //the point with the highest index is perturbed the most, so it decides first. raising one
+    //of p, q or r pushes the circle out on its side of the other two, raising t pushes t out.
+    let mut order = [0, 1, 2, 3];
+    order.sort_by_key(|&position| indexes[position]);
+
+    for &position in order.iter().rev() {
+        if position == 3 {
             return CircleSide::Outside;
         }
-    } else if maxy < 1e76 {
-        //this is tricky one. I am assuming here that if everything is so close to the edge
-        // then its OUTSIDE the circle.
-        if det >= -1e-6 {
-            return CircleSide::Outside;
-        } else {
+
+        let mut substituted = [p, q, r];
+        substituted[position] = t;
+
+        let side = orient2d(substituted[0], substituted[1], substituted[2]) * orientation;
+        if side > 0. {
             return CircleSide::Inside;
+        } else if side < 0. {
+            return CircleSide::Outside;
         }
     }
 
-    panic!("which_side_of_circumcircle unexpected maxx{} maxy{} det{}",
-           maxx,
-           maxy,
-           det);
+    CircleSide::Outside
+}
+
+//1 or -1 by the orientation of the points, 0 when they lie on one line. signum would give 1
+//for 0 too.
+#[inline]
+fn orientation_sign(p: &Point2, q: &Point2, r: &Point2) -> f64 {
+    let orientation = orient2d(p, q, r);
+    if orientation > 0. {
+        1.
+    } else if orientation < 0. {
+        -1.
+    } else {
+        0.
+    }
 }
 
 #[cfg(test)]
@@ -144,6 +131,51 @@ mod tests {
                                      &Point2::new(1.1, 1.1)));
     }
 
+    #[test]
+    fn testing_perturbed_square() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(1., 0.),
+                     Point2::new(1., 1.),
+                     Point2::new(0., 1.)];
+        let side = |a: usize, b: usize, c: usize, t: usize| {
+            circumcircle_side_perturbed(&nodes[a], &nodes[b], &nodes[c], &nodes[t], [a, b, c, t])
+        };
+
+        assert_eq!(CircleSide::Outside,
+                   circumcircle_side(&nodes[0], &nodes[1], &nodes[2], &nodes[3]));
+
+        //exactly one of the diagonals gives delaunay triangles, whatever the order of nodes.
+        assert_eq!(CircleSide::Outside, side(0, 1, 2, 3));
+        assert_eq!(CircleSide::Outside, side(2, 1, 0, 3));
+        assert_eq!(CircleSide::Outside, side(0, 2, 3, 1));
+        assert_eq!(CircleSide::Outside, side(3, 0, 2, 1));
+
+        assert_eq!(CircleSide::Inside, side(1, 2, 3, 0));
+        assert_eq!(CircleSide::Inside, side(3, 2, 1, 0));
+        assert_eq!(CircleSide::Inside, side(0, 1, 3, 2));
+        assert_eq!(CircleSide::Inside, side(1, 0, 3, 2));
+    }
+
+    #[test]
+    fn testing_points_on_circle() {
+        let (p, q, r) = (Point2::new(5., 0.), Point2::new(0., 5.), Point2::new(-5., 0.));
+
+        for t in [Point2::new(3., 4.), Point2::new(-4., -3.), Point2::new(0., -5.)].iter() {
+            assert_eq!(CircleSide::Outside, circumcircle_side(&p, &q, &r, t));
+            assert_eq!(CircleSide::Outside, circumcircle_side(&r, &q, &p, t));
+        }
+    }
+
+    #[test]
+    fn testing_degenerate_triangle() {
+        let (p, q, r) = (Point2::new(0., 0.), Point2::new(1., 1.), Point2::new(2., 2.));
+
+        for t in [Point2::new(1., 0.), Point2::new(0., 1.), Point2::new(3., 3.)].iter() {
+            assert_eq!(CircleSide::Outside, circumcircle_side(&p, &q, &r, t));
+            assert_eq!(CircleSide::Outside,
+                       circumcircle_side_perturbed(&p, &q, &r, t, [0, 1, 2, 3]));
+        }
+    }
 
     #[quickcheck]
     fn quick_check_test(x: f64, y: f64, r: f64) -> bool {
@@ -167,6 +199,14 @@ mod tests {
 
         for i in 1..20 {
             let multiplier = 0.1 * i as f64;
+            if i == 10 {
+                //rotated points are rounded off the circle to either side of it, the nodes
+                //lie exactly on it.
+                for node in [p0, p1, p2].iter() {
+                    assert_eq!(CircleSide::Outside, circumcircle_side(&p0, &p1, &p2, node));
+                }
+                continue;
+            }
 
             let mut rotated_point = Point2::new(r * multiplier, 0.);
 
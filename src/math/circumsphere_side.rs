@@ -1,9 +1,6 @@
 use types::Point3;
-use cgmath::Matrix3;
-use cgmath::Matrix4;
-use cgmath::SquareMatrix;
-use std::mem;
-use types::fp;
+use math::insphere;
+use math::orient3d;
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum SphereSide {
@@ -11,107 +8,78 @@ pub enum SphereSide {
     Outside,
 }
 
-//todo rewrite this.
+//points exactly on the sphere are outside. p, q, r and s can be given in any order. a flat
+//tetrahedron has no sphere, every point is outside of it.
 pub fn circumsphere_side(p: &Point3, q: &Point3, r: &Point3, s: &Point3, t: &Point3) -> SphereSide {
-    let ptx = p.x - t.x;
-    let pty = p.y - t.y;
-    let ptz = p.z - t.z;
-    let pt2 = ptx * ptx + pty * pty + ptz * ptz;
-
-    let qtx = q.x - t.x;
-    let qty = q.y - t.y;
-    let qtz = q.z - t.z;
-    let qt2 = qtx * qtx + qty * qty + qtz * qtz;
-
-    let rtx = r.x - t.x;
-    let rty = r.y - t.y;
-    let rtz = r.z - t.z;
-    let rt2 = rtx * rtx + rty * rty + rtz * rtz;
-
-    let stx = s.x - t.x;
-    let sty = s.y - t.y;
-    let stz = s.z - t.z;
-    let st2 = stx * stx + sty * sty + stz * stz;
-
-    let mut maxx = ptx.abs();
-    let mut maxy = pty.abs();
-    let mut maxz = ptz.abs();
-
-    let aqtx = qtx.abs();
-    let artx = rtx.abs();
-    let astx = stx.abs();
-
-    let aqty = qty.abs();
-    let arty = rty.abs();
-    let asty = sty.abs();
-
-    let aqtz = qtz.abs();
-    let artz = rtz.abs();
-    let astz = stz.abs();
-
-    if maxx < aqtx {
-        maxx = aqtx
-    };
-    if maxx < artx {
-        maxx = artx
-    };
-    if maxx < astx {
-        maxx = astx
-    };
-
-    if maxy < aqty {
-        maxy = aqty
-    };
-    if maxy < arty {
-        maxy = arty
-    };
-    if maxy < asty {
-        maxy = asty
-    };
-
-    if maxz < aqtz {
-        maxz = aqtz
-    };
-    if maxz < artz {
-        maxz = artz
-    };
-    if maxz < astz {
-        maxz = astz
-    };
-
-    let mut eps = 1e-12 * maxx * maxy * maxz;
-    if maxx > maxz {
-        ::std::mem::swap(&mut maxx, &mut maxz);
+    let det = insphere(p, q, r, s, t) * orientation_sign(p, q, r, s);
+
+    if det > 0. {
+        SphereSide::Inside
+    } else {
+        SphereSide::Outside
     }
-    if maxy > maxz {
-        ::std::mem::swap(&mut maxy, &mut maxz);
-    } else if maxy < maxx {
-        ::std::mem::swap(&mut maxx, &mut maxy);
+}
+
+//same as circumsphere_side, but points on the sphere are decided as if the lifted coordinate
+//x^2 + y^2 + z^2 of every point was raised by an infinitesimal growing with its index, so the
+//result only depends on the indexes and never on the order in which the points are given.
+pub fn circumsphere_side_perturbed(p: &Point3,
+                                   q: &Point3,
+                                   r: &Point3,
+                                   s: &Point3,
+                                   t: &Point3,
+                                   indexes: [usize; 5])
+                                   -> SphereSide {
+    let orientation = orientation_sign(p, q, r, s);
+    if orientation == 0. {
+        return SphereSide::Outside;
     }
 
-    let det = Matrix4::new(ptx,
-                           pty,
-                           ptz,
-                           pt2,
-                           rtx,
-                           rty,
-                           rtz,
-                           rt2,
-                           qtx,
-                           qty,
-                           qtz,
-                           qt2,
-                           stx,
-                           sty,
-                           stz,
-                           st2)
-            .determinant();
-
-    eps *= maxz * maxz;
-    if det > eps {
+    let det = insphere(p, q, r, s, t) * orientation;
+
+    if det > 0. {
         return SphereSide::Inside;
+    } else if det < 0. {
+        return SphereSide::Outside;
+    }
+
+    //the point with the highest index is perturbed the most, so it decides first. raising one
+    //of p, q, r or s pushes the sphere out on its side of the other three, raising t pushes t out.
+    let mut order = [0, 1, 2, 3, 4];
+    order.sort_by_key(|&position| indexes[position]);
+
+    for &position in order.iter().rev() {
+        if position == 4 {
+            return SphereSide::Outside;
+        }
+
+        let mut substituted = [p, q, r, s];
+        substituted[position] = t;
+
+        let det = orient3d(substituted[0], substituted[1], substituted[2], substituted[3]) *
+                  orientation;
+        if det > 0. {
+            return SphereSide::Inside;
+        } else if det < 0. {
+            return SphereSide::Outside;
+        }
+    }
+
+    SphereSide::Outside
+}
+
+//1 or -1 by the orientation of the points, 0 when they lie on one plane. signum would give 1
+//for 0 too.
+#[inline]
+fn orientation_sign(p: &Point3, q: &Point3, r: &Point3, s: &Point3) -> f64 {
+    let orientation = orient3d(p, q, r, s);
+    if orientation > 0. {
+        1.
+    } else if orientation < 0. {
+        -1.
+    } else {
+        0.
     }
-    return SphereSide::Outside;
 }
 
 #[cfg(test)]
@@ -142,6 +110,73 @@ mod circumsphere_side {
         assert!(tetra.is_point_in_circumsphere(&center, &nodes));
     }
 
+    #[test]
+    fn perturbed_cube_test() {
+        let nodes = vec![Point3::new(0., 0., 0.),
+                         Point3::new(1., 0., 0.),
+                         Point3::new(0., 1., 0.),
+                         Point3::new(0., 0., 1.),
+                         Point3::new(1., 1., 1.)];
+        let side = |a: usize, b: usize, c: usize, d: usize, t: usize| {
+            circumsphere_side_perturbed(&nodes[a],
+                                        &nodes[b],
+                                        &nodes[c],
+                                        &nodes[d],
+                                        &nodes[t],
+                                        [a, b, c, d, t])
+        };
+
+        assert_eq!(SphereSide::Outside,
+                   circumsphere_side(&nodes[0], &nodes[1], &nodes[2], &nodes[3], &nodes[4]));
+
+        //tetras on both sides of face 1 2 3 are delaunay, whatever the order of nodes.
+        assert_eq!(SphereSide::Outside, side(0, 1, 2, 3, 4));
+        assert_eq!(SphereSide::Outside, side(3, 1, 2, 0, 4));
+        assert_eq!(SphereSide::Outside, side(1, 2, 3, 4, 0));
+        assert_eq!(SphereSide::Outside, side(4, 2, 3, 1, 0));
+
+        //tetras around the diagonal 0 4 are not.
+        assert_eq!(SphereSide::Inside, side(0, 1, 2, 4, 3));
+        assert_eq!(SphereSide::Inside, side(2, 1, 0, 4, 3));
+        assert_eq!(SphereSide::Inside, side(0, 4, 2, 3, 1));
+    }
+
+    #[test]
+    fn points_on_sphere_test() {
+        let nodes = [Point3::new(3., 0., 0.),
+                     Point3::new(0., 3., 0.),
+                     Point3::new(0., 0., 3.),
+                     Point3::new(-3., 0., 0.)];
+
+        for t in [Point3::new(1., 2., 2.), Point3::new(2., -1., -2.), Point3::new(-2., -2., 1.)]
+            .iter() {
+            assert_eq!(SphereSide::Outside,
+                       circumsphere_side(&nodes[0], &nodes[1], &nodes[2], &nodes[3], t));
+            assert_eq!(SphereSide::Outside,
+                       circumsphere_side(&nodes[1], &nodes[0], &nodes[2], &nodes[3], t));
+        }
+    }
+
+    #[test]
+    fn flat_tetrahedron_test() {
+        let nodes = [Point3::new(0., 0., 0.),
+                     Point3::new(1., 0., 0.),
+                     Point3::new(0., 1., 0.),
+                     Point3::new(1., 1., 0.)];
+
+        for t in [Point3::new(0.5, 0.5, 0.1), Point3::new(0.5, 0.5, 0.)].iter() {
+            assert_eq!(SphereSide::Outside,
+                       circumsphere_side(&nodes[0], &nodes[1], &nodes[2], &nodes[3], t));
+            assert_eq!(SphereSide::Outside,
+                       circumsphere_side_perturbed(&nodes[0],
+                                                   &nodes[1],
+                                                   &nodes[2],
+                                                   &nodes[3],
+                                                   t,
+                                                   [0, 1, 2, 3, 4]));
+        }
+    }
+
     #[quickcheck]
     fn quick_check_test(x: f64, y: f64, z: f64, r: f64) {
         if r < 0.00001 {
@@ -191,6 +226,14 @@ mod circumsphere_side {
 
         for i in 1..10 {
             let multiplier = 0.2 * i as f64;
+            if i == 5 {
+                //rotated points are rounded off the sphere to either side of it, the nodes
+                //lie exactly on it.
+                for node in nodes.iter() {
+                    assert_eq!(SphereSide::Outside, circumsphere_side(&a, &b, &c, &d, node));
+                }
+                continue;
+            }
 
             let rotated_point = Point3::new(r * multiplier, r * multiplier, r * multiplier);
 
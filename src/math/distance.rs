@@ -1,5 +1,11 @@
+use types::Point2;
 use types::Point3;
 
+use math::orient2d;
+use math::orient3d;
+
+use std::f64;
+
 #[inline]
 fn squared_euclidean(a: &[f64], b: &[f64]) -> f64 {
     debug_assert!(a.len() == b.len());
@@ -10,11 +16,114 @@ fn squared_euclidean(a: &[f64], b: &[f64]) -> f64 {
         .sum()
 }
 
+#[inline]
+pub fn distance2_squared(l: &Point2, r: &Point2) -> f64 {
+    return squared_euclidean(&[l.x, l.y], &[r.x, r.y]);
+}
+
 #[inline]
 pub fn distance3_squared(l: &Point3, r: &Point3) -> f64 {
     return squared_euclidean(&[l.x, l.y, l.z], &[r.x, r.y, r.z]);
 }
 
+//squared distance from p to the segment between a and b.
+pub fn distance2_to_segment_squared(p: &Point2, a: &Point2, b: &Point2) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0. {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_squared).max(0.).min(1.)
+    } else {
+        0.
+    };
+
+    distance2_squared(p, &Point2::new(a.x + t * dx, a.y + t * dy))
+}
+
+//squared distance from p to the triangle abc, zero inside of it.
+pub fn distance2_to_triangle_squared(p: &Point2, a: &Point2, b: &Point2, c: &Point2) -> f64 {
+    let sides = [orient2d(a, b, p), orient2d(b, c, p), orient2d(c, a, p)];
+    if sides.iter().all(|s| *s >= 0.) || sides.iter().all(|s| *s <= 0.) {
+        return 0.;
+    }
+
+    distance2_to_segment_squared(p, a, b)
+        .min(distance2_to_segment_squared(p, b, c))
+        .min(distance2_to_segment_squared(p, c, a))
+}
+
+//squared distance from p to the segment between a and b.
+pub fn distance3_to_segment_squared(p: &Point3, a: &Point3, b: &Point3) -> f64 {
+    let ab = difference(b, a);
+    let length_squared = dot(&ab, &ab);
+    let t = if length_squared > 0. {
+        (dot(&difference(p, a), &ab) / length_squared).max(0.).min(1.)
+    } else {
+        0.
+    };
+
+    distance3_squared(p, &Point3::new(a.x + t * ab[0], a.y + t * ab[1], a.z + t * ab[2]))
+}
+
+//squared distance from p to the triangle abc. when p projected to the plane of the triangle lies
+//in it, the distance is the one to the plane, otherwise to the nearest edge.
+pub fn distance3_to_triangle_squared(p: &Point3, a: &Point3, b: &Point3, c: &Point3) -> f64 {
+    let normal = cross(&difference(b, a), &difference(c, a));
+    let normal_squared = dot(&normal, &normal);
+    let above = dot(&difference(p, a), &normal);
+
+    if normal_squared > 0. {
+        let projected = Point3::new(p.x - above / normal_squared * normal[0],
+                                    p.y - above / normal_squared * normal[1],
+                                    p.z - above / normal_squared * normal[2]);
+        let inside = [(a, b), (b, c), (c, a)].iter().all(|&(from, to)| {
+            let side = cross(&difference(to, from), &difference(&projected, from));
+            dot(&side, &normal) >= 0.
+        });
+        if inside {
+            return above * above / normal_squared;
+        }
+    }
+
+    distance3_to_segment_squared(p, a, b)
+        .min(distance3_to_segment_squared(p, b, c))
+        .min(distance3_to_segment_squared(p, c, a))
+}
+
+//squared distance from p to the tetrahedron abcd, zero inside of it.
+pub fn distance3_to_tetrahedron_squared(p: &Point3,
+                                        a: &Point3,
+                                        b: &Point3,
+                                        c: &Point3,
+                                        d: &Point3)
+                                        -> f64 {
+    let faces = [(a, b, c, d), (a, b, d, c), (a, c, d, b), (b, c, d, a)];
+    let inside = orient3d(a, b, c, d) != 0. &&
+                 faces.iter().all(|&(x, y, z, opposite)| {
+        orient3d(x, y, z, p) * orient3d(x, y, z, opposite) >= 0.
+    });
+    if inside {
+        return 0.;
+    }
+
+    faces.iter()
+        .map(|&(x, y, z, _)| distance3_to_triangle_squared(p, x, y, z))
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[inline]
+fn difference(l: &Point3, r: &Point3) -> [f64; 3] {
+    [l.x - r.x, l.y - r.y, l.z - r.z]
+}
+
+#[inline]
+fn dot(l: &[f64; 3], r: &[f64; 3]) -> f64 {
+    l[0] * r[0] + l[1] * r[1] + l[2] * r[2]
+}
+
+#[inline]
+fn cross(l: &[f64; 3], r: &[f64; 3]) -> [f64; 3] {
+    [l[1] * r[2] - l[2] * r[1], l[2] * r[0] - l[0] * r[2], l[0] * r[1] - l[1] * r[0]]
+}
 
 #[cfg(test)]
 mod math_distance_tests {
@@ -31,4 +140,52 @@ mod math_distance_tests {
         assert_eq!(12.0, distance3_squared(&zero, &two));
         assert_eq!(3.0, distance3_squared(&one, &two));
     }
+
+    #[test]
+    fn distance2_test() {
+        let zero = Point2::new(0., 0.);
+
+        assert_eq!(0.0, distance2_squared(&zero, &zero));
+        assert_eq!(25.0, distance2_squared(&zero, &Point2::new(3., -4.)));
+    }
+
+    #[test]
+    fn distance2_to_segment_and_triangle_test() {
+        let a = Point2::new(0., 0.);
+        let b = Point2::new(4., 0.);
+        let c = Point2::new(0., 4.);
+
+        assert_eq!(4.0, distance2_to_segment_squared(&Point2::new(1., 2.), &a, &b));
+        assert_eq!(5.0, distance2_to_segment_squared(&Point2::new(-1., 2.), &a, &b));
+        assert_eq!(1.0, distance2_to_segment_squared(&Point2::new(5., 0.), &a, &b));
+
+        assert_eq!(0.0, distance2_to_triangle_squared(&Point2::new(1., 1.), &a, &b, &c));
+        assert_eq!(0.0, distance2_to_triangle_squared(&Point2::new(1., 1.), &a, &c, &b));
+        assert_eq!(0.0, distance2_to_triangle_squared(&Point2::new(2., 0.), &a, &b, &c));
+        assert_eq!(8.0, distance2_to_triangle_squared(&Point2::new(4., 4.), &a, &b, &c));
+        assert_eq!(1.0, distance2_to_triangle_squared(&Point2::new(-1., 3.), &a, &b, &c));
+    }
+
+    #[test]
+    fn distance3_to_segment_triangle_and_tetrahedron_test() {
+        let a = Point3::new(0., 0., 0.);
+        let b = Point3::new(4., 0., 0.);
+        let c = Point3::new(0., 4., 0.);
+        let d = Point3::new(0., 0., 4.);
+
+        assert_eq!(4.0, distance3_to_segment_squared(&Point3::new(1., 2., 0.), &a, &b));
+        assert_eq!(6.0, distance3_to_segment_squared(&Point3::new(-1., 2., 1.), &a, &b));
+
+        assert_eq!(9.0, distance3_to_triangle_squared(&Point3::new(1., 1., 3.), &a, &b, &c));
+        assert_eq!(9.0, distance3_to_triangle_squared(&Point3::new(1., 1., -3.), &a, &c, &b));
+        assert_eq!(5.0, distance3_to_triangle_squared(&Point3::new(-1., 1., 2.), &a, &b, &c));
+
+        let inside = Point3::new(1., 1., 1.);
+        assert_eq!(0.0, distance3_to_tetrahedron_squared(&inside, &a, &b, &c, &d));
+        assert_eq!(0.0, distance3_to_tetrahedron_squared(&inside, &b, &a, &c, &d));
+        assert_eq!(4.0,
+                   distance3_to_tetrahedron_squared(&Point3::new(1., 1., -2.), &a, &b, &c, &d));
+        assert_eq!(3.0,
+                   distance3_to_tetrahedron_squared(&Point3::new(5., 1., 1.), &a, &b, &c, &d));
+    }
 }
@@ -0,0 +1,256 @@
+//floating point expansion arithmetic following J. R. Shewchuk, "Adaptive Precision
+//Floating-Point Arithmetic and Fast Robust Geometric Predicates". an expansion is a sum of
+//non-overlapping f64 components, sorted by increasing magnitude.
+
+pub const EPSILON: f64 = 1.1102230246251565e-16; //2^-53
+const SPLITTER: f64 = 134217729.; //2^27 + 1
+
+pub const RESULT_ERR_BOUND: f64 = (3. + 8. * EPSILON) * EPSILON;
+
+#[inline]
+pub fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    (x, b - b_virtual)
+}
+
+#[inline]
+pub fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    let b_round = b - b_virtual;
+    let a_round = a - a_virtual;
+    (x, a_round + b_round)
+}
+
+#[inline]
+pub fn two_diff_tail(a: f64, b: f64, x: f64) -> f64 {
+    let b_virtual = a - x;
+    let a_virtual = x + b_virtual;
+    let b_round = b_virtual - b;
+    let a_round = a - a_virtual;
+    a_round + b_round
+}
+
+#[inline]
+pub fn two_diff(a: f64, b: f64) -> (f64, f64) {
+    let x = a - b;
+    (x, two_diff_tail(a, b, x))
+}
+
+#[inline]
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let a_big = c - a;
+    let a_hi = c - a_big;
+    (a_hi, a - a_hi)
+}
+
+#[inline]
+pub fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let (b_hi, b_lo) = split(b);
+    (x, two_product_tail(a, b_hi, b_lo, x))
+}
+
+#[inline]
+fn two_product_tail(a: f64, b_hi: f64, b_lo: f64, x: f64) -> f64 {
+    let (a_hi, a_lo) = split(a);
+    let err1 = x - (a_hi * b_hi);
+    let err2 = err1 - (a_lo * b_hi);
+    let err3 = err2 - (a_hi * b_lo);
+    (a_lo * b_lo) - err3
+}
+
+//exact a1 + a0 - (b1 + b0) as an expansion of four components.
+#[inline]
+pub fn two_two_diff(a1: f64, a0: f64, b1: f64, b0: f64) -> [f64; 4] {
+    let (i, x0) = two_diff(a0, b0);
+    let (j, zero) = two_sum(a1, i);
+    let (i, x1) = two_diff(zero, b1);
+    let (x3, x2) = two_sum(j, i);
+    [x0, x1, x2, x3]
+}
+
+//exact a - b as an expansion.
+#[inline]
+pub fn diff_expansion(a: f64, b: f64) -> Vec<f64> {
+    let (x, y) = two_diff(a, b);
+    if y == 0. { vec![x] } else { vec![y, x] }
+}
+
+pub fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut h = Vec::with_capacity(e.len() + f.len());
+    let (mut e_index, mut f_index) = (0, 0);
+    let component = |g: &[f64], index: usize| if index < g.len() { g[index] } else { 0. };
+
+    let mut e_now = e[0];
+    let mut f_now = f[0];
+
+    let mut q;
+    if (f_now > e_now) == (f_now > -e_now) {
+        q = e_now;
+        e_index += 1;
+        e_now = component(e, e_index);
+    } else {
+        q = f_now;
+        f_index += 1;
+        f_now = component(f, f_index);
+    }
+
+    if e_index < e.len() && f_index < f.len() {
+        let (q_new, hh) = if (f_now > e_now) == (f_now > -e_now) {
+            let sum = fast_two_sum(e_now, q);
+            e_index += 1;
+            e_now = component(e, e_index);
+            sum
+        } else {
+            let sum = fast_two_sum(f_now, q);
+            f_index += 1;
+            f_now = component(f, f_index);
+            sum
+        };
+
+        q = q_new;
+        if hh != 0. {
+            h.push(hh);
+        }
+
+        while e_index < e.len() && f_index < f.len() {
+            let (q_new, hh) = if (f_now > e_now) == (f_now > -e_now) {
+                let sum = two_sum(q, e_now);
+                e_index += 1;
+                e_now = component(e, e_index);
+                sum
+            } else {
+                let sum = two_sum(q, f_now);
+                f_index += 1;
+                f_now = component(f, f_index);
+                sum
+            };
+
+            q = q_new;
+            if hh != 0. {
+                h.push(hh);
+            }
+        }
+    }
+
+    while e_index < e.len() {
+        let (q_new, hh) = two_sum(q, e_now);
+        e_index += 1;
+        e_now = component(e, e_index);
+        q = q_new;
+        if hh != 0. {
+            h.push(hh);
+        }
+    }
+
+    while f_index < f.len() {
+        let (q_new, hh) = two_sum(q, f_now);
+        f_index += 1;
+        f_now = component(f, f_index);
+        q = q_new;
+        if hh != 0. {
+            h.push(hh);
+        }
+    }
+
+    if q != 0. || h.is_empty() {
+        h.push(q);
+    }
+
+    h
+}
+
+pub fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(e.len() * 2);
+    let (b_hi, b_lo) = split(b);
+
+    let mut q = e[0] * b;
+    let hh = two_product_tail(e[0], b_hi, b_lo, q);
+    if hh != 0. {
+        h.push(hh);
+    }
+
+    for &e_now in e[1..].iter() {
+        let product1 = e_now * b;
+        let product0 = two_product_tail(e_now, b_hi, b_lo, product1);
+
+        let (sum, hh) = two_sum(q, product0);
+        if hh != 0. {
+            h.push(hh);
+        }
+
+        let (q_new, hh) = fast_two_sum(product1, sum);
+        q = q_new;
+        if hh != 0. {
+            h.push(hh);
+        }
+    }
+
+    if q != 0. || h.is_empty() {
+        h.push(q);
+    }
+
+    h
+}
+
+pub fn expansion_product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut product = scale_expansion(e, f[0]);
+
+    for &f_now in f[1..].iter() {
+        product = expansion_sum(&product, &scale_expansion(e, f_now));
+    }
+
+    product
+}
+
+pub fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let negated: Vec<f64> = f.iter().map(|c| -c).collect();
+    expansion_sum(e, &negated)
+}
+
+#[inline]
+pub fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
+
+//the largest component carries the sign of the whole expansion.
+#[inline]
+pub fn most_significant(e: &[f64]) -> f64 {
+    *e.last().expect("Expansion has no components.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_sum_and_product_are_exact() {
+        let (x, y) = two_sum(1., 1e-20);
+        assert_eq!((1., 1e-20), (x, y));
+
+        let a = 1. + EPSILON * 2.;
+        let (x, y) = two_product(a, a);
+        assert_eq!(1. + EPSILON * 4., x);
+        assert_eq!(EPSILON * EPSILON * 4., y);
+    }
+
+    #[test]
+    fn expansions_keep_all_digits() {
+        let e = expansion_sum(&[1e-30, 1.], &[1e-15]);
+        assert_eq!(1e-30 + 1e-15 + 1., estimate(&e));
+        assert_eq!(3, e.len());
+
+        let cancelled = expansion_diff(&e, &e);
+        assert_eq!(vec![0.], cancelled);
+
+        assert_eq!(vec![13.5], expansion_product(&[3.], &[0.5, 4.]));
+
+        let squared = expansion_product(&[1e-20, 1.], &[1e-20, 1.]);
+        assert_eq!(1., most_significant(&squared));
+        assert_eq!(2e-20, most_significant(&expansion_diff(&squared, &[1.])));
+    }
+}
@@ -0,0 +1,105 @@
+use types::Point2;
+use super::exact_arithmetic::*;
+
+const ICC_ERR_BOUND_A: f64 = (10. + 96. * EPSILON) * EPSILON;
+
+//positive when d lies inside of the circle going through a, b and c, negative when it lies
+//outside and zero when the four points are cocircular. a, b and c have to be in counterclockwise
+//order, otherwise the sign is reversed. the sign is always exact.
+#[inline]
+pub fn incircle(a: &Point2, b: &Point2, c: &Point2, d: &Point2) -> f64 {
+    let adx = a.x - d.x;
+    let bdx = b.x - d.x;
+    let cdx = c.x - d.x;
+    let ady = a.y - d.y;
+    let bdy = b.y - d.y;
+    let cdy = c.y - d.y;
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+    let a_lift = adx * adx + ady * ady;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+    let b_lift = bdx * bdx + bdy * bdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+    let c_lift = cdx * cdx + cdy * cdy;
+
+    let det = a_lift * (bdxcdy - cdxbdy) + b_lift * (cdxady - adxcdy) +
+              c_lift * (adxbdy - bdxady);
+
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * a_lift +
+                    (cdxady.abs() + adxcdy.abs()) * b_lift +
+                    (adxbdy.abs() + bdxady.abs()) * c_lift;
+
+    let err_bound = ICC_ERR_BOUND_A * permanent;
+    if det > err_bound || -det > err_bound {
+        return det;
+    }
+
+    incircle_exact(a, b, c, d)
+}
+
+fn incircle_exact(a: &Point2, b: &Point2, c: &Point2, d: &Point2) -> f64 {
+    let adx = diff_expansion(a.x, d.x);
+    let bdx = diff_expansion(b.x, d.x);
+    let cdx = diff_expansion(c.x, d.x);
+    let ady = diff_expansion(a.y, d.y);
+    let bdy = diff_expansion(b.y, d.y);
+    let cdy = diff_expansion(c.y, d.y);
+
+    let lift = |dx: &[f64], dy: &[f64]| {
+        expansion_sum(&expansion_product(dx, dx), &expansion_product(dy, dy))
+    };
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+        expansion_diff(&expansion_product(x1, y2), &expansion_product(x2, y1))
+    };
+
+    let a_term = expansion_product(&lift(&adx, &ady), &cross(&bdx, &bdy, &cdx, &cdy));
+    let b_term = expansion_product(&lift(&bdx, &bdy), &cross(&cdx, &cdy, &adx, &ady));
+    let c_term = expansion_product(&lift(&cdx, &cdy), &cross(&adx, &ady, &bdx, &bdy));
+
+    let det = expansion_sum(&expansion_sum(&a_term, &b_term), &c_term);
+    most_significant(&det)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Point2;
+
+    #[test]
+    fn incircle_of_simple_points() {
+        let a = Point2::new(0., 0.);
+        let b = Point2::new(2., 0.);
+        let c = Point2::new(0., 2.);
+
+        assert!(incircle(&a, &b, &c, &Point2::new(1., 1.)) > 0.);
+        assert!(incircle(&a, &b, &c, &Point2::new(3., 3.)) < 0.);
+        assert_eq!(0., incircle(&a, &b, &c, &Point2::new(2., 2.)));
+
+        assert!(incircle(&a, &c, &b, &Point2::new(1., 1.)) < 0.);
+    }
+
+    #[test]
+    fn incircle_of_nearly_cocircular_points() {
+        let a = Point2::new(1., 0.);
+        let b = Point2::new(0., 1.);
+        let c = Point2::new(-1., 0.);
+
+        let ulp = 2f64.powi(-53);
+        assert_eq!(0., incircle(&a, &b, &c, &Point2::new(0., -1.)));
+        assert!(incircle(&a, &b, &c, &Point2::new(0., -1. + ulp)) > 0.);
+        assert!(incircle(&a, &b, &c, &Point2::new(0., -1. - 2. * ulp)) < 0.);
+
+        //same circle, shifted far away from the origin so differences are not exact anymore.
+        let shift = Point2::new(1e10 + 0.5, 1e10 + 0.25);
+        let moved = |p: &Point2| Point2::new(p.x * 1e-3 + shift.x, p.y * 1e-3 + shift.y);
+        let inside = incircle(&moved(&a), &moved(&b), &moved(&c), &moved(&Point2::new(0., -0.99)));
+        let outside = incircle(&moved(&a), &moved(&b), &moved(&c), &moved(&Point2::new(0., -1.01)));
+        assert!(inside > 0.);
+        assert!(outside < 0.);
+    }
+}
@@ -0,0 +1,170 @@
+use types::Point3;
+use super::exact_arithmetic::*;
+
+const ISP_ERR_BOUND_A: f64 = (16. + 224. * EPSILON) * EPSILON;
+
+//positive when e lies inside of the sphere going through a, b, c and d, negative when it lies
+//outside and zero when the five points are cospherical. a, b, c and d have to be ordered so
+//that orient3d(a, b, c, d) is positive, otherwise the sign is reversed. the sign is always exact.
+#[inline]
+pub fn insphere(a: &Point3, b: &Point3, c: &Point3, d: &Point3, e: &Point3) -> f64 {
+    let aex = a.x - e.x;
+    let bex = b.x - e.x;
+    let cex = c.x - e.x;
+    let dex = d.x - e.x;
+    let aey = a.y - e.y;
+    let bey = b.y - e.y;
+    let cey = c.y - e.y;
+    let dey = d.y - e.y;
+    let aez = a.z - e.z;
+    let bez = b.z - e.z;
+    let cez = c.z - e.z;
+    let dez = d.z - e.z;
+
+    let aexbey = aex * bey;
+    let bexaey = bex * aey;
+    let ab = aexbey - bexaey;
+    let bexcey = bex * cey;
+    let cexbey = cex * bey;
+    let bc = bexcey - cexbey;
+    let cexdey = cex * dey;
+    let dexcey = dex * cey;
+    let cd = cexdey - dexcey;
+    let dexaey = dex * aey;
+    let aexdey = aex * dey;
+    let da = dexaey - aexdey;
+    let aexcey = aex * cey;
+    let cexaey = cex * aey;
+    let ac = aexcey - cexaey;
+    let bexdey = bex * dey;
+    let dexbey = dex * bey;
+    let bd = bexdey - dexbey;
+
+    let abc = aez * bc - bez * ac + cez * ab;
+    let bcd = bez * cd - cez * bd + dez * bc;
+    let cda = cez * da + dez * ac + aez * cd;
+    let dab = dez * ab + aez * bd + bez * da;
+
+    let a_lift = aex * aex + aey * aey + aez * aez;
+    let b_lift = bex * bex + bey * bey + bez * bez;
+    let c_lift = cex * cex + cey * cey + cez * cez;
+    let d_lift = dex * dex + dey * dey + dez * dez;
+
+    let det = (d_lift * abc - c_lift * dab) + (b_lift * cda - a_lift * bcd);
+
+    let (aez, bez, cez, dez) = (aez.abs(), bez.abs(), cez.abs(), dez.abs());
+    let (aexbey, bexaey) = (aexbey.abs(), bexaey.abs());
+    let (bexcey, cexbey) = (bexcey.abs(), cexbey.abs());
+    let (cexdey, dexcey) = (cexdey.abs(), dexcey.abs());
+    let (dexaey, aexdey) = (dexaey.abs(), aexdey.abs());
+    let (aexcey, cexaey) = (aexcey.abs(), cexaey.abs());
+    let (bexdey, dexbey) = (bexdey.abs(), dexbey.abs());
+
+    let permanent = ((cexdey + dexcey) * bez + (dexbey + bexdey) * cez +
+                     (bexcey + cexbey) * dez) * a_lift +
+                    ((dexaey + aexdey) * cez + (aexcey + cexaey) * dez +
+                     (cexdey + dexcey) * aez) * b_lift +
+                    ((aexbey + bexaey) * dez + (bexdey + dexbey) * aez +
+                     (dexaey + aexdey) * bez) * c_lift +
+                    ((bexcey + cexbey) * aez + (cexaey + aexcey) * bez +
+                     (aexbey + bexaey) * cez) * d_lift;
+
+    let err_bound = ISP_ERR_BOUND_A * permanent;
+    if det > err_bound || -det > err_bound {
+        return det;
+    }
+
+    insphere_exact(a, b, c, d, e)
+}
+
+fn insphere_exact(a: &Point3, b: &Point3, c: &Point3, d: &Point3, e: &Point3) -> f64 {
+    let aex = diff_expansion(a.x, e.x);
+    let bex = diff_expansion(b.x, e.x);
+    let cex = diff_expansion(c.x, e.x);
+    let dex = diff_expansion(d.x, e.x);
+    let aey = diff_expansion(a.y, e.y);
+    let bey = diff_expansion(b.y, e.y);
+    let cey = diff_expansion(c.y, e.y);
+    let dey = diff_expansion(d.y, e.y);
+    let aez = diff_expansion(a.z, e.z);
+    let bez = diff_expansion(b.z, e.z);
+    let cez = diff_expansion(c.z, e.z);
+    let dez = diff_expansion(d.z, e.z);
+
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+        expansion_diff(&expansion_product(x1, y2), &expansion_product(x2, y1))
+    };
+    let lift = |dx: &[f64], dy: &[f64], dz: &[f64]| {
+        expansion_sum(&expansion_sum(&expansion_product(dx, dx), &expansion_product(dy, dy)),
+                      &expansion_product(dz, dz))
+    };
+    //z1 * xy23 - z2 * xy13 + z3 * xy12
+    let triple = |z1: &[f64], xy23: &[f64], z2: &[f64], xy13: &[f64], z3: &[f64], xy12: &[f64]| {
+        expansion_sum(&expansion_diff(&expansion_product(z1, xy23),
+                                      &expansion_product(z2, xy13)),
+                      &expansion_product(z3, xy12))
+    };
+
+    let ab = cross(&aex, &aey, &bex, &bey);
+    let bc = cross(&bex, &bey, &cex, &cey);
+    let cd = cross(&cex, &cey, &dex, &dey);
+    let ac = cross(&aex, &aey, &cex, &cey);
+    let bd = cross(&bex, &bey, &dex, &dey);
+    let ad = cross(&aex, &aey, &dex, &dey);
+
+    let abc = triple(&aez, &bc, &bez, &ac, &cez, &ab);
+    let bcd = triple(&bez, &cd, &cez, &bd, &dez, &bc);
+    let acd = triple(&aez, &cd, &cez, &ad, &dez, &ac);
+    let abd = triple(&aez, &bd, &bez, &ad, &dez, &ab);
+
+    let d_term = expansion_product(&lift(&dex, &dey, &dez), &abc);
+    let c_term = expansion_product(&lift(&cex, &cey, &cez), &abd);
+    let b_term = expansion_product(&lift(&bex, &bey, &bez), &acd);
+    let a_term = expansion_product(&lift(&aex, &aey, &aez), &bcd);
+
+    let det = expansion_sum(&expansion_diff(&d_term, &c_term),
+                            &expansion_diff(&b_term, &a_term));
+    most_significant(&det)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use math::orient3d;
+    use types::Point3;
+
+    #[test]
+    fn insphere_of_simple_points() {
+        let a = Point3::new(0., 0., 0.);
+        let b = Point3::new(0., 1., 0.);
+        let c = Point3::new(1., 0., 0.);
+        let d = Point3::new(0., 0., 1.);
+        assert!(orient3d(&a, &b, &c, &d) > 0.);
+
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(0.25, 0.25, 0.25)) > 0.);
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(0.9, 0.9, 0.9)) > 0.);
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(1.1, 1.1, 1.1)) < 0.);
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(-1., 0., 0.)) < 0.);
+        assert_eq!(0., insphere(&a, &b, &c, &d, &Point3::new(1., 1., 1.)));
+        assert_eq!(0., insphere(&a, &b, &c, &d, &Point3::new(1., 1., 0.)));
+
+        assert!(insphere(&b, &a, &c, &d, &Point3::new(0.25, 0.25, 0.25)) < 0.);
+    }
+
+    #[test]
+    fn insphere_of_nearly_cospherical_points() {
+        //corners of a cube lie on one sphere, moving the last one by an ulp has to be noticed.
+        let shift = 1e6;
+        let a = Point3::new(shift, shift, shift);
+        let b = Point3::new(shift, shift + 1., shift);
+        let c = Point3::new(shift + 1., shift, shift);
+        let d = Point3::new(shift, shift, shift + 1.);
+        assert!(orient3d(&a, &b, &c, &d) > 0.);
+
+        let corner = shift + 1.;
+        let ulp = 2f64.powi(-32);
+        assert_eq!(0., insphere(&a, &b, &c, &d, &Point3::new(corner, corner, corner)));
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(corner - ulp, corner, corner)) > 0.);
+        assert!(insphere(&a, &b, &c, &d, &Point3::new(corner + ulp, corner, corner)) < 0.);
+    }
+}
@@ -1,3 +1,9 @@
+mod exact_arithmetic;
+mod orient2d;
+mod incircle;
+mod orient3d;
+mod insphere;
+
 mod side_of_line;
 
 mod circumcircle_side;
@@ -6,13 +12,20 @@ mod circumsphere_side;
 mod side_of_plane;
 mod order_float;
 mod distance;
+mod space_filling_curve;
 
 pub use self::order_float::*;
 
+pub use self::orient2d::*;
+pub use self::incircle::*;
+pub use self::orient3d::*;
+pub use self::insphere::*;
+
 pub use self::side_of_line::*;
 pub use self::circumcircle_side::*;
 
 pub use self::circumsphere_side::*;
 pub use self::side_of_plane::*;
 
-pub use self::distance::*;
\ No newline at end of file
+pub use self::distance::*;
+pub use self::space_filling_curve::*;
\ No newline at end of file
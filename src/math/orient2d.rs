@@ -0,0 +1,121 @@
+use types::Point2;
+use super::exact_arithmetic::*;
+
+const CCW_ERR_BOUND_A: f64 = (3. + 16. * EPSILON) * EPSILON;
+const CCW_ERR_BOUND_B: f64 = (2. + 12. * EPSILON) * EPSILON;
+const CCW_ERR_BOUND_C: f64 = (9. + 64. * EPSILON) * EPSILON * EPSILON;
+
+//positive when c lies left of the line going from a to b, negative when it lies right of it
+//and zero when the points are collinear. the sign is always exact.
+#[inline]
+pub fn orient2d(a: &Point2, b: &Point2, c: &Point2) -> f64 {
+    let det_left = (a.x - c.x) * (b.y - c.y);
+    let det_right = (a.y - c.y) * (b.x - c.x);
+    let det = det_left - det_right;
+
+    let det_sum = if det_left > 0. {
+        if det_right <= 0. {
+            return det;
+        }
+        det_left + det_right
+    } else if det_left < 0. {
+        if det_right >= 0. {
+            return det;
+        }
+        -det_left - det_right
+    } else {
+        return det;
+    };
+
+    let err_bound = CCW_ERR_BOUND_A * det_sum;
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    orient2d_adapt(a, b, c, det_sum)
+}
+
+fn orient2d_adapt(a: &Point2, b: &Point2, c: &Point2, det_sum: f64) -> f64 {
+    let acx = a.x - c.x;
+    let bcx = b.x - c.x;
+    let acy = a.y - c.y;
+    let bcy = b.y - c.y;
+
+    let (det_left, det_left_tail) = two_product(acx, bcy);
+    let (det_right, det_right_tail) = two_product(acy, bcx);
+    let b_expansion = two_two_diff(det_left, det_left_tail, det_right, det_right_tail);
+
+    let mut det = estimate(&b_expansion);
+    let err_bound = CCW_ERR_BOUND_B * det_sum;
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    let acx_tail = two_diff_tail(a.x, c.x, acx);
+    let bcx_tail = two_diff_tail(b.x, c.x, bcx);
+    let acy_tail = two_diff_tail(a.y, c.y, acy);
+    let bcy_tail = two_diff_tail(b.y, c.y, bcy);
+
+    if acx_tail == 0. && acy_tail == 0. && bcx_tail == 0. && bcy_tail == 0. {
+        return det;
+    }
+
+    let err_bound = CCW_ERR_BOUND_C * det_sum + RESULT_ERR_BOUND * det.abs();
+    det += (acx * bcy_tail + bcy * acx_tail) - (acy * bcx_tail + bcx * acy_tail);
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    let (s1, s0) = two_product(acx_tail, bcy);
+    let (t1, t0) = two_product(acy_tail, bcx);
+    let c1 = expansion_sum(&b_expansion, &two_two_diff(s1, s0, t1, t0));
+
+    let (s1, s0) = two_product(acx, bcy_tail);
+    let (t1, t0) = two_product(acy, bcx_tail);
+    let c2 = expansion_sum(&c1, &two_two_diff(s1, s0, t1, t0));
+
+    let (s1, s0) = two_product(acx_tail, bcy_tail);
+    let (t1, t0) = two_product(acy_tail, bcx_tail);
+    let d = expansion_sum(&c2, &two_two_diff(s1, s0, t1, t0));
+
+    most_significant(&d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Point2;
+
+    #[test]
+    fn orientation_of_simple_points() {
+        let a = Point2::new(0., 0.);
+        let b = Point2::new(1., 1.);
+
+        assert!(orient2d(&a, &b, &Point2::new(0., 1.)) > 0.);
+        assert!(orient2d(&a, &b, &Point2::new(1., 0.)) < 0.);
+        assert_eq!(0., orient2d(&a, &b, &Point2::new(0.5, 0.5)));
+    }
+
+    #[test]
+    fn orientation_of_nearly_collinear_points() {
+        //plain f64 evaluation gets the sign of most of these wrong.
+        let b = Point2::new(12., 12.);
+        let c = Point2::new(24., 24.);
+
+        let ulp = 2f64.powi(-53);
+        for i in 0..64 {
+            for j in 0..64 {
+                let p = Point2::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                let side = orient2d(&p, &b, &c);
+
+                if i == j {
+                    assert_eq!(0., side);
+                } else if i < j {
+                    assert!(side > 0.);
+                } else {
+                    assert!(side < 0.);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use types::Point3;
+use super::exact_arithmetic::*;
+
+const O3D_ERR_BOUND_A: f64 = (7. + 56. * EPSILON) * EPSILON;
+
+//positive when d lies below the plane going through a, b and c, where a, b and c appear in
+//counterclockwise order when looked at from above the plane. negative when d lies above it and
+//zero when the points are coplanar. the sign is always exact.
+#[inline]
+pub fn orient3d(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> f64 {
+    let adx = a.x - d.x;
+    let bdx = b.x - d.x;
+    let cdx = c.x - d.x;
+    let ady = a.y - d.y;
+    let bdy = b.y - d.y;
+    let cdy = c.y - d.y;
+    let adz = a.z - d.z;
+    let bdz = b.z - d.z;
+    let cdz = c.z - d.z;
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+
+    let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs() +
+                    (cdxady.abs() + adxcdy.abs()) * bdz.abs() +
+                    (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+
+    let err_bound = O3D_ERR_BOUND_A * permanent;
+    if det > err_bound || -det > err_bound {
+        return det;
+    }
+
+    orient3d_exact(a, b, c, d)
+}
+
+fn orient3d_exact(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> f64 {
+    let adx = diff_expansion(a.x, d.x);
+    let bdx = diff_expansion(b.x, d.x);
+    let cdx = diff_expansion(c.x, d.x);
+    let ady = diff_expansion(a.y, d.y);
+    let bdy = diff_expansion(b.y, d.y);
+    let cdy = diff_expansion(c.y, d.y);
+    let adz = diff_expansion(a.z, d.z);
+    let bdz = diff_expansion(b.z, d.z);
+    let cdz = diff_expansion(c.z, d.z);
+
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+        expansion_diff(&expansion_product(x1, y2), &expansion_product(x2, y1))
+    };
+
+    let a_term = expansion_product(&adz, &cross(&bdx, &bdy, &cdx, &cdy));
+    let b_term = expansion_product(&bdz, &cross(&cdx, &cdy, &adx, &ady));
+    let c_term = expansion_product(&cdz, &cross(&adx, &ady, &bdx, &bdy));
+
+    let det = expansion_sum(&expansion_sum(&a_term, &b_term), &c_term);
+    most_significant(&det)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Point3;
+
+    #[test]
+    fn orientation_of_simple_points() {
+        let a = Point3::new(0., 0., 0.);
+        let b = Point3::new(1., 0., 0.);
+        let c = Point3::new(0., 1., 0.);
+
+        assert!(orient3d(&a, &b, &c, &Point3::new(0., 0., -1.)) > 0.);
+        assert!(orient3d(&a, &b, &c, &Point3::new(0., 0., 1.)) < 0.);
+        assert_eq!(0., orient3d(&a, &b, &c, &Point3::new(7., -3., 0.)));
+    }
+
+    #[test]
+    fn orientation_of_nearly_coplanar_points() {
+        //far from the origin plain f64 evaluation gets the sign of these wrong.
+        let shift = 1073741824.; //2^30
+        let a = Point3::new(shift, shift, shift);
+        let b = Point3::new(shift + 3., shift + 1., shift + 0.5);
+        let c = Point3::new(shift + 1., shift + 2., shift + 0.25);
+        let on_plane = Point3::new(shift + 4., shift + 3., shift + 0.75);
+
+        let ulp = 2f64.powi(-22);
+        let above = Point3::new(on_plane.x, on_plane.y, on_plane.z + ulp);
+        let below = Point3::new(on_plane.x, on_plane.y, on_plane.z - ulp);
+
+        assert_eq!(0., orient3d(&a, &b, &c, &on_plane));
+        assert!(orient3d(&a, &b, &c, &above) < 0.);
+        assert!(orient3d(&a, &b, &c, &below) > 0.);
+        assert!(orient3d(&b, &a, &c, &below) < 0.);
+    }
+}
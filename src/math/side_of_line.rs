@@ -1,4 +1,5 @@
 use types::Point2;
+use math::orient2d;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum PointLiesOnLineSide {
@@ -9,16 +10,11 @@ pub enum PointLiesOnLineSide {
 
 #[inline]
 pub fn side_of_line(line_begin: &Point2, line_end: &Point2, point: &Point2) -> PointLiesOnLineSide {
-    let c0r0 = line_end.x - line_begin.x;
-    let c0r1 = line_end.y - line_begin.y;
-    let c1r0 = point.x - line_begin.x;
-    let c1r1 = point.y - line_begin.y;
+    let orientation = orient2d(line_begin, line_end, point);
 
-    let cross = c0r0 * c1r1 - c1r0 * c0r1;
-
-    if cross > 0. {
+    if orientation > 0. {
         PointLiesOnLineSide::Left
-    } else if cross < 0. {
+    } else if orientation < 0. {
         PointLiesOnLineSide::Right
     } else {
         PointLiesOnLineSide::OnLine
@@ -1,10 +1,5 @@
 use types::Point3;
-use types::Point3Err;
-use types::point3_err_from_point3;
-use cgmath::Matrix3;
-use cgmath::SquareMatrix;
-use math::order_float;
-use types::fp;
+use math::orient3d;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum SideOfPlane {
@@ -15,22 +10,12 @@ pub enum SideOfPlane {
 
 #[inline]
 pub fn side_of_plane(a: &Point3, b: &Point3, c: &Point3, p: &Point3) -> SideOfPlane {
-    let a = point3_err_from_point3(&a);
-    let b = point3_err_from_point3(&b);
-    let c = point3_err_from_point3(&c);
-    let p = point3_err_from_point3(&p);
+    //orient3d is positive for points below the plane, which is the right side of a, b, c.
+    let det = orient3d(a, b, c, p);
 
-    let ab = b - a;
-    let ac = c - a;
-    let ap = p - a;
-
-    let det = Matrix3::new(ab.x, ab.y, ab.z, ac.x, ac.y, ac.z, ap.x, ap.y, ap.z).determinant();
-
-    let eps = det.err_times_eps();
-
-    if det.val() < -eps {
+    if det > 0. {
         SideOfPlane::Right
-    } else if det.val() > eps {
+    } else if det < 0. {
         SideOfPlane::Left
     } else {
         SideOfPlane::OnPlane
@@ -73,11 +58,15 @@ mod tests {
         let b = Point3::new(8., 10., 11.);
         let c = Point3::new(3., 3., 2.);
 
+        //the centroid is rounded, so a point which is on the plane exactly is used instead.
+        let on_plane = Point3::new(b.x + c.x - a.x, b.y + c.y - a.y, b.z + c.z - a.z);
         let center = Point3::new((a.x + b.x + c.x) / 3.,
                                  (a.y + b.y + c.y) / 3.,
                                  (a.z + b.z + c.z) / 3.);
 
-        assert_eq!(SideOfPlane::OnPlane, side_of_plane(&a, &b, &c, &center));
+        assert_eq!(SideOfPlane::OnPlane, side_of_plane(&a, &b, &c, &on_plane));
+        assert_eq!(side_of_plane(&a, &b, &c, &center),
+                   side_of_plane(&b, &c, &a, &center));
     }
 
     #[test]
@@ -168,9 +157,11 @@ mod tests {
             let vec = side_center - center;
             let on_the_other_side = center + (2. * vec);
 
+            //side_center is rounded, so it is only checked to get the same answer for every
+            //rotation of the face.
             let result = side_of_plane(&side.0, &side.1, &side.2, &side_center);
-            if result != SideOfPlane::OnPlane {
-                panic!(format!("Expected p: {:?} to be on plane of {:?} {:?} {:?}",
+            if result != side_of_plane(&side.1, &side.2, &side.0, &side_center) {
+                panic!(format!("Expected p: {:?} to be on one side of plane of {:?} {:?} {:?}",
                                &side_center,
                                &side.0,
                                &side.1,
@@ -0,0 +1,166 @@
+//coordinates are integers with this many bits, so that the index of a 3d point fits into u64.
+pub const CURVE_BITS: u32 = 21;
+
+//rounds of a biased randomized insertion order are not split below this size.
+const MIN_ROUND: usize = 64;
+
+//position of the point on the hilbert curve going through all points of the integer grid. uses
+//the transposition of skilling, which works in any dimension.
+pub fn hilbert_index(coordinates: &[u32]) -> u64 {
+    let mut x: Vec<u32> = coordinates.to_vec();
+    let n = x.len();
+    let m = 1u32 << (CURVE_BITS - 1);
+
+    //inverse undo of the excess work.
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    //gray encoding.
+    for i in 1..n {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if x[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for value in x.iter_mut() {
+        *value ^= t;
+    }
+
+    interleave(&x)
+}
+
+//position of the point on the z-order curve, its coordinate bits interleaved.
+pub fn morton_index(coordinates: &[u32]) -> u64 {
+    interleave(coordinates)
+}
+
+fn interleave(coordinates: &[u32]) -> u64 {
+    let mut index = 0u64;
+    for bit in (0..CURVE_BITS).rev() {
+        for coordinate in coordinates.iter() {
+            index = (index << 1) | ((coordinate >> bit) & 1) as u64;
+        }
+    }
+    index
+}
+
+//maps the coordinate between min and max to the integer grid of the curves.
+pub fn to_curve_coordinate(value: f64, min: f64, max: f64) -> u32 {
+    let cells = ((1u32 << CURVE_BITS) - 1) as f64;
+    if max > min {
+        ((value - min) / (max - min) * cells).round() as u32
+    } else {
+        0
+    }
+}
+
+//biased randomized insertion order of amenta, choi and rote. points are shuffled and split into
+//rounds, each twice as large as the one before it, and every round is sorted by the curve
+//index. points of a round are spread over the whole domain, while consecutive points lie close
+//to each other.
+pub fn brio_order(curve_indexes: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..curve_indexes.len()).collect();
+
+    //xorshift, so that the order does not change between runs.
+    let mut state = 0x2545_f491u32;
+    for i in (1..order.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        order.swap(i, state as usize % (i + 1));
+    }
+
+    let mut end = order.len();
+    while end > 0 {
+        let start = if end > MIN_ROUND { end / 2 } else { 0 };
+        order[start..end].sort_by_key(|&index| curve_indexes[index]);
+        end = start;
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //cells of a 8 by 8 grid ordered by their curve index.
+    fn grid_path<F>(index: F) -> Vec<(i64, i64)>
+        where F: Fn(&[u32]) -> u64
+    {
+        let mut cells = Vec::new();
+        for x in 0..8u32 {
+            for y in 0..8u32 {
+                cells.push((index(&[x << (CURVE_BITS - 3), y << (CURVE_BITS - 3)]),
+                            (x as i64, y as i64)));
+            }
+        }
+        cells.sort();
+        cells.into_iter().map(|(_, cell)| cell).collect()
+    }
+
+    #[test]
+    fn curves_through_2d_grid() {
+        //hilbert curve only steps to neighboring cells, z-order jumps.
+        let hilbert = grid_path(hilbert_index);
+        assert_eq!((0, 0), hilbert[0]);
+        assert!(hilbert.windows(2)
+            .all(|w| ((w[0].0 - w[1].0).abs() + (w[0].1 - w[1].1).abs()) == 1));
+
+        let morton = grid_path(morton_index);
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0), (1, 1), (0, 2)], morton[..5].to_vec());
+    }
+
+    #[test]
+    fn hilbert_curve_through_3d_grid() {
+        let mut cells = Vec::new();
+        for x in 0..8u32 {
+            for y in 0..8u32 {
+                for z in 0..8u32 {
+                    let shift = CURVE_BITS - 3;
+                    cells.push((hilbert_index(&[x << shift, y << shift, z << shift]),
+                                [x as i64, y as i64, z as i64]));
+                }
+            }
+        }
+        cells.sort();
+
+        assert_eq!([0, 0, 0], cells[0].1);
+        for w in cells.windows(2) {
+            let (a, b) = (w[0].1, w[1].1);
+            assert_eq!(1, (a[0] - b[0]).abs() + (a[1] - b[1]).abs() + (a[2] - b[2]).abs());
+        }
+    }
+
+    #[test]
+    fn brio_rounds() {
+        let curve_indexes: Vec<u64> = (0..1000).map(|i| (i * 7919 % 1000) as u64).collect();
+        let order = brio_order(&curve_indexes);
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!((0..1000).collect::<Vec<usize>>(), sorted);
+
+        //last round is the second half, sorted along the curve.
+        assert!(order[500..].windows(2).all(|w| curve_indexes[w[0]] <= curve_indexes[w[1]]));
+        assert!(order[250..500].windows(2).all(|w| curve_indexes[w[0]] <= curve_indexes[w[1]]));
+        assert!(!order.windows(2).all(|w| curve_indexes[w[0]] <= curve_indexes[w[1]]));
+    }
+}
@@ -0,0 +1,46 @@
+//order in which the nodes given to new are inserted into a triangulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertionOrder {
+    //sorted by x, then y and z. every node lies outside of the triangulation built so far.
+    Lexicographic,
+    //biased randomized insertion order with rounds sorted along the hilbert curve.
+    Hilbert,
+    //same as Hilbert, with rounds sorted along the z-order curve.
+    Morton,
+}
+
+impl Default for InsertionOrder {
+    fn default() -> InsertionOrder {
+        InsertionOrder::Lexicographic
+    }
+}
+
+//algorithm building a Triangulation2.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuildAlgorithm {
+    //nodes are inserted one by one in the insertion order and the triangulation is flipped
+    //after each of them.
+    Incremental,
+    //nodes sorted by x are split in halves, both are triangulated recursively and merged. does
+    //not depend on the insertion order and is not split into threads. triangulation3 is always
+    //built incrementally.
+    DivideAndConquer,
+}
+
+impl Default for BuildAlgorithm {
+    fn default() -> BuildAlgorithm {
+        BuildAlgorithm::Incremental
+    }
+}
+
+//how Triangulation2::new_with_options and Triangulation3::new_with_options build the
+//triangulation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BuildOptions {
+    pub algorithm: BuildAlgorithm,
+    pub insertion_order: InsertionOrder,
+    //with the parallel feature, nodes are split into this many parts triangulated in their own
+    //threads. 0 and 1 build the triangulation in the calling thread. without the feature it has
+    //no effect.
+    pub threads: usize,
+}
@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DelaunayError {
+    //index of the node with nan or infinite coordinates.
+    InvalidCoordinates(usize),
+    //indexes of the existing node and of the node with the same coordinates.
+    DuplicateNodes(usize, usize),
+    //index of the node which is not a part of the triangulation.
+    UnknownNode(usize),
+    //nodes of the constrained edge crossed by a new constraint.
+    CrossingConstraints(usize, usize),
+    //all elements were outside of the domain.
+    EmptyDomain,
+    //inserted node lies outside of the domain the triangulation is restricted to.
+    OutsideOfDomain,
+    //index of the node on the border of the domain, which can not be removed.
+    NodeOnDomainBorder(usize),
+    //segments or facets could not be recovered, they intersect or meet at a too small angle.
+    UnrecoverableBoundary,
+    //elements can not be flipped, their union is not convex or the flipped face or edge lies on
+    //the border or is constrained.
+    NotFlippable,
+    //no three nodes span a triangle.
+    CollinearNodes,
+    //no four nodes span a tetrahedron.
+    CoplanarNodes,
+    //min angle of the refinement above MAX_UNBOUNDED_MIN_ANGLE without a node budget.
+    UnboundedRefinement(f64),
+}
+
+impl fmt::Display for DelaunayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DelaunayError::InvalidCoordinates(index) => {
+                write!(f, "Triangulation received node {} with invalid coordinates.", index)
+            }
+            DelaunayError::DuplicateNodes(existing, duplicate) => {
+                write!(f,
+                       "Triangulation received equal nodes. node {} duplicates node {}.",
+                       duplicate,
+                       existing)
+            }
+            DelaunayError::UnknownNode(index) => {
+                write!(f, "Triangulation does not contain node {}.", index)
+            }
+            DelaunayError::CrossingConstraints(n1, n2) => {
+                write!(f,
+                       "Constraint crosses constrained edge between nodes {} and {}.",
+                       n1,
+                       n2)
+            }
+            DelaunayError::EmptyDomain => write!(f, "Domain of the triangulation has no elements."),
+            DelaunayError::UnrecoverableBoundary => {
+                write!(f,
+                       "Boundary could not be recovered, its facets intersect or meet at a too \
+                        small angle.")
+            }
+            DelaunayError::OutsideOfDomain => {
+                write!(f, "Node lies outside of the domain of the triangulation.")
+            }
+            DelaunayError::NodeOnDomainBorder(index) => {
+                write!(f, "Node {} lies on the border of the domain and can not be removed.", index)
+            }
+            DelaunayError::NotFlippable => {
+                write!(f,
+                       "Elements can not be flipped, their union is not convex or they lie on the \
+                        border.")
+            }
+            DelaunayError::CollinearNodes => {
+                write!(f,
+                       "Triangulation requires at least three nodes which are not collinear.")
+            }
+            DelaunayError::CoplanarNodes => {
+                write!(f, "Triangulation requires at least four nodes which are not coplanar.")
+            }
+            DelaunayError::UnboundedRefinement(min_angle) => {
+                write!(f,
+                       "Refinement to min angle {} may not end, it needs max_steiner_nodes.",
+                       min_angle)
+            }
+        }
+    }
+}
+
+impl Error for DelaunayError {
+    fn description(&self) -> &str {
+        match *self {
+            DelaunayError::InvalidCoordinates(_) => "node with invalid coordinates",
+            DelaunayError::DuplicateNodes(_, _) => "equal nodes",
+            DelaunayError::UnknownNode(_) => "node is not in triangulation",
+            DelaunayError::CrossingConstraints(_, _) => "constraints cross",
+            DelaunayError::EmptyDomain => "domain has no elements",
+            DelaunayError::UnrecoverableBoundary => "boundary could not be recovered",
+            DelaunayError::OutsideOfDomain => "node outside of domain",
+            DelaunayError::NodeOnDomainBorder(_) => "node on domain border",
+            DelaunayError::NotFlippable => "elements can not be flipped",
+            DelaunayError::CollinearNodes => "all nodes are collinear",
+            DelaunayError::CoplanarNodes => "all nodes are coplanar",
+            DelaunayError::UnboundedRefinement(_) => "refinement may not end",
+        }
+    }
+}
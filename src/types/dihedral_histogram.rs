@@ -0,0 +1,63 @@
+use std::fmt;
+
+//number of tetrahedra by their smallest dihedral angle in bins of 10 degrees. the regular
+//tetrahedron has the largest one, about 70.5 degrees, so the last bin stays empty.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DihedralHistogram {
+    pub counts: [usize; 8],
+}
+
+impl DihedralHistogram {
+    pub fn add(&mut self, min_dihedral_angle: f64) {
+        let bin = (min_dihedral_angle / 10.) as usize;
+        self.counts[bin.min(self.counts.len() - 1)] += 1;
+    }
+
+    //number of tetrahedra in the bins below the angle, which is rounded down to whole bins.
+    pub fn count_below(&self, angle: f64) -> usize {
+        let bins = ((angle / 10.) as usize).min(self.counts.len());
+        self.counts[..bins].iter().sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+impl fmt::Display for DihedralHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (bin, count) in self.counts.iter().enumerate() {
+            writeln!(f, "{:>2}-{:<2} deg: {}", bin * 10, bin * 10 + 10, count)?;
+        }
+        Ok(())
+    }
+}
+
+//result of Triangulation3::remove_slivers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliverRemoval {
+    pub flips: usize,
+    pub before: DihedralHistogram,
+    pub after: DihedralHistogram,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_angles() {
+        let mut histogram = DihedralHistogram::default();
+        for angle in [0.5, 9.9, 10., 35., 70.5].iter() {
+            histogram.add(*angle);
+        }
+
+        assert_eq!([2, 1, 0, 1, 0, 0, 0, 1], histogram.counts);
+        assert_eq!(2, histogram.count_below(10.));
+        assert_eq!(2, histogram.count_below(15.));
+        assert_eq!(3, histogram.count_below(20.));
+        assert_eq!(5, histogram.count_below(180.));
+        assert_eq!(5, histogram.total());
+        assert!(format!("{}", histogram).starts_with(" 0-10 deg: 2\n10-20 deg: 1\n"));
+    }
+}
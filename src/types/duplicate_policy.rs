@@ -0,0 +1,16 @@
+//decides what happens with a node inserted into a triangulation on top of an existing node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicatePolicy {
+    //insertion fails with DelaunayError::DuplicateNodes.
+    Reject,
+    //node is not inserted, the index of the existing node with equal coordinates is returned.
+    Merge,
+    //same as Merge, but for any existing node not further than the given distance.
+    MergeWithin(f64),
+}
+
+impl Default for DuplicatePolicy {
+    fn default() -> DuplicatePolicy {
+        DuplicatePolicy::Reject
+    }
+}
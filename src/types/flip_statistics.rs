@@ -0,0 +1,53 @@
+//decides in which order the edges changed by flips are checked after a node is inserted into
+//a 2d triangulation. the result is the same, only the order of the flips differs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlipWorkList {
+    //last changed element is checked first, flips go deep before they go wide.
+    Stack,
+    //elements are checked in the order in which they were changed.
+    Queue,
+}
+
+impl Default for FlipWorkList {
+    fn default() -> FlipWorkList {
+        FlipWorkList::Stack
+    }
+}
+
+//counters of the flips done while inserting nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FlipStatistics {
+    pub flips: usize,
+    //largest number of elements waiting in the work list at once.
+    pub max_work_list_len: usize,
+}
+
+impl FlipStatistics {
+    pub fn add(&mut self, other: &FlipStatistics) {
+        self.flips += other.flips;
+        self.max_work_list_len = self.max_work_list_len.max(other.max_work_list_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_statistics() {
+        let mut statistics = FlipStatistics {
+            flips: 3,
+            max_work_list_len: 5,
+        };
+        statistics.add(&FlipStatistics {
+            flips: 2,
+            max_work_list_len: 4,
+        });
+
+        assert_eq!(FlipStatistics {
+                       flips: 5,
+                       max_work_list_len: 5,
+                   },
+                   statistics);
+    }
+}
@@ -0,0 +1,15 @@
+//decides how a node inserted into a 3d triangulation restores the delaunay property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertionAlgorithm {
+    //elements whose circumsphere contains the node are replaced by a star of elements around it.
+    BowyerWatson,
+    //elements containing the node are split and the faces around it are flipped until they are
+    //locally delaunay.
+    Flipping,
+}
+
+impl Default for InsertionAlgorithm {
+    fn default() -> InsertionAlgorithm {
+        InsertionAlgorithm::BowyerWatson
+    }
+}
@@ -0,0 +1,9 @@
+//cost of locating a point in a triangulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LocationStatistics {
+    //elements the walk went through.
+    pub visited: usize,
+    //elements tested one by one after the walk stopped at a concave border of a restricted
+    //triangulation.
+    pub scanned: usize,
+}
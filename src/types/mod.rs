@@ -1,5 +1,14 @@
 pub mod fp;
 
+mod delaunay_error;
+mod duplicate_policy;
+mod insertion_algorithm;
+mod refinement_options;
+mod build_options;
+mod dihedral_histogram;
+mod flip_statistics;
+mod location_statistics;
+
 mod triangle;
 mod point2;
 mod triangulation2;
@@ -14,6 +23,14 @@ mod t4_index;
 mod triangulation3;
 
 pub use self::fp::Fp;
+pub use self::delaunay_error::*;
+pub use self::duplicate_policy::*;
+pub use self::insertion_algorithm::*;
+pub use self::refinement_options::*;
+pub use self::build_options::*;
+pub use self::dihedral_histogram::*;
+pub use self::flip_statistics::*;
+pub use self::location_statistics::*;
 
 pub use self::triangle::*;
 pub use self::point2::*;
@@ -0,0 +1,50 @@
+//largest min_angle accepted without a node budget. refinement is proven to end up to about 20
+//degrees and in practice ends up to about 33, above it it may run forever.
+pub const MAX_UNBOUNDED_MIN_ANGLE: f64 = 33.;
+
+//limits of the element quality refinement of a triangulation. angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefinementOptions {
+    //elements with a smaller angle are split. angles above MAX_UNBOUNDED_MIN_ANGLE are rejected
+    //unless max_steiner_nodes is given, they often make refinement run until the budget is used.
+    pub min_angle: f64,
+    //elements with a larger angle are split.
+    pub max_angle: f64,
+    //elements with a larger area are split.
+    pub max_area: Option<f64>,
+    //refinement stops after inserting this many nodes.
+    pub max_steiner_nodes: Option<usize>,
+}
+
+impl Default for RefinementOptions {
+    fn default() -> RefinementOptions {
+        RefinementOptions {
+            min_angle: 20.,
+            max_angle: 180.,
+            max_area: None,
+            max_steiner_nodes: None,
+        }
+    }
+}
+
+//limits of the element quality refinement of a 3d triangulation. angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refinement3Options {
+    //tetrahedra whose circumradius is larger than this many times their shortest edge are split.
+    //bounds below 2 are not guaranteed to end.
+    pub max_radius_edge_ratio: f64,
+    //refinement stops after inserting this many nodes.
+    pub max_steiner_nodes: Option<usize>,
+    //tetrahedra with a smaller dihedral angle are slivers, see Triangulation3::remove_slivers.
+    pub min_dihedral_angle: f64,
+}
+
+impl Default for Refinement3Options {
+    fn default() -> Refinement3Options {
+        Refinement3Options {
+            max_radius_edge_ratio: 2.,
+            max_steiner_nodes: None,
+            min_dihedral_angle: 10.,
+        }
+    }
+}
@@ -304,8 +304,22 @@ impl Tetrahedron {
 
     #[inline]
     pub fn is_point_in_circumsphere(&self, p: &Point3, pts: &[Point3]) -> bool {
-        //todo: investigate why this requires different order.
-        circumsphere_side(self.a(pts), self.d(pts), self.c(pts), self.b(pts), p) ==
+        //tetrahedrons are ordered so that d is right of abc, which is what insphere expects.
+        insphere(self.a(pts), self.b(pts), self.c(pts), self.d(pts), p) > 0.
+    }
+
+    //node lying on the circumsphere is decided consistently by node indexes, so tetrahedralizations
+    //of cospherical nodes do not depend on the order of insertion.
+    #[inline]
+    pub fn is_node_in_circumsphere(&self, node_index: N3Index, pts: &[Point3]) -> bool {
+        let v = &self.v;
+
+        circumsphere_side_perturbed(self.a(pts),
+                                    self.b(pts),
+                                    self.c(pts),
+                                    self.d(pts),
+                                    &pts[node_index.0],
+                                    [v[0].0, v[1].0, v[2].0, v[3].0, node_index.0]) ==
         SphereSide::Inside
     }
 
@@ -1,5 +1,4 @@
 use math::*;
-use cgmath::InnerSpace;
 use types::Point2;
 use types::n2_index::N2Index;
 use types::t3_index::T3Index;
@@ -9,6 +8,9 @@ pub struct Triangle {
     v: [N2Index; 3],
 
     n: [Option<T3Index>; 3],
+
+    //constrained edges are never flipped.
+    c: [bool; 3],
 }
 
 impl Triangle {
@@ -18,18 +20,24 @@ impl Triangle {
             Triangle {
                 v: [a, b, c],
                 n: [None, None, None],
+                c: [false, false, false],
             }
         } else {
             Triangle {
                 v: [a, c, b],
                 n: [None, None, None],
+                c: [false, false, false],
             }
         }
     }
 
     #[inline]
     pub fn new_exact(v: [N2Index; 3], n: [Option<T3Index>; 3]) -> Triangle {
-        Triangle { v: v, n: n }
+        Triangle {
+            v: v,
+            n: n,
+            c: [false, false, false],
+        }
     }
 
     #[inline]
@@ -97,22 +105,12 @@ impl Triangle {
 
     #[inline]
     pub fn is_point_inside(&self, points: &[Point2], p: &Point2) -> bool {
-        let v0 = self.c(points) - self.a(points);
-        let v1 = self.b(points) - self.a(points);
-        let v2 = p - self.a(points);
-
-        let dot00 = v0.dot(v0);
-        let dot01 = v0.dot(v1);
-        let dot02 = v0.dot(v2);
-        let dot11 = v1.dot(v1);
-        let dot12 = v1.dot(v2);
-
-        let inv_denom = 1. / (dot00 * dot11 - dot01 * dot01);
-        let u = (dot11 * dot02 - dot01 * dot12) * inv_denom;
-        let v = (dot00 * dot12 - dot01 * dot02) * inv_denom;
+        let a = self.a(points);
+        let b = self.b(points);
+        let c = self.c(points);
 
-        let eps = 1e-11; //Well. silly solution but okay.
-        (u + eps >= 0.) && (v + eps >= 0.) && (u + v <= 1. + eps)
+        //triangles are clockwise, so inside means not left of any edge.
+        orient2d(a, b, p) <= 0. && orient2d(b, c, p) <= 0. && orient2d(c, a, p) <= 0.
     }
 
     #[inline]
@@ -187,6 +185,27 @@ impl Triangle {
         self.set_neighbor(neighbor_index, update_with);
     }
 
+    #[inline]
+    pub fn is_constrained(&self, edge_index: usize) -> bool {
+        self.c[edge_index]
+    }
+
+    #[inline]
+    pub fn is_constrained_for_nodes(&self, n1: N2Index, n2: N2Index) -> bool {
+        self.c[self.get_neighbor_index(n1, n2)]
+    }
+
+    #[inline]
+    pub fn set_constrained(&mut self, edge_index: usize, constrained: bool) {
+        self.c[edge_index] = constrained;
+    }
+
+    #[inline]
+    pub fn set_constrained_for_nodes(&mut self, n1: N2Index, n2: N2Index, constrained: bool) {
+        let edge_index = self.get_neighbor_index(n1, n2);
+        self.set_constrained(edge_index, constrained);
+    }
+
     #[inline]
     pub fn get_others_two_nodes(&self, other_than: N2Index) -> (N2Index, N2Index) {
         for i in 0..self.v.len() {
@@ -1,7 +1,16 @@
 mod triangulation2;
 mod triangulation2_neighborhood;
 mod triangulation2_insertion;
+mod triangulation2_removal;
+mod triangulation2_constraints;
+mod triangulation2_domain;
+mod triangulation2_refinement;
 mod triangulation2_utilities;
+mod triangulation2_divide_and_conquer;
+#[cfg(feature = "parallel")]
+mod triangulation2_parallel;
+
+pub mod triangulation2_test_utils;
 
 pub use self::triangulation2::*;
 pub use self::triangulation2_neighborhood::*;
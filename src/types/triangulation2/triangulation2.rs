@@ -3,17 +3,42 @@ use types::Triangle;
 use types::TriangulationNeighborhood;
 use types::N2Index;
 use types::T3Index;
+use types::DelaunayError;
+use types::DuplicatePolicy;
+use types::RefinementOptions;
+use types::MAX_UNBOUNDED_MIN_ANGLE;
+use types::BuildOptions;
+use types::InsertionOrder;
+use types::BuildAlgorithm;
+use types::FlipWorkList;
+use types::FlipStatistics;
+use types::LocationStatistics;
 
 use algorithms2::element_locators::*;
+use algorithms2::nearest_node::find_nearest_node;
+use algorithms2::nearest_node::find_k_nearest_nodes;
+use algorithms2::nearest_node::find_nodes_within_radius;
 use algorithms2::lawson_flipping;
 
 use super::triangulation2_insertion;
+use super::triangulation2_removal;
+use super::triangulation2_constraints;
+use super::triangulation2_domain;
+use super::triangulation2_refinement;
 use super::triangulation2_utilities;
+use super::triangulation2_divide_and_conquer;
+#[cfg(feature = "parallel")]
+use super::triangulation2_parallel;
 
 pub struct Triangulation2 {
     nodes: Vec<Point2>,
     elements: Vec<Triangle>,
     last_added_element_index: T3Index,
+    duplicate_policy: DuplicatePolicy,
+    flip_work_list: FlipWorkList,
+    flip_statistics: FlipStatistics,
+    //elements outside of the domain were deleted, so the border is not convex.
+    restricted: bool,
 }
 
 impl Triangulation2 {
@@ -25,6 +50,10 @@ impl Triangulation2 {
             nodes: nodes,
             elements: elements,
             last_added_element_index: T3Index(0),
+            duplicate_policy: DuplicatePolicy::default(),
+            flip_work_list: FlipWorkList::default(),
+            flip_statistics: FlipStatistics::default(),
+            restricted: false,
         };
 
         TriangulationNeighborhood::teach_triangles_of_neighborhood(&mut tr.elements);
@@ -33,20 +62,38 @@ impl Triangulation2 {
 
     #[inline]
     pub fn new(nodes: &[Point2]) -> Triangulation2 {
-        let (top_left_index, top_right_index, bottom_left_index, bottom_right_index) =
-            triangulation2_utilities::find_corner_nodes(nodes);
-        let mut indexes_except_corner: Vec<usize> = Vec::new();
+        match Triangulation2::try_new(nodes) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
 
-        for i in 0..nodes.len() {
-            if i != top_left_index && i != top_right_index && i != bottom_left_index &&
-               i != bottom_right_index {
-                indexes_except_corner.push(i);
+    pub fn try_new(nodes: &[Point2]) -> Result<Triangulation2, DelaunayError> {
+        Triangulation2::try_new_with_options(nodes, &BuildOptions::default())
+    }
+
+    #[inline]
+    pub fn new_with_options(nodes: &[Point2], options: &BuildOptions) -> Triangulation2 {
+        match Triangulation2::try_new_with_options(nodes, options) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_new_with_options(nodes: &[Point2],
+                                options: &BuildOptions)
+                                -> Result<Triangulation2, DelaunayError> {
+        for (index, node) in nodes.iter().enumerate() {
+            if !node.x.is_finite() || !node.y.is_finite() {
+                return Err(DelaunayError::InvalidCoordinates(index));
             }
         }
 
-        indexes_except_corner.sort_by(|a, b| if nodes[*a].x < nodes[*b].x {
-                                          ::std::cmp::Ordering::Less
-                                      } else if nodes[*a].x > nodes[*b].x {
+        let mut sorted_indexes: Vec<usize> = (0..nodes.len()).collect();
+
+        sorted_indexes.sort_by(|a, b| if nodes[*a].x < nodes[*b].x {
+                                   ::std::cmp::Ordering::Less
+                               } else if nodes[*a].x > nodes[*b].x {
             ::std::cmp::Ordering::Greater
         } else {
             if nodes[*a].y < nodes[*b].y {
@@ -54,34 +101,104 @@ impl Triangulation2 {
             } else if nodes[*a].y > nodes[*b].y {
                 ::std::cmp::Ordering::Greater
             } else {
-                panic!("Triangulation received equal nodes. x {} y {}",
-                       nodes[*a].x,
-                       nodes[*a].y);
+                a.cmp(b)
             }
         });
 
+        for pair in sorted_indexes.windows(2) {
+            if nodes[pair[0]] == nodes[pair[1]] {
+                return Err(DelaunayError::DuplicateNodes(pair[0], pair[1]));
+            }
+        }
+
+        if options.algorithm == BuildAlgorithm::DivideAndConquer {
+            if triangulation2_utilities::find_first_not_collinear(nodes, &sorted_indexes)
+                .is_none() {
+                return Err(DelaunayError::CollinearNodes);
+            }
+            return Ok(triangulation2_divide_and_conquer::build(nodes, &sorted_indexes));
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            if options.threads > 1 {
+                if let Some(triangulation) =
+                    triangulation2_parallel::build(nodes, &sorted_indexes, options) {
+                    return Ok(triangulation);
+                }
+            }
+        }
+
+        let sorted_indexes = match options.insertion_order {
+            InsertionOrder::Lexicographic => sorted_indexes,
+            order => triangulation2_utilities::curve_order(nodes, order),
+        };
+
+        let third_position =
+            match triangulation2_utilities::find_first_not_collinear(nodes, &sorted_indexes) {
+                Some(third_position) => third_position,
+                None => return Err(DelaunayError::CollinearNodes),
+            };
+
         let nodes = Vec::from(nodes);
-        let mut eles = vec![Triangle::new(&nodes,
-                                          N2Index(top_left_index),
-                                          N2Index(bottom_right_index),
-                                          N2Index(bottom_left_index)),
-                            Triangle::new(&nodes,
-                                          N2Index(top_left_index),
-                                          N2Index(top_right_index),
-                                          N2Index(bottom_right_index))];
-
-        TriangulationNeighborhood::teach_triangles_of_neighborhood(&mut eles);
+        let eles = vec![Triangle::new(&nodes,
+                                      N2Index(sorted_indexes[0]),
+                                      N2Index(sorted_indexes[1]),
+                                      N2Index(sorted_indexes[third_position]))];
+
         let mut triangulation = Triangulation2 {
             elements: eles,
             last_added_element_index: T3Index(0),
+            duplicate_policy: DuplicatePolicy::default(),
+            flip_work_list: FlipWorkList::default(),
+            flip_statistics: FlipStatistics::default(),
+            restricted: false,
             nodes: nodes,
         };
 
-        for index in indexes_except_corner.into_iter() {
-            triangulation.insert_into_triangulation(N2Index(index));
+        //in lexicographic order every node is inserted outside of the current hull, as if the
+        //hull was connected to a node in infinity. this way no helper geometry has to be removed
+        //at the end. curve orders insert most nodes inside and keep the walks short.
+        for (position, index) in sorted_indexes.into_iter().enumerate() {
+            if position < 2 || position == third_position {
+                continue;
+            }
+
+            let location_result = triangulation.locate(&triangulation.nodes[index], None).0;
+            triangulation.insert_into_triangulation(N2Index(index), location_result)?;
+        }
+
+        Ok(triangulation)
+    }
+
+    #[inline]
+    pub fn from_pslg(nodes: &[Point2],
+                     segments: &[(N2Index, N2Index)],
+                     holes: &[Point2])
+                     -> Triangulation2 {
+        match Triangulation2::try_from_pslg(nodes, segments, holes) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //triangulation of a planar straight line graph restricted to the domain bounded by its
+    //segments. elements outside of the outer boundary or reachable from a hole point without
+    //crossing a segment are deleted, nodes which were only a part of them stay unused. later
+    //insertions outside of the domain are rejected.
+    pub fn try_from_pslg(nodes: &[Point2],
+                         segments: &[(N2Index, N2Index)],
+                         holes: &[Point2])
+                         -> Result<Triangulation2, DelaunayError> {
+        let mut triangulation = Triangulation2::try_new(nodes)?;
+        for &(first, last) in segments.iter() {
+            triangulation.try_insert_constraint(first, last)?;
         }
 
-        triangulation
+        triangulation2_domain::remove_outside_of_domain(&mut triangulation, holes)?;
+        triangulation.last_added_element_index = T3Index(0);
+        triangulation.restricted = true;
+        Ok(triangulation)
     }
 
     #[inline]
@@ -94,11 +211,60 @@ impl Triangulation2 {
         &self.elements
     }
 
+    #[inline]
+    pub fn nodes_mut(&mut self) -> &mut Vec<Point2> {
+        &mut self.nodes
+    }
+
     #[inline]
     pub fn elements_mut(&mut self) -> &mut Vec<Triangle> {
         &mut self.elements
     }
 
+    #[inline]
+    pub fn is_restricted(&self) -> bool {
+        self.restricted
+    }
+
+    #[inline]
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    //applies to the nodes inserted after the call, nodes given to new are always rejected.
+    #[inline]
+    pub fn set_duplicate_policy(&mut self, duplicate_policy: DuplicatePolicy) {
+        self.duplicate_policy = duplicate_policy;
+    }
+
+    #[inline]
+    pub fn flip_work_list(&self) -> FlipWorkList {
+        self.flip_work_list
+    }
+
+    //applies to the nodes inserted after the call.
+    #[inline]
+    pub fn set_flip_work_list(&mut self, flip_work_list: FlipWorkList) {
+        self.flip_work_list = flip_work_list;
+    }
+
+    //flips done by the insertions since the triangulation was created or the statistics were
+    //reset.
+    #[inline]
+    pub fn flip_statistics(&self) -> FlipStatistics {
+        self.flip_statistics
+    }
+
+    #[inline]
+    pub fn flip_statistics_mut(&mut self) -> &mut FlipStatistics {
+        &mut self.flip_statistics
+    }
+
+    #[inline]
+    pub fn reset_flip_statistics(&mut self) {
+        self.flip_statistics = FlipStatistics::default();
+    }
+
     #[inline]
     pub fn is_inside_circumcircle(&self, tr: &Triangle, p: &Point2) -> bool {
         let a = tr.a(self.nodes());
@@ -108,83 +274,728 @@ impl Triangulation2 {
         ::math::circumcircle_side(a, b, c, p) == ::math::CircleSide::Inside
     }
 
+    //node lying on the circumcircle is decided consistently by node indexes, so triangulations
+    //of cocircular nodes do not depend on the order of insertion.
+    #[inline]
+    pub fn is_node_inside_circumcircle(&self, tr: &Triangle, node_index: N2Index) -> bool {
+        let v = tr.nodes();
+
+        ::math::circumcircle_side_perturbed(tr.a(self.nodes()),
+                                            tr.b(self.nodes()),
+                                            tr.c(self.nodes()),
+                                            &self.nodes[node_index.0],
+                                            [v[0].0, v[1].0, v[2].0, node_index.0]) ==
+        ::math::CircleSide::Inside
+    }
+
     #[inline]
-    pub fn insert_node(&mut self, p: &Point2) {
+    pub fn insert_node(&mut self, p: &Point2) -> N2Index {
+        match self.try_insert_node(p) {
+            Ok(node_index) => node_index,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //returns the index the node ended up as, which is an existing node when the duplicate
+    //policy merged them. on error the triangulation is left as it was before the call.
+    pub fn try_insert_node(&mut self, p: &Point2) -> Result<N2Index, DelaunayError> {
+        let new_node_index = N2Index(self.nodes.len());
+        if !p.x.is_finite() || !p.y.is_finite() {
+            return Err(DelaunayError::InvalidCoordinates(new_node_index.0));
+        }
+
+        let location_result = self.locate(p, None).0;
+
+        if let Some(existing_node_index) = self.find_duplicate(&location_result, p) {
+            return match self.duplicate_policy {
+                DuplicatePolicy::Reject => {
+                    Err(DelaunayError::DuplicateNodes(existing_node_index.0, new_node_index.0))
+                }
+                DuplicatePolicy::Merge |
+                DuplicatePolicy::MergeWithin(_) => Ok(existing_node_index),
+            };
+        }
+
+        if let LocationResult::Outside(_, _) = location_result {
+            if self.restricted {
+                return Err(DelaunayError::OutsideOfDomain);
+            }
+        }
+
         self.nodes.push(*p);
-        let new_node_index = N2Index(self.nodes.len() - 1);
+        match self.insert_into_triangulation(new_node_index, location_result) {
+            Ok(()) => Ok(new_node_index),
+            Err(error) => {
+                self.nodes.pop();
+                Err(error)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove_node(&mut self, node_index: N2Index) {
+        if let Err(error) = self.try_remove_node(node_index) {
+            panic!("{}", error);
+        }
+    }
+
+    //removed node keeps its coordinates in nodes, so indexes of the other nodes do not change.
+    //elements are compacted and some of them get a new index. on error the triangulation is left
+    //as it was before the call.
+    pub fn try_remove_node(&mut self, node_index: N2Index) -> Result<(), DelaunayError> {
+        let ele_index = self.find_element_with_node(node_index)?;
+        self.last_added_element_index =
+            triangulation2_removal::remove_node(self, ele_index, node_index, self.restricted)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn insert_constraint(&mut self, first: N2Index, last: N2Index) {
+        if let Err(error) = self.try_insert_constraint(first, last) {
+            panic!("{}", error);
+        }
+    }
 
-        self.insert_into_triangulation(new_node_index);
+    //forces the segment between two nodes to be an edge which no flip removes. segment passing
+    //through other nodes becomes a chain of constrained edges. a segment leaving a restricted
+    //triangulation is rejected with OutsideOfDomain. on error the triangulation is left as it
+    //was before the call.
+    pub fn try_insert_constraint(&mut self,
+                                 first: N2Index,
+                                 last: N2Index)
+                                 -> Result<(), DelaunayError> {
+        let first_element = self.find_element_with_node(first)?;
+        self.find_element_with_node(last)?;
+
+        if first == last {
+            return Err(DelaunayError::DuplicateNodes(first.0, last.0));
+        }
+
+        triangulation2_constraints::insert_constraint(self, first_element, first, last)
     }
 
     #[inline]
-    fn insert_into_triangulation(&mut self, new_node_index: N2Index) {
-        let location_result = locate_element_containing(self.last_added_element_index,
-                                                        &self.elements,
-                                                        &self.nodes,
-                                                        &self.nodes[new_node_index.0]);
+    pub fn flip_edge(&mut self, ele_index: T3Index, edge_index: usize) -> (T3Index, T3Index) {
+        match self.try_flip_edge(ele_index, edge_index) {
+            Ok(new_elements) => new_elements,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //replaces the edge of the element with the other diagonal of the quad made by the element
+    //and its neighbor across the edge, whether the edge is locally delaunay or not. the quad has
+    //to be strictly convex, border and constrained edges are not flipped. the two new elements
+    //keep the indexes of the old ones.
+    pub fn try_flip_edge(&mut self,
+                         ele_index: T3Index,
+                         edge_index: usize)
+                         -> Result<(T3Index, T3Index), DelaunayError> {
+        let bottom_node_index = {
+            let ele = &self.elements[ele_index.0];
+            let (n1, n2) = ele.get_edge(edge_index);
+            let neighbor_index = match ele.get_neighbor_from_index(edge_index) {
+                Some(neighbor_index) => neighbor_index,
+                None => return Err(DelaunayError::NotFlippable),
+            };
+            if ele.is_constrained(edge_index) {
+                return Err(DelaunayError::NotFlippable);
+            }
+
+            let bottom = ele.get_other_last_node(n1, n2);
+            let top = self.elements[neighbor_index.0].get_other_last_node(n1, n2);
+            let (bottom_point, top_point) = (&self.nodes[bottom.0], &self.nodes[top.0]);
+            let side1 = ::math::orient2d(bottom_point, top_point, &self.nodes[n1.0]);
+            let side2 = ::math::orient2d(bottom_point, top_point, &self.nodes[n2.0]);
+
+            //new diagonal has to separate the nodes of the old one.
+            if !(side1 < 0. && side2 > 0. || side1 > 0. && side2 < 0.) {
+                return Err(DelaunayError::NotFlippable);
+            }
+            bottom
+        };
+
+        Ok(lawson_flipping::flip(self, bottom_node_index, ele_index))
+    }
+
+    //edge is locally delaunay when the node across it does not lie in the circumcircle of the
+    //element. border edges always are, constrained edges are tested like the others.
+    pub fn is_locally_delaunay(&self, ele_index: T3Index, edge_index: usize) -> bool {
+        let ele = &self.elements[ele_index.0];
+        match ele.get_neighbor_from_index(edge_index) {
+            Some(neighbor_index) => {
+                let (n1, n2) = ele.get_edge(edge_index);
+                let top = self.elements[neighbor_index.0].get_other_last_node(n1, n2);
+                !self.is_node_inside_circumcircle(ele, top)
+            }
+            None => true,
+        }
+    }
+
+    //element containing the point, walking from the hint or from the last added element when
+    //there is none. the walk stops at a concave border of a restricted triangulation, then the
+    //element containing the point is searched among all of them, which is counted as scanned.
+    pub fn locate(&self,
+                  p: &Point2,
+                  hint: Option<T3Index>)
+                  -> (LocationResult, LocationStatistics) {
+        let start = match hint {
+            Some(hint) if hint.0 < self.elements.len() => hint,
+            _ => self.last_added_element_index,
+        };
+        let (location_result, visited) =
+            locate_counting_visited(start, &self.elements, &self.nodes, p);
+        let mut statistics = LocationStatistics {
+            visited: visited,
+            scanned: 0,
+        };
 
         match location_result {
-            LocationResult::InElement(ele_index) => {
-                self.last_added_element_index = ele_index;
-                let (t1_index, t2_index, t3_index) =
-                    triangulation2_insertion::insert_into_element(self, ele_index, new_node_index);
-                lawson_flipping::propagating_flip(self, new_node_index, t1_index);
-                lawson_flipping::propagating_flip(self, new_node_index, t2_index);
-                lawson_flipping::propagating_flip(self, new_node_index, t3_index);
-            }
-            LocationResult::OnEdge(ele_index, edge_index) => {
-                self.last_added_element_index = ele_index;
-                let (neighbor_index, edge_node1, edge_node2) = {
-                    let ele: &Triangle = &self.elements[ele_index.0];
-                    let neighbor_index = ele.get_neighbor_from_index(edge_index);
-                    let edge = ele.get_edge(edge_index);
-
-                    (neighbor_index, edge.0, edge.1)
-                };
-
-
-                if let Some(neighbor_index) = neighbor_index {
-                    triangulation2_insertion::insert_into_element(self, ele_index, new_node_index);
-                    let neighbor_last_node = {
-                        let neighbor: &Triangle = &self.elements[neighbor_index.0];
-                        neighbor.get_other_last_node(edge_node1, edge_node2)
-                    };
-                    lawson_flipping::try_flip(self, neighbor_last_node, neighbor_index);
-                } else {
-                    let (ele1, ele2) = triangulation2_insertion::insert_in_edge(self,
-                                                                                ele_index,
-                                                                                new_node_index,
-                                                                                edge_index);
-
-                    lawson_flipping::propagating_flip(self, new_node_index, ele1);
-                    lawson_flipping::propagating_flip(self, new_node_index, ele2);
-                }
+            LocationResult::Outside(_, _) if self.restricted => {
+                let (location_result, scanned) = locate_by_scan(&self.elements, &self.nodes, p);
+                statistics.scanned = scanned;
+                (location_result, statistics)
+            }
+            _ => (location_result, statistics),
+        }
+    }
+
+    //node nearest to the point, searched over the elements by their distance to the point from
+    //the element containing it. the elements do not have to be delaunay, so constrained and
+    //flipped edges are fine.
+    pub fn nearest_node(&self, p: &Point2) -> N2Index {
+        match self.locate(p, None).0 {
+            LocationResult::OnVertex(_, node_index) => node_index,
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnEdge(ele_index, _) |
+            LocationResult::Outside(ele_index, _) => {
+                find_nearest_node(ele_index, &self.elements, &self.nodes, p, self.restricted)
             }
         }
     }
+
+    //at most k nodes nearest to the point, nearest first.
+    pub fn k_nearest(&self, p: &Point2, k: usize) -> Vec<N2Index> {
+        let ele_index = match self.locate(p, None).0 {
+            LocationResult::OnVertex(ele_index, _) |
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnEdge(ele_index, _) |
+            LocationResult::Outside(ele_index, _) => ele_index,
+        };
+
+        find_k_nearest_nodes(ele_index, &self.elements, &self.nodes, p, k, self.restricted)
+    }
+
+    //splits elements with a too small or too large angle or a too large area. constrained and
+    //border edges are split instead of being crossed. returns the number of inserted nodes.
+    pub fn refine(&mut self, options: &RefinementOptions) -> usize {
+        match self.try_refine(options) {
+            Ok(inserted) => inserted,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_refine(&mut self, options: &RefinementOptions) -> Result<usize, DelaunayError> {
+        self.refine_checked(options, None::<fn(&Point2) -> f64>)
+    }
+
+    //same as refine, with the target edge length at a point given by size. elements with an edge
+    //longer than the size at their centroid are split, so the size has to be positive. a
+    //background mesh can be used through a closure interpolating on it.
+    pub fn refine_with_size<F>(&mut self, options: &RefinementOptions, size: F) -> usize
+        where F: Fn(&Point2) -> f64
+    {
+        match self.try_refine_with_size(options, size) {
+            Ok(inserted) => inserted,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_refine_with_size<F>(&mut self,
+                                   options: &RefinementOptions,
+                                   size: F)
+                                   -> Result<usize, DelaunayError>
+        where F: Fn(&Point2) -> f64
+    {
+        self.refine_checked(options, Some(size))
+    }
+
+    fn refine_checked<F>(&mut self,
+                         options: &RefinementOptions,
+                         size: Option<F>)
+                         -> Result<usize, DelaunayError>
+        where F: Fn(&Point2) -> f64
+    {
+        if options.min_angle > MAX_UNBOUNDED_MIN_ANGLE && options.max_steiner_nodes.is_none() {
+            return Err(DelaunayError::UnboundedRefinement(options.min_angle));
+        }
+
+        Ok(triangulation2_refinement::refine(self, options, size))
+    }
+
+    fn find_element_with_node(&self, node_index: N2Index) -> Result<T3Index, DelaunayError> {
+        if node_index.0 >= self.nodes.len() {
+            return Err(DelaunayError::UnknownNode(node_index.0));
+        }
+
+        match self.locate(&self.nodes[node_index.0], None).0 {
+            LocationResult::OnVertex(ele_index, found) if found == node_index => Ok(ele_index),
+            _ => Err(DelaunayError::UnknownNode(node_index.0)),
+        }
+    }
+
+    //existing node which the point at the location should be merged with or rejected as.
+    fn find_duplicate(&self, location_result: &LocationResult, p: &Point2) -> Option<N2Index> {
+        let ele_index = match *location_result {
+            LocationResult::OnVertex(_, existing_node_index) => return Some(existing_node_index),
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnEdge(ele_index, _) |
+            LocationResult::Outside(ele_index, _) => ele_index,
+        };
+
+        if let DuplicatePolicy::MergeWithin(tolerance) = self.duplicate_policy {
+            return find_nodes_within_radius(ele_index,
+                                            &self.elements,
+                                            &self.nodes,
+                                            p,
+                                            tolerance,
+                                            self.restricted)
+                .first()
+                .cloned();
+        }
+
+        None
+    }
+
+    #[inline]
+    fn insert_into_triangulation(&mut self,
+                                 new_node_index: N2Index,
+                                 location_result: LocationResult)
+                                 -> Result<(), DelaunayError> {
+        self.last_added_element_index =
+            triangulation2_insertion::insert_at_location(self, new_node_index, location_result)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use types::BuildOptions;
+    use types::DelaunayError;
+    use types::DuplicatePolicy;
+    use types::FlipStatistics;
+    use types::FlipWorkList;
+    use types::InsertionOrder;
+    use types::RefinementOptions;
     use types::Point2;
     use types::Triangle;
     use types::N2Index;
     use types::T3Index;
+    use types::LocationStatistics;
+    use algorithms2::element_locators::LocationResult;
     use super::Triangulation2;
+    use types::triangulation2_test_utils::assert_neighborhood;
+    use types::triangulation2_test_utils::random_points;
+    use types::triangulation2_test_utils::sorted_elements;
 
     #[test]
-    fn testing_bounding_box_creation() {
+    fn testing_square_creation() {
         let triangulation: Triangulation2 = Triangulation2::new(&[Point2::new(0., 1.),
                                                                   Point2::new(1., 1.),
                                                                   Point2::new(0., 0.),
                                                                   Point2::new(1., 0.)]);
 
+        //all four nodes are on one circle, node 3 has the highest index so it is perturbed
+        //outwards the most and the diagonal avoids it.
         assert_eq!(2, triangulation.elements.len());
-        assert_eq!(Triangle::new_exact([N2Index(0), N2Index(3), N2Index(2)],
+        assert_eq!(Triangle::new_exact([N2Index(2), N2Index(1), N2Index(3)],
                                        [Some(T3Index(1)), None, None]),
                    triangulation.elements()[0]);
-        assert_eq!(Triangle::new_exact([N2Index(0), N2Index(1), N2Index(3)],
-                                       [None, None, Some(T3Index(0))]),
+        assert_eq!(Triangle::new_exact([N2Index(0), N2Index(1), N2Index(2)],
+                                       [None, Some(T3Index(0)), None]),
                    triangulation.elements()[1]);
     }
+
+    #[test]
+    fn testing_creation_covers_convex_hull() {
+        //node 4 is collinear with the first two nodes in sorted order.
+        let triangulation: Triangulation2 = Triangulation2::new(&[Point2::new(0., 1.),
+                                                                  Point2::new(1., 1.),
+                                                                  Point2::new(0., 0.),
+                                                                  Point2::new(1., 0.),
+                                                                  Point2::new(2., 2.),
+                                                                  Point2::new(0., 0.5)]);
+
+        assert_eq!(5, triangulation.elements.len());
+        for ele in triangulation.elements() {
+            ele.assert_order(triangulation.nodes());
+        }
+
+        assert!(triangulation.elements()
+            .iter()
+            .any(|e| e.is_made_of([N2Index(0), N2Index(1), N2Index(4)])));
+        assert!(triangulation.elements()
+            .iter()
+            .any(|e| e.is_made_of([N2Index(1), N2Index(3), N2Index(4)])));
+    }
+
+    #[test]
+    fn testing_grid_does_not_depend_on_insertion_order() {
+        //every triangle of a regular grid has a fourth node on its circumcircle.
+        let mut grid = Vec::new();
+        for i in 0..25 {
+            let position = (i * 7) % 25;
+            grid.push(Point2::new((position % 5) as f64, (position / 5) as f64));
+        }
+
+        let all_at_once = Triangulation2::new(&grid);
+
+        let mut one_by_one = Triangulation2::new(&grid[..4]);
+        for node in grid[4..].iter() {
+            one_by_one.insert_node(node);
+        }
+
+        assert_eq!(32, all_at_once.elements().len());
+        assert_eq!(sorted_elements(&all_at_once), sorted_elements(&one_by_one));
+    }
+
+    #[test]
+    fn testing_curve_insertion_orders() {
+        let nodes = random_points(5, 2000);
+
+        let lexicographic = Triangulation2::new(&nodes);
+        for &insertion_order in [InsertionOrder::Hilbert, InsertionOrder::Morton].iter() {
+            let options = BuildOptions {
+                insertion_order: insertion_order,
+                ..BuildOptions::default()
+            };
+            let triangulation = Triangulation2::new_with_options(&nodes, &options);
+
+            assert_eq!(sorted_elements(&lexicographic), sorted_elements(&triangulation));
+        }
+    }
+
+    #[test]
+    fn testing_errors_on_invalid_input() {
+        let square = [Point2::new(0., 1.),
+                      Point2::new(1., 1.),
+                      Point2::new(0., 0.),
+                      Point2::new(1., 0.)];
+
+        match Triangulation2::try_new(&[square[0], square[1], square[2], square[1]]) {
+            Err(DelaunayError::DuplicateNodes(1, 3)) => (),
+            other => panic!("expected duplicate nodes, got {:?}", other.err()),
+        }
+        match Triangulation2::try_new(&[square[0], Point2::new(::std::f64::NAN, 0.), square[2]]) {
+            Err(DelaunayError::InvalidCoordinates(1)) => (),
+            other => panic!("expected invalid coordinates, got {:?}", other.err()),
+        }
+        match Triangulation2::try_new(&[Point2::new(0., 0.), Point2::new(1., 1.)]) {
+            Err(DelaunayError::CollinearNodes) => (),
+            other => panic!("expected collinear nodes, got {:?}", other.err()),
+        }
+
+        let mut triangulation = Triangulation2::try_new(&square).unwrap();
+        let elements = triangulation.elements().clone();
+
+        match triangulation.try_insert_node(&Point2::new(1., 0.)) {
+            Err(DelaunayError::DuplicateNodes(3, 4)) => (),
+            other => panic!("expected duplicate nodes, got {:?}", other),
+        }
+        match triangulation.try_insert_node(&Point2::new(0.5, ::std::f64::INFINITY)) {
+            Err(DelaunayError::InvalidCoordinates(4)) => (),
+            other => panic!("expected invalid coordinates, got {:?}", other),
+        }
+
+        assert_eq!(4, triangulation.nodes().len());
+        assert_eq!(elements, *triangulation.elements());
+
+        assert_eq!(N2Index(4),
+                   triangulation.try_insert_node(&Point2::new(0.5, 0.5)).unwrap());
+        assert_eq!(4, triangulation.elements().len());
+    }
+
+    #[test]
+    fn testing_duplicate_policies() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 1.),
+                                                      Point2::new(1., 1.),
+                                                      Point2::new(0., 0.),
+                                                      Point2::new(1., 0.)]);
+        assert_eq!(DuplicatePolicy::Reject, triangulation.duplicate_policy());
+
+        triangulation.set_duplicate_policy(DuplicatePolicy::Merge);
+        assert_eq!(N2Index(3), triangulation.insert_node(&Point2::new(1., 0.)));
+        assert_eq!(N2Index(4), triangulation.insert_node(&Point2::new(0.5, 0.5)));
+        assert_eq!(N2Index(5), triangulation.insert_node(&Point2::new(0.505, 0.5)));
+
+        triangulation.set_duplicate_policy(DuplicatePolicy::MergeWithin(0.01));
+        assert_eq!(N2Index(5), triangulation.insert_node(&Point2::new(0.51, 0.5)));
+        assert_eq!(N2Index(4), triangulation.insert_node(&Point2::new(0.5, 0.499)));
+        assert_eq!(N2Index(3), triangulation.insert_node(&Point2::new(1.005, -0.005)));
+        assert_eq!(N2Index(6), triangulation.insert_node(&Point2::new(0.5, 0.2)));
+
+        assert_eq!(7, triangulation.nodes().len());
+        assert_eq!(8, triangulation.elements().len());
+        for ele in triangulation.elements() {
+            ele.assert_order(triangulation.nodes());
+        }
+    }
+
+    #[test]
+    fn testing_flip_work_lists() {
+        let nodes = random_points(11, 300);
+        let corners = [Point2::new(-1., -1.),
+                       Point2::new(2., -1.),
+                       Point2::new(-1., 2.),
+                       Point2::new(2., 2.)];
+
+        let mut built = Vec::new();
+        for &work_list in [FlipWorkList::Stack, FlipWorkList::Queue].iter() {
+            let mut triangulation = Triangulation2::new(&corners);
+            assert_eq!(FlipWorkList::Stack, triangulation.flip_work_list());
+            triangulation.set_flip_work_list(work_list);
+            triangulation.reset_flip_statistics();
+
+            for node in nodes.iter() {
+                triangulation.insert_node(node);
+            }
+
+            let statistics = triangulation.flip_statistics();
+            assert!(statistics.flips > nodes.len());
+            assert!(statistics.max_work_list_len >= 3);
+            assert_neighborhood(&triangulation);
+            built.push(sorted_elements(&triangulation));
+
+            triangulation.reset_flip_statistics();
+            assert_eq!(FlipStatistics::default(), triangulation.flip_statistics());
+        }
+
+        //both work lists end in the same delaunay triangulation of the random nodes.
+        let mut all_nodes = corners.to_vec();
+        all_nodes.extend(nodes);
+        assert_eq!(sorted_elements(&Triangulation2::new(&all_nodes)), built[0]);
+        assert_eq!(built[0], built[1]);
+    }
+
+    #[test]
+    fn testing_edge_flips() {
+        //kite whose diagonal between nodes 0 and 2 is not delaunay, node 4 makes the quad of
+        //the elements at edge 1-2 concave.
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(2., -1.),
+                     Point2::new(4., 0.),
+                     Point2::new(2., 1.),
+                     Point2::new(5., -3.)];
+        let elements = vec![Triangle::new(&nodes, N2Index(0), N2Index(3), N2Index(2)),
+                            Triangle::new(&nodes, N2Index(0), N2Index(2), N2Index(1)),
+                            Triangle::new(&nodes, N2Index(1), N2Index(2), N2Index(4))];
+        let mut triangulation = Triangulation2::new_from_prebuilt_triangulation(nodes.to_vec(),
+                                                                                elements);
+        let diagonal = triangulation.elements()[0].get_neighbor_index(N2Index(0), N2Index(2));
+
+        assert!(!triangulation.is_locally_delaunay(T3Index(0), diagonal));
+        assert_eq!((T3Index(1), T3Index(0)), triangulation.flip_edge(T3Index(0), diagonal));
+        for ele in triangulation.elements() {
+            ele.assert_order(triangulation.nodes());
+            assert!(!ele.is_made_of([N2Index(0), N2Index(2), N2Index(1)]));
+        }
+
+        let diagonal = triangulation.elements()[0].get_neighbor_index(N2Index(1), N2Index(3));
+        assert!(triangulation.is_locally_delaunay(T3Index(0), diagonal));
+
+        //delaunay edge is flipped back on request.
+        triangulation.flip_edge(T3Index(0), diagonal);
+        assert!(triangulation.elements()[0].is_made_of([N2Index(0), N2Index(2), N2Index(3)]) ||
+                triangulation.elements()[1].is_made_of([N2Index(0), N2Index(2), N2Index(3)]));
+
+        let ele_index = T3Index(triangulation.elements()
+            .iter()
+            .position(|e| e.is_made_of([N2Index(1), N2Index(2), N2Index(4)]))
+            .unwrap());
+        let border = triangulation.elements()[ele_index.0].get_neighbor_index(N2Index(2),
+                                                                              N2Index(4));
+        let concave = triangulation.elements()[ele_index.0].get_neighbor_index(N2Index(1),
+                                                                               N2Index(2));
+        assert!(triangulation.is_locally_delaunay(ele_index, border));
+        for &edge_index in [border, concave].iter() {
+            match triangulation.try_flip_edge(ele_index, edge_index) {
+                Err(DelaunayError::NotFlippable) => (),
+                other => panic!("expected not flippable, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn testing_constrained_edge_is_not_flipped() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(2., -1.),
+                     Point2::new(4., 0.),
+                     Point2::new(2., 1.)];
+        let mut triangulation = Triangulation2::new(&nodes);
+        triangulation.insert_constraint(N2Index(0), N2Index(2));
+
+        let ele_index = T3Index(0);
+        let edge_index = triangulation.elements()[0].get_neighbor_index(N2Index(0), N2Index(2));
+        assert!(!triangulation.is_locally_delaunay(ele_index, edge_index));
+        match triangulation.try_flip_edge(ele_index, edge_index) {
+            Err(DelaunayError::NotFlippable) => (),
+            other => panic!("expected not flippable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn testing_nearest_nodes() {
+        let mut nodes = Vec::new();
+        for i in 0..100 {
+            nodes.push(Point2::new((i % 10) as f64 + 0.013 * ((i * 7) % 11) as f64,
+                                   (i / 10) as f64 + 0.017 * ((i * 3) % 13) as f64));
+        }
+        let triangulation = Triangulation2::new(&nodes);
+        let distance = |p: &Point2, n: &N2Index| ::math::distance2_squared(p, &nodes[n.0]);
+
+        let points = [Point2::new(4.3, 5.6),
+                      Point2::new(-3., 4.),
+                      Point2::new(12., 20.),
+                      nodes[42]];
+        for p in points.iter() {
+            let brute_force = (0..nodes.len())
+                .map(N2Index)
+                .min_by(|a, b| distance(p, a).partial_cmp(&distance(p, b)).unwrap())
+                .unwrap();
+            assert_eq!(brute_force, triangulation.nearest_node(p));
+
+            let nearest = triangulation.k_nearest(p, 5);
+            assert_eq!(5, nearest.len());
+            assert_eq!(brute_force, nearest[0]);
+            assert!(nearest.windows(2).all(|w| distance(p, &w[0]) <= distance(p, &w[1])));
+        }
+        assert_eq!(N2Index(42), triangulation.nearest_node(&nodes[42]));
+    }
+
+    #[test]
+    fn testing_nearest_nodes_in_restricted_triangulation() {
+        //u shaped domain, nodes of one arm are far apart along the edges from the other arm.
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(5., 0.),
+                     Point2::new(5., 5.),
+                     Point2::new(4., 5.),
+                     Point2::new(4., 1.),
+                     Point2::new(1., 1.),
+                     Point2::new(1., 5.),
+                     Point2::new(0., 5.)];
+        let segments: Vec<(N2Index, N2Index)> =
+            (0..8).map(|i| (N2Index(i), N2Index((i + 1) % 8))).collect();
+        let mut triangulation = Triangulation2::from_pslg(&nodes, &segments, &[]);
+        triangulation.refine(&RefinementOptions {
+            max_area: Some(0.2),
+            ..RefinementOptions::default()
+        });
+
+        let nodes = triangulation.nodes().clone();
+        let distance = |p: &Point2, n: &N2Index| ::math::distance2_squared(p, &nodes[n.0]);
+        for i in 0..15 {
+            for j in 0..15 {
+                let p = Point2::new(0.5 * i as f64 - 1.2, 0.5 * j as f64 - 1.1);
+                let mut brute_force: Vec<N2Index> = (0..nodes.len()).map(N2Index).collect();
+                brute_force.sort_by(|a, b| distance(&p, a).partial_cmp(&distance(&p, b)).unwrap());
+
+                assert_eq!(distance(&p, &brute_force[0]),
+                           distance(&p, &triangulation.nearest_node(&p)));
+                let nearest = triangulation.k_nearest(&p, 4);
+                assert_eq!(distance(&p, &brute_force[3]), distance(&p, &nearest[3]));
+            }
+        }
+    }
+
+    #[test]
+    fn testing_locate_with_hint() {
+        let mut nodes = Vec::new();
+        for i in 0..400 {
+            nodes.push(Point2::new((i % 20) as f64 + ((i * 7) % 11) as f64 / 64.,
+                                   (i / 20) as f64 + ((i * 3) % 13) as f64 / 64.));
+        }
+        let triangulation = Triangulation2::new(&nodes);
+
+        let p = Point2::new(15.3, 14.6);
+        let (location_result, statistics) = triangulation.locate(&p, Some(T3Index(0)));
+        let ele_index = match location_result {
+            LocationResult::InElement(ele_index) => ele_index,
+            other => panic!("expected element, got {:?}", other),
+        };
+        assert!(statistics.visited > 1);
+        assert_eq!(0, statistics.scanned);
+        let one_element = LocationStatistics {
+            visited: 1,
+            scanned: 0,
+        };
+        assert_eq!((LocationResult::InElement(ele_index), one_element),
+                   triangulation.locate(&p, Some(ele_index)));
+
+        match triangulation.locate(&nodes[42], Some(ele_index)).0 {
+            LocationResult::OnVertex(_, N2Index(42)) => (),
+            other => panic!("expected node 42, got {:?}", other),
+        }
+
+        //coordinates are multiples of 1/64, so the middle of an edge lies exactly on it.
+        let (n1, n2) = triangulation.elements()[ele_index.0].get_edge(1);
+        let middle = Point2::new((nodes[n1.0].x + nodes[n2.0].x) / 2.,
+                                 (nodes[n1.0].y + nodes[n2.0].y) / 2.);
+        match triangulation.locate(&middle, Some(ele_index)) {
+            (LocationResult::OnEdge(found, edge_index), statistics) => {
+                assert_eq!((ele_index, 1, one_element), (found, edge_index, statistics))
+            }
+            other => panic!("expected edge, got {:?}", other),
+        }
+
+        match triangulation.locate(&Point2::new(-5., 7.), None).0 {
+            LocationResult::Outside(found, edge_index) => {
+                let ele = &triangulation.elements()[found.0];
+                assert_eq!(None, ele.get_neighbor_from_index(edge_index));
+            }
+            other => panic!("expected outside, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn testing_locate_in_restricted_triangulation() {
+        //l shaped domain, the walk from the upper arm towards the right arm leaves it through
+        //the concave corner.
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(4., 0.),
+                     Point2::new(4., 1.),
+                     Point2::new(1., 1.),
+                     Point2::new(1., 4.),
+                     Point2::new(0., 4.)];
+        let segments: Vec<(N2Index, N2Index)> =
+            (0..6).map(|i| (N2Index(i), N2Index((i + 1) % 6))).collect();
+        let triangulation = Triangulation2::from_pslg(&nodes, &segments, &[]);
+
+        let upper = Point2::new(0.5, 3.5);
+        let start = match triangulation.locate(&upper, None).0 {
+            LocationResult::InElement(ele_index) => ele_index,
+            other => panic!("expected element, got {:?}", other),
+        };
+
+        let (location_result, statistics) = triangulation.locate(&Point2::new(3.5, 0.5),
+                                                                 Some(start));
+        match location_result {
+            LocationResult::InElement(_) => (),
+            other => panic!("expected element, got {:?}", other),
+        }
+        assert!(statistics.scanned > 0);
+        assert!(statistics.scanned <= triangulation.elements().len());
+
+        let (location_result, statistics) = triangulation.locate(&Point2::new(3., 3.),
+                                                                 Some(start));
+        match location_result {
+            LocationResult::Outside(_, _) => (),
+            other => panic!("expected outside, got {:?}", other),
+        }
+        assert_eq!(triangulation.elements().len(), statistics.scanned);
+    }
+
+    #[test]
+    #[should_panic]
+    fn testing_creation_from_collinear_nodes() {
+        Triangulation2::new(&[Point2::new(0., 0.), Point2::new(1., 1.), Point2::new(2., 2.)]);
+    }
 }
@@ -0,0 +1,393 @@
+use super::Triangulation2;
+
+use types::*;
+use math::orient2d;
+use algorithms2::nearest_node::elements_around_node;
+
+use std::collections::BTreeMap;
+
+//what lies on the segment from its first node towards the last one.
+enum Trace {
+    //segment is already an edge of the element.
+    Existing(T3Index, usize),
+    //segment passes through the node, which is in the element.
+    ThroughNode(N2Index, T3Index),
+    //elements crossed by the segment, with nodes left and right of it in order from the first node.
+    Crossing(Vec<T3Index>, Vec<N2Index>, Vec<N2Index>),
+}
+
+//forces the segment into the triangulation. segment passing through nodes is split into more
+//constrained edges. elements crossed by the segment are replaced by constrained delaunay
+//triangulations of the polygons on both sides of it. a segment leaving a restricted
+//triangulation is rejected with OutsideOfDomain. the whole segment is traced before anything is
+//changed, so on error nothing is changed.
+pub fn insert_constraint(triangulation: &mut Triangulation2,
+                         element_index: T3Index,
+                         first: N2Index,
+                         last: N2Index)
+                         -> Result<(), DelaunayError> {
+    let (mut from, mut from_element) = (first, element_index);
+    loop {
+        match trace(triangulation, from_element, from, last)? {
+            Trace::ThroughNode(node, ele_index) => {
+                trace(triangulation, from_element, from, node)?;
+                from = node;
+                from_element = ele_index;
+            }
+            _ => break,
+        }
+    }
+
+    let (mut from, mut from_element) = (first, element_index);
+    while from != last {
+        let (to, to_element) = match trace(triangulation, from_element, from, last)? {
+            Trace::ThroughNode(node, ele_index) => (node, ele_index),
+            _ => (last, from_element),
+        };
+
+        let to_element = match trace(triangulation, from_element, from, to)? {
+            Trace::Existing(ele_index, edge_index) => {
+                mark_constrained(triangulation, ele_index, edge_index);
+                ele_index
+            }
+            Trace::Crossing(elements, left, right) => {
+                retriangulate(triangulation, elements, from, to, &left, &right)?
+            }
+            Trace::ThroughNode(_, _) => to_element,
+        };
+
+        from = to;
+        from_element = to_element;
+    }
+
+    Ok(())
+}
+
+fn trace(triangulation: &Triangulation2,
+         element_index: T3Index,
+         first: N2Index,
+         last: N2Index)
+         -> Result<Trace, DelaunayError> {
+    let nodes = triangulation.nodes();
+    let (a, b) = (&nodes[first.0], &nodes[last.0]);
+    let lies_ahead = |node: N2Index| {
+        let p = &nodes[node.0];
+        (p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y) > 0.
+    };
+
+    let mut crossing = None;
+    for ele_index in elements_around_node(element_index, triangulation.elements(), first) {
+        let ele: &Triangle = &triangulation.elements()[ele_index.0];
+        let (p, q) = ele.get_others_two_nodes(first);
+
+        if p == last || q == last {
+            return Ok(Trace::Existing(ele_index, ele.get_neighbor_index(first, last)));
+        }
+
+        let (side_p, side_q) = (orient2d(a, b, &nodes[p.0]), orient2d(a, b, &nodes[q.0]));
+        if side_p == 0. && lies_ahead(p) {
+            return Ok(Trace::ThroughNode(p, ele_index));
+        }
+        if side_q == 0. && lies_ahead(q) {
+            return Ok(Trace::ThroughNode(q, ele_index));
+        }
+        if side_p > 0. && side_q < 0. {
+            crossing = Some((ele_index, p, q));
+        }
+    }
+
+    //no element around the first node is crossed when the segment starts out of the domain.
+    let (mut ele_index, mut left, mut right) = match crossing {
+        Some(crossing) => crossing,
+        None => return Err(DelaunayError::OutsideOfDomain),
+    };
+    let mut elements = vec![ele_index];
+    let mut left_nodes = vec![left];
+    let mut right_nodes = vec![right];
+
+    loop {
+        let ele: &Triangle = &triangulation.elements()[ele_index.0];
+        if ele.is_constrained_for_nodes(left, right) {
+            return Err(DelaunayError::CrossingConstraints(left.0, right.0));
+        }
+
+        ele_index = match ele.get_neighor_for_nodes(left, right) {
+            Some(neighbor_index) => neighbor_index,
+            None => return Err(DelaunayError::OutsideOfDomain),
+        };
+        elements.push(ele_index);
+
+        let next = triangulation.elements()[ele_index.0].get_other_last_node(left, right);
+        if next == last {
+            //the polygons on both sides are triangulated into one element per node between.
+            if elements.len() != left_nodes.len() + right_nodes.len() {
+                return Err(DelaunayError::OutsideOfDomain);
+            }
+            return Ok(Trace::Crossing(elements, left_nodes, right_nodes));
+        }
+
+        let side = orient2d(a, b, &nodes[next.0]);
+        if side > 0. {
+            left = next;
+            left_nodes.push(next);
+        } else if side < 0. {
+            right = next;
+            right_nodes.push(next);
+        } else {
+            return Ok(Trace::ThroughNode(next, ele_index));
+        }
+    }
+}
+
+fn mark_constrained(triangulation: &mut Triangulation2, element_index: T3Index, edge_index: usize) {
+    let (n1, n2) = triangulation.elements()[element_index.0].get_edge(edge_index);
+    triangulation.elements_mut()[element_index.0].set_constrained(edge_index, true);
+
+    if let Some(neighbor_index) = triangulation.elements()[element_index.0]
+        .get_neighbor_from_index(edge_index) {
+        triangulation.elements_mut()[neighbor_index.0].set_constrained_for_nodes(n1, n2, true);
+    }
+}
+
+//replaces the crossed elements by the same number of new ones, in the same slots. returns the
+//element with the new constrained edge.
+fn retriangulate(triangulation: &mut Triangulation2,
+                 elements: Vec<T3Index>,
+                 first: N2Index,
+                 last: N2Index,
+                 left: &[N2Index],
+                 right: &[N2Index])
+                 -> Result<T3Index, DelaunayError> {
+    //edges around the crossed elements, with the element behind them.
+    let mut outer_edges = BTreeMap::new();
+    for ele_index in elements.iter() {
+        let ele: &Triangle = &triangulation.elements()[ele_index.0];
+        for edge_index in 0..3 {
+            let neighbor = ele.get_neighbor_from_index(edge_index);
+            if neighbor.map_or(false, |n| elements.contains(&n)) {
+                continue;
+            }
+
+            outer_edges.insert(sorted_edge(ele.get_edge(edge_index)),
+                               (neighbor, ele.is_constrained(edge_index)));
+        }
+    }
+
+    let mut new_nodes = Vec::with_capacity(elements.len());
+    triangulate_pseudo_polygon(triangulation, first, last, left, &mut new_nodes);
+    triangulate_pseudo_polygon(triangulation, first, last, right, &mut new_nodes);
+    if elements.len() != new_nodes.len() {
+        return Err(DelaunayError::OutsideOfDomain);
+    }
+
+    let mut inner_edges: BTreeMap<(N2Index, N2Index), T3Index> = BTreeMap::new();
+    for (slot, v) in elements.iter().zip(new_nodes.into_iter()) {
+        let ele = Triangle::new(triangulation.nodes(), v[0], v[1], v[2]);
+        triangulation.elements_mut()[slot.0] = ele.clone();
+
+        for edge_index in 0..3 {
+            let (n1, n2) = ele.get_edge(edge_index);
+            if let Some(&(outside, constrained)) = outer_edges.get(&sorted_edge((n1, n2))) {
+                let new_ele: &mut Triangle = &mut triangulation.elements_mut()[slot.0];
+                new_ele.set_neighbor(edge_index, outside);
+                new_ele.set_constrained(edge_index, constrained);
+
+                if let Some(outside) = outside {
+                    triangulation.elements_mut()[outside.0].update_neighbor(n1, n2, Some(*slot));
+                }
+            } else if let Some(other) = inner_edges.remove(&sorted_edge((n1, n2))) {
+                triangulation.elements_mut()[slot.0].set_neighbor(edge_index, Some(other));
+                triangulation.elements_mut()[other.0].update_neighbor(n1, n2, Some(*slot));
+            } else {
+                inner_edges.insert(sorted_edge((n1, n2)), *slot);
+            }
+        }
+    }
+
+    //the base edge is an edge of the last element of each pseudo polygon.
+    let with_constraint = elements[left.len() - 1];
+    let edge_index = triangulation.elements()[with_constraint.0].get_neighbor_index(first, last);
+    mark_constrained(triangulation, with_constraint, edge_index);
+
+    Ok(with_constraint)
+}
+
+//polygon of the base edge and the nodes on one side of it, all of them seen from the edge. the
+//node whose circle with the base edge holds no other node splits the polygon in two smaller ones.
+fn triangulate_pseudo_polygon(triangulation: &Triangulation2,
+                              first: N2Index,
+                              last: N2Index,
+                              between: &[N2Index],
+                              new_nodes: &mut Vec<[N2Index; 3]>) {
+    if between.is_empty() {
+        return;
+    }
+
+    let mut split = 0;
+    for position in 1..between.len() {
+        let circle = Triangle::new_exact([first, last, between[split]], [None, None, None]);
+        if triangulation.is_node_inside_circumcircle(&circle, between[position]) {
+            split = position;
+        }
+    }
+
+    triangulate_pseudo_polygon(triangulation, first, between[split], &between[..split], new_nodes);
+    triangulate_pseudo_polygon(triangulation,
+                               between[split],
+                               last,
+                               &between[split + 1..],
+                               new_nodes);
+    new_nodes.push([first, last, between[split]]);
+}
+
+#[inline]
+fn sorted_edge(edge: (N2Index, N2Index)) -> (N2Index, N2Index) {
+    if edge.0 < edge.1 { edge } else { (edge.1, edge.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation2_test_utils::assert_constrained_delaunay;
+    use types::triangulation2_test_utils::assert_neighborhood;
+    use types::triangulation2_test_utils::random_points;
+    use types::triangulation2_test_utils::randomly_constrained;
+    use algorithms2::element_locators::LocationResult;
+
+    fn constrained_edges(tr: &Triangulation2) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        for ele in tr.elements() {
+            for edge_index in 0..3 {
+                if ele.is_constrained(edge_index) {
+                    let (n1, n2) = ele.get_edge(edge_index);
+                    edges.push(if n1 < n2 { (n1.0, n2.0) } else { (n2.0, n1.0) });
+                }
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    fn grid() -> Vec<Point2> {
+        let mut nodes = Vec::new();
+        for i in 0..36 {
+            nodes.push(Point2::new((i % 6) as f64, (i / 6) as f64));
+        }
+        nodes
+    }
+
+    #[test]
+    fn inserting_constraint_across_grid() {
+        let mut triangulation = Triangulation2::new(&grid());
+
+        //from (0, 1) to (5, 3) crossing many edges and no nodes.
+        triangulation.insert_constraint(N2Index(6), N2Index(23));
+
+        assert_eq!(vec![(6, 23)], constrained_edges(&triangulation));
+        assert_eq!(50, triangulation.elements().len());
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn inserting_constraint_through_nodes() {
+        let mut triangulation = Triangulation2::new(&grid());
+
+        //diagonal from (0, 0) to (4, 4) goes through (1, 1), (2, 2) and (3, 3).
+        triangulation.insert_constraint(N2Index(0), N2Index(28));
+
+        assert_eq!(vec![(0, 7), (7, 14), (14, 21), (21, 28)],
+                   constrained_edges(&triangulation));
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn constraints_survive_insertion_and_removal() {
+        let mut triangulation = Triangulation2::new(&grid());
+        triangulation.insert_constraint(N2Index(6), N2Index(23));
+        triangulation.insert_constraint(N2Index(24), N2Index(35));
+
+        //node on a constrained edge splits it, nodes next to it do not flip it.
+        let on_constraint = triangulation.insert_node(&Point2::new(2.5, 2.));
+        let next_to_constraint = triangulation.insert_node(&Point2::new(3.5, 2.5));
+        triangulation.remove_node(N2Index(15));
+        triangulation.remove_node(N2Index(16));
+
+        assert_eq!(vec![(6, 36), (23, 36), (24, 35)], constrained_edges(&triangulation));
+        assert_eq!(N2Index(36), on_constraint);
+        assert_eq!(N2Index(37), next_to_constraint);
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn crossing_constraints_are_rejected() {
+        let mut triangulation = Triangulation2::new(&grid());
+        triangulation.insert_constraint(N2Index(6), N2Index(23));
+        let elements = triangulation.elements().clone();
+
+        match triangulation.try_insert_constraint(N2Index(2), N2Index(33)) {
+            Err(DelaunayError::CrossingConstraints(_, _)) => (),
+            other => panic!("expected crossing constraints, got {:?}", other),
+        }
+        match triangulation.try_insert_constraint(N2Index(2), N2Index(40)) {
+            Err(DelaunayError::UnknownNode(40)) => (),
+            other => panic!("expected unknown node, got {:?}", other),
+        }
+
+        assert_eq!(elements, *triangulation.elements());
+    }
+
+    #[test]
+    fn constraints_leaving_domain_are_rejected() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(2., 0.),
+                     Point2::new(2., 1.),
+                     Point2::new(1., 1.),
+                     Point2::new(1., 2.),
+                     Point2::new(0., 2.)];
+        let boundary: Vec<(N2Index, N2Index)> =
+            (0..6).map(|i| (N2Index(i), N2Index((i + 1) % 6))).collect();
+        let mut triangulation = Triangulation2::from_pslg(&nodes, &boundary, &[]);
+        let elements = triangulation.elements().clone();
+
+        //the segment passes over the notch of the l shape.
+        match triangulation.try_insert_constraint(N2Index(2), N2Index(4)) {
+            Err(DelaunayError::OutsideOfDomain) => (),
+            other => panic!("expected outside of domain, got {:?}", other),
+        }
+        match triangulation.try_insert_constraint(N2Index(4), N2Index(2)) {
+            Err(DelaunayError::OutsideOfDomain) => (),
+            other => panic!("expected outside of domain, got {:?}", other),
+        }
+
+        assert_eq!(elements, *triangulation.elements());
+        triangulation.insert_constraint(N2Index(0), N2Index(3));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn inserting_nodes_after_random_constraints() {
+        let mut triangulation = randomly_constrained(10);
+        let constraints = constrained_edges(&triangulation);
+        assert_eq!(121, constraints.len());
+
+        //the walk from the last added element went around a cycle of elements towards it.
+        triangulation.insert_node(&Point2::new(69.30666644275902, 13.23524183605917));
+
+        for p in random_points(7, 200) {
+            let p = Point2::new(10. + 80. * p.x, 10. + 80. * p.y);
+            match triangulation.locate(&p, None).0 {
+                LocationResult::Outside(_, _) => panic!("{:?} is inside of the hull", p),
+                _ => (),
+            }
+            triangulation.insert_node(&p);
+        }
+
+        assert_eq!(constraints.len(), constrained_edges(&triangulation).len());
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+}
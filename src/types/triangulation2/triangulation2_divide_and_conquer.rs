@@ -0,0 +1,369 @@
+use super::Triangulation2;
+
+use types::*;
+use math::orient2d;
+use math::circumcircle_side_perturbed;
+use math::CircleSide;
+
+//divide and conquer construction of guibas and stolfi. nodes sorted by x and y are split in
+//halves, both halves are triangulated recursively and merged by zipping their hulls together
+//from the lower common tangent upwards. node lying on a circumcircle is decided by node indexes
+//like in the incremental construction, so both build the same elements. sorted_indexes must
+//not all be collinear.
+pub fn build(nodes: &[Point2], sorted_indexes: &[usize]) -> Triangulation2 {
+    let mut edges = QuadEdges::with_capacity(3 * sorted_indexes.len());
+    edges.triangulate(nodes, sorted_indexes);
+
+    let elements = edges.elements(nodes);
+    let mut triangulation = Triangulation2::new_from_prebuilt_triangulation(Vec::from(nodes),
+                                                                            Vec::new());
+    *triangulation.elements_mut() = elements;
+    triangulation
+}
+
+//edges are kept in groups of four records: the edge, its dual, the edge reversed and the dual
+//reversed. next holds the next edge counterclockwise around the origin of each record, origin
+//is the node index of the primal records.
+struct QuadEdges {
+    next: Vec<usize>,
+    origin: Vec<usize>,
+    deleted: Vec<bool>,
+}
+
+impl QuadEdges {
+    fn with_capacity(edge_count: usize) -> QuadEdges {
+        QuadEdges {
+            next: Vec::with_capacity(4 * edge_count),
+            origin: Vec::with_capacity(4 * edge_count),
+            deleted: Vec::with_capacity(edge_count),
+        }
+    }
+
+    #[inline]
+    fn rot(e: usize) -> usize {
+        (e & !3) | ((e + 1) & 3)
+    }
+
+    #[inline]
+    fn sym(e: usize) -> usize {
+        (e & !3) | ((e + 2) & 3)
+    }
+
+    #[inline]
+    fn rot_inv(e: usize) -> usize {
+        (e & !3) | ((e + 3) & 3)
+    }
+
+    #[inline]
+    fn onext(&self, e: usize) -> usize {
+        self.next[e]
+    }
+
+    #[inline]
+    fn oprev(&self, e: usize) -> usize {
+        QuadEdges::rot(self.onext(QuadEdges::rot(e)))
+    }
+
+    #[inline]
+    fn lnext(&self, e: usize) -> usize {
+        QuadEdges::rot(self.onext(QuadEdges::rot_inv(e)))
+    }
+
+    #[inline]
+    fn rprev(&self, e: usize) -> usize {
+        self.onext(QuadEdges::sym(e))
+    }
+
+    #[inline]
+    fn org(&self, e: usize) -> usize {
+        self.origin[e]
+    }
+
+    #[inline]
+    fn dest(&self, e: usize) -> usize {
+        self.origin[QuadEdges::sym(e)]
+    }
+
+    fn make_edge(&mut self, org: usize, dest: usize) -> usize {
+        let e = self.next.len();
+        self.next.extend_from_slice(&[e, e + 3, e + 2, e + 1]);
+        self.origin.extend_from_slice(&[org, 0, dest, 0]);
+        self.deleted.push(false);
+        e
+    }
+
+    fn splice(&mut self, a: usize, b: usize) {
+        let alpha = QuadEdges::rot(self.onext(a));
+        let beta = QuadEdges::rot(self.onext(b));
+
+        self.next.swap(a, b);
+        self.next.swap(alpha, beta);
+    }
+
+    //new edge from the destination of a to the origin of b, on the left of both.
+    fn connect(&mut self, a: usize, b: usize) -> usize {
+        let (org, dest) = (self.dest(a), self.org(b));
+        let e = self.make_edge(org, dest);
+        let lnext = self.lnext(a);
+        self.splice(e, lnext);
+        self.splice(QuadEdges::sym(e), b);
+        e
+    }
+
+    fn delete(&mut self, e: usize) {
+        let oprev = self.oprev(e);
+        self.splice(e, oprev);
+        let sym = QuadEdges::sym(e);
+        let oprev = self.oprev(sym);
+        self.splice(sym, oprev);
+        self.deleted[e / 4] = true;
+    }
+
+    //counterclockwise hull edge leaving the leftmost node and clockwise hull edge leaving the
+    //rightmost node.
+    fn triangulate(&mut self, nodes: &[Point2], s: &[usize]) -> (usize, usize) {
+        if s.len() == 2 {
+            let a = self.make_edge(s[0], s[1]);
+            return (a, QuadEdges::sym(a));
+        }
+
+        if s.len() == 3 {
+            let a = self.make_edge(s[0], s[1]);
+            let b = self.make_edge(s[1], s[2]);
+            self.splice(QuadEdges::sym(a), b);
+
+            let orientation = orient2d(&nodes[s[0]], &nodes[s[1]], &nodes[s[2]]);
+            if orientation > 0. {
+                self.connect(b, a);
+                return (a, QuadEdges::sym(b));
+            } else if orientation < 0. {
+                let c = self.connect(b, a);
+                return (QuadEdges::sym(c), c);
+            }
+            return (a, QuadEdges::sym(b));
+        }
+
+        let (left, right) = s.split_at(s.len() / 2);
+        let (mut ldo, mut ldi) = self.triangulate(nodes, left);
+        let (mut rdi, mut rdo) = self.triangulate(nodes, right);
+
+        //lower common tangent of both hulls.
+        loop {
+            if self.left_of(nodes, self.org(rdi), ldi) {
+                ldi = self.lnext(ldi);
+            } else if self.right_of(nodes, self.org(ldi), rdi) {
+                rdi = self.rprev(rdi);
+            } else {
+                break;
+            }
+        }
+
+        let mut basel = self.connect(QuadEdges::sym(rdi), ldi);
+        if self.org(ldi) == self.org(ldo) {
+            ldo = QuadEdges::sym(basel);
+        }
+        if self.org(rdi) == self.org(rdo) {
+            rdo = basel;
+        }
+
+        //each step adds the edge of the triangle above basel, whose circumcircle is empty.
+        loop {
+            let mut lcand = self.onext(QuadEdges::sym(basel));
+            if self.is_valid(nodes, lcand, basel) {
+                loop {
+                    let next = self.onext(lcand);
+                    if !self.is_in_circle(nodes,
+                                          [self.dest(basel),
+                                           self.org(basel),
+                                           self.dest(lcand),
+                                           self.dest(next)]) {
+                        break;
+                    }
+                    self.delete(lcand);
+                    lcand = next;
+                }
+            }
+
+            let mut rcand = self.oprev(basel);
+            if self.is_valid(nodes, rcand, basel) {
+                loop {
+                    let prev = self.oprev(rcand);
+                    if !self.is_in_circle(nodes,
+                                          [self.dest(basel),
+                                           self.org(basel),
+                                           self.dest(rcand),
+                                           self.dest(prev)]) {
+                        break;
+                    }
+                    self.delete(rcand);
+                    rcand = prev;
+                }
+            }
+
+            let lvalid = self.is_valid(nodes, lcand, basel);
+            let rvalid = self.is_valid(nodes, rcand, basel);
+            if !lvalid && !rvalid {
+                break;
+            }
+
+            if !lvalid ||
+               (rvalid &&
+                self.is_in_circle(nodes,
+                                  [self.dest(lcand),
+                                   self.org(lcand),
+                                   self.org(rcand),
+                                   self.dest(rcand)])) {
+                basel = self.connect(rcand, QuadEdges::sym(basel));
+            } else {
+                basel = self.connect(QuadEdges::sym(basel), QuadEdges::sym(lcand));
+            }
+        }
+
+        (ldo, rdo)
+    }
+
+    #[inline]
+    fn left_of(&self, nodes: &[Point2], node: usize, e: usize) -> bool {
+        orient2d(&nodes[node], &nodes[self.org(e)], &nodes[self.dest(e)]) > 0.
+    }
+
+    #[inline]
+    fn right_of(&self, nodes: &[Point2], node: usize, e: usize) -> bool {
+        orient2d(&nodes[node], &nodes[self.dest(e)], &nodes[self.org(e)]) > 0.
+    }
+
+    #[inline]
+    fn is_valid(&self, nodes: &[Point2], e: usize, basel: usize) -> bool {
+        self.right_of(nodes, self.dest(e), basel)
+    }
+
+    //whether the last node lies inside of the circumcircle of the first three.
+    #[inline]
+    fn is_in_circle(&self, nodes: &[Point2], indexes: [usize; 4]) -> bool {
+        circumcircle_side_perturbed(&nodes[indexes[0]],
+                                    &nodes[indexes[1]],
+                                    &nodes[indexes[2]],
+                                    &nodes[indexes[3]],
+                                    indexes) == CircleSide::Inside
+    }
+
+    //every counterclockwise face of three edges becomes an element, the outer face is
+    //clockwise. elements are kept clockwise, so the edges are taken in reverse.
+    fn elements(&self, nodes: &[Point2]) -> Vec<Triangle> {
+        let mut faces: Vec<Option<usize>> = vec![None; self.next.len()];
+        let mut cycles = Vec::new();
+
+        for quad in 0..self.deleted.len() {
+            if self.deleted[quad] {
+                continue;
+            }
+
+            for &a in [4 * quad, 4 * quad + 2].iter() {
+                if faces[a].is_some() {
+                    continue;
+                }
+
+                let b = self.lnext(a);
+                let c = self.lnext(b);
+                if self.lnext(c) != a ||
+                   orient2d(&nodes[self.org(a)], &nodes[self.org(b)], &nodes[self.org(c)]) <= 0. {
+                    continue;
+                }
+
+                let face = cycles.len();
+                faces[a] = Some(face);
+                faces[b] = Some(face);
+                faces[c] = Some(face);
+                cycles.push((a, b, c));
+            }
+        }
+
+        cycles.iter()
+            .map(|&(a, b, c)| {
+                let neighbor = |e: usize| faces[QuadEdges::sym(e)].map(T3Index);
+                Triangle::new_exact([N2Index(self.org(a)),
+                                     N2Index(self.org(c)),
+                                     N2Index(self.org(b))],
+                                    [neighbor(c), neighbor(b), neighbor(a)])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation2_test_utils::random_points;
+    use types::triangulation2_test_utils::sorted_elements;
+
+    fn assert_elements(tr: &Triangulation2) {
+        for (index, ele) in tr.elements().iter().enumerate() {
+            ele.assert_order(tr.nodes());
+            for edge_index in 0..3 {
+                if let Some(neighbor) = ele.get_neighbor_from_index(edge_index) {
+                    let (n1, n2) = ele.get_edge(edge_index);
+                    assert_eq!(Some(T3Index(index)),
+                               tr.elements()[neighbor.0].get_neighor_for_nodes(n1, n2));
+                }
+            }
+        }
+    }
+
+    fn divide_and_conquer(nodes: &[Point2]) -> Result<Triangulation2, DelaunayError> {
+        let options = BuildOptions {
+            algorithm: BuildAlgorithm::DivideAndConquer,
+            ..BuildOptions::default()
+        };
+        Triangulation2::try_new_with_options(nodes, &options)
+    }
+
+    #[test]
+    fn building_random_nodes() {
+        let nodes = random_points(7, 5000);
+
+        let triangulation = divide_and_conquer(&nodes).unwrap();
+
+        assert_eq!(sorted_elements(&Triangulation2::new(&nodes)),
+                   sorted_elements(&triangulation));
+        assert_elements(&triangulation);
+    }
+
+    #[test]
+    fn building_grid() {
+        //every four nodes of the grid lie on a common circle and the hull has collinear nodes.
+        let mut nodes = Vec::new();
+        for i in 0..900 {
+            let position = (i * 37) % 900;
+            nodes.push(Point2::new((position % 30) as f64, (position / 30) as f64));
+        }
+
+        let triangulation = divide_and_conquer(&nodes).unwrap();
+
+        assert_eq!(2 * 29 * 29, triangulation.elements().len());
+        assert_eq!(sorted_elements(&Triangulation2::new(&nodes)),
+                   sorted_elements(&triangulation));
+        assert_elements(&triangulation);
+    }
+
+    #[test]
+    fn building_small_sets() {
+        let triangle = [Point2::new(0., 0.), Point2::new(1., 0.), Point2::new(0., 1.)];
+        let triangulation = divide_and_conquer(&triangle).unwrap();
+        assert_eq!(vec![vec![0, 1, 2]], sorted_elements(&triangulation));
+        assert_elements(&triangulation);
+
+        let square = [Point2::new(0., 1.),
+                      Point2::new(1., 1.),
+                      Point2::new(0., 0.),
+                      Point2::new(1., 0.)];
+        let triangulation = divide_and_conquer(&square).unwrap();
+        assert_eq!(sorted_elements(&Triangulation2::new(&square)),
+                   sorted_elements(&triangulation));
+        assert_elements(&triangulation);
+
+        match divide_and_conquer(&[Point2::new(0., 0.), Point2::new(1., 1.), Point2::new(2., 2.)]) {
+            Err(DelaunayError::CollinearNodes) => (),
+            other => panic!("expected collinear nodes, got {:?}", other.err()),
+        }
+    }
+}
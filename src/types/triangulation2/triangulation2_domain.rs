@@ -0,0 +1,178 @@
+use super::Triangulation2;
+use super::triangulation2_removal::remove_element;
+
+use types::*;
+use algorithms2::element_locators::*;
+
+//deletes the elements reachable from the convex hull or from the elements containing the hole
+//points without crossing a constrained edge. constrained edges of the convex hull are a part of
+//the domain border, so the hull is entered only through the unconstrained ones. hole points
+//outside of the hull are ignored.
+pub fn remove_outside_of_domain(triangulation: &mut Triangulation2,
+                                holes: &[Point2])
+                                -> Result<(), DelaunayError> {
+    let mut outside = vec![false; triangulation.elements().len()];
+    let mut stack = Vec::new();
+
+    for (ele_index, ele) in triangulation.elements().iter().enumerate() {
+        for i in 0..3 {
+            if ele.get_neighbor_from_index(i).is_none() && !ele.is_constrained(i) {
+                stack.push(T3Index(ele_index));
+            }
+        }
+    }
+
+    for hole in holes.iter() {
+        match locate_element_containing(T3Index(0),
+                                        triangulation.elements(),
+                                        triangulation.nodes(),
+                                        hole) {
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnEdge(ele_index, _) |
+            LocationResult::OnVertex(ele_index, _) => stack.push(ele_index),
+            LocationResult::Outside(_, _) => (),
+        }
+    }
+
+    while let Some(ele_index) = stack.pop() {
+        if outside[ele_index.0] {
+            continue;
+        }
+        outside[ele_index.0] = true;
+
+        let ele = &triangulation.elements()[ele_index.0];
+        for i in 0..3 {
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(i) {
+                if !ele.is_constrained(i) && !outside[neighbor_index.0] {
+                    stack.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    if outside.iter().all(|o| *o) {
+        return Err(DelaunayError::EmptyDomain);
+    }
+
+    //edges between the domain and the deleted elements become the border.
+    for ele_index in 0..outside.len() {
+        if outside[ele_index] {
+            continue;
+        }
+
+        for i in 0..3 {
+            let neighbor = triangulation.elements()[ele_index].get_neighbor_from_index(i);
+            if let Some(neighbor_index) = neighbor {
+                if outside[neighbor_index.0] {
+                    triangulation.elements_mut()[ele_index].set_neighbor(i, None);
+                }
+            }
+        }
+    }
+
+    for ele_index in (0..outside.len()).rev() {
+        if outside[ele_index] {
+            remove_element(triangulation, T3Index(ele_index));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation2_test_utils::area;
+    use types::triangulation2_test_utils::assert_neighborhood;
+
+    fn square_with_hole() -> Triangulation2 {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(4., 0.),
+                     Point2::new(4., 4.),
+                     Point2::new(0., 4.),
+                     Point2::new(1., 1.),
+                     Point2::new(3., 1.),
+                     Point2::new(3., 3.),
+                     Point2::new(1., 3.),
+                     Point2::new(2., 2.)];
+        let segments = [(N2Index(0), N2Index(1)),
+                        (N2Index(1), N2Index(2)),
+                        (N2Index(2), N2Index(3)),
+                        (N2Index(3), N2Index(0)),
+                        (N2Index(4), N2Index(5)),
+                        (N2Index(5), N2Index(6)),
+                        (N2Index(6), N2Index(7)),
+                        (N2Index(7), N2Index(4))];
+
+        Triangulation2::from_pslg(&nodes, &segments, &[Point2::new(1.5, 2.5)])
+    }
+
+    #[test]
+    fn removing_hole_of_square() {
+        let triangulation = square_with_hole();
+
+        //node 8 in the middle of the hole is left out.
+        assert_eq!(8, triangulation.elements().len());
+        assert_eq!(12., area(&triangulation));
+        assert!(triangulation.elements().iter().all(|e| !e.nodes().contains(&N2Index(8))));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_outside_of_l_shape() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(4., 0.),
+                     Point2::new(4., 2.),
+                     Point2::new(2., 2.),
+                     Point2::new(2., 4.),
+                     Point2::new(0., 4.),
+                     Point2::new(1., 1.),
+                     Point2::new(3., 3.)];
+        let mut segments: Vec<(N2Index, N2Index)> =
+            (0..6).map(|i| (N2Index(i), N2Index((i + 1) % 6))).collect();
+        //inner segment splits the domain but does not bound it.
+        segments.push((N2Index(0), N2Index(4)));
+
+        let mut triangulation = Triangulation2::from_pslg(&nodes, &segments, &[]);
+
+        assert_eq!(12., area(&triangulation));
+        assert!(triangulation.elements().iter().all(|e| !e.nodes().contains(&N2Index(7))));
+        assert!(triangulation.elements()
+            .iter()
+            .filter(|e| e.nodes().contains(&N2Index(0)) && e.nodes().contains(&N2Index(4)))
+            .all(|e| e.is_constrained_for_nodes(N2Index(0), N2Index(4))));
+        assert_neighborhood(&triangulation);
+
+        //points in the far arm are found by scanning, points in the notch are rejected.
+        assert_eq!(N2Index(8), triangulation.insert_node(&Point2::new(1.5, 3.5)));
+        assert_eq!(N2Index(9), triangulation.insert_node(&Point2::new(3.5, 0.5)));
+        match triangulation.try_insert_node(&Point2::new(3., 2.5)) {
+            Err(DelaunayError::OutsideOfDomain) => (),
+            other => panic!("expected outside of domain, got {:?}", other),
+        }
+        assert_eq!(12., area(&triangulation));
+        assert_neighborhood(&triangulation);
+
+        triangulation.remove_node(N2Index(6));
+        match triangulation.try_remove_node(N2Index(3)) {
+            Err(DelaunayError::NodeOnDomainBorder(3)) => (),
+            other => panic!("expected node on domain border, got {:?}", other),
+        }
+        assert_eq!(12., area(&triangulation));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn errors_of_domain() {
+        let nodes = [Point2::new(0., 0.), Point2::new(1., 0.), Point2::new(0., 1.)];
+
+        match Triangulation2::try_from_pslg(&nodes, &[(N2Index(0), N2Index(1))], &[]) {
+            Err(DelaunayError::EmptyDomain) => (),
+            other => panic!("expected empty domain, got {:?}", other.err()),
+        }
+        match Triangulation2::try_from_pslg(&nodes, &[(N2Index(0), N2Index(5))], &[]) {
+            Err(DelaunayError::UnknownNode(5)) => (),
+            other => panic!("expected unknown node, got {:?}", other.err()),
+        }
+    }
+}
@@ -1,6 +1,54 @@
 use super::Triangulation2;
 
 use types::*;
+use math::side_of_line;
+use math::PointLiesOnLineSide;
+use algorithms2::element_locators::LocationResult;
+use algorithms2::lawson_flipping;
+
+//inserts the node at the location and flips the new elements until they are delaunay. returns an
+//element containing the node.
+pub fn insert_at_location(triangulation: &mut Triangulation2,
+                          new_node_index: N2Index,
+                          location_result: LocationResult)
+                          -> Result<T3Index, DelaunayError> {
+    let (containing, new_elements) = match location_result {
+        LocationResult::InElement(ele_index) => {
+            let (t1_index, t2_index, t3_index) =
+                insert_into_element(triangulation, ele_index, new_node_index);
+            (ele_index, vec![t1_index, t2_index, t3_index])
+        }
+        LocationResult::OnEdge(ele_index, edge_index) => {
+            let has_neighbor = triangulation.elements()[ele_index.0]
+                .get_neighbor_from_index(edge_index)
+                .is_some();
+
+            if has_neighbor {
+                let new_elements =
+                    insert_in_inner_edge(triangulation, ele_index, new_node_index, edge_index);
+                (ele_index, new_elements.to_vec())
+            } else {
+                let (ele1, ele2) =
+                    insert_in_edge(triangulation, ele_index, new_node_index, edge_index);
+                (ele_index, vec![ele1, ele2])
+            }
+        }
+        LocationResult::Outside(ele_index, edge_index) => {
+            let new_elements = insert_outside(triangulation, ele_index, new_node_index, edge_index);
+            (new_elements[0], new_elements)
+        }
+        LocationResult::OnVertex(_, existing_node_index) => {
+            return Err(DelaunayError::DuplicateNodes(existing_node_index.0, new_node_index.0));
+        }
+    };
+
+    let work_list = triangulation.flip_work_list();
+    let statistics =
+        lawson_flipping::propagate_flips(triangulation, new_node_index, &new_elements, work_list);
+    triangulation.flip_statistics_mut().add(&statistics);
+
+    Ok(containing)
+}
 
 pub fn insert_into_element(triangulation: &mut Triangulation2,
                            element_index: T3Index,
@@ -13,19 +61,25 @@ pub fn insert_into_element(triangulation: &mut Triangulation2,
     let index_of_right = element_index;
 
 
-    let (original_elements_nodes, original_element_neighbors) = {
+    let (original_elements_nodes, original_element_neighbors, original_constrained) = {
         let original_element: &Triangle = &triangulation.elements()[element_index.0];
-        (*original_element.nodes(), *original_element.neighbors())
+        (*original_element.nodes(),
+         *original_element.neighbors(),
+         [original_element.is_constrained(0),
+          original_element.is_constrained(1),
+          original_element.is_constrained(2)])
     };
 
-    let left_element = Triangle::new(triangulation.nodes(),
-                                     original_elements_nodes[0],
-                                     original_elements_nodes[1],
-                                     new_node_index);
-    let top_element = Triangle::new(triangulation.nodes(),
-                                    original_elements_nodes[1],
-                                    original_elements_nodes[2],
-                                    new_node_index);
+    let mut left_element = Triangle::new(triangulation.nodes(),
+                                         original_elements_nodes[0],
+                                         original_elements_nodes[1],
+                                         new_node_index);
+    let mut top_element = Triangle::new(triangulation.nodes(),
+                                        original_elements_nodes[1],
+                                        original_elements_nodes[2],
+                                        new_node_index);
+    left_element.set_constrained(0, original_constrained[0]);
+    top_element.set_constrained(0, original_constrained[1]);
 
     assert_eq!(*left_element.nodes(),
                [original_elements_nodes[0], original_elements_nodes[1], new_node_index]);
@@ -59,6 +113,9 @@ pub fn insert_into_element(triangulation: &mut Triangulation2,
     original_element.update_nodes(original_elements_nodes[2],
                                   original_elements_nodes[0],
                                   new_node_index);
+    original_element.set_constrained(0, original_constrained[2]);
+    original_element.set_constrained(1, false);
+    original_element.set_constrained(2, false);
 
     (index_of_right, index_of_top, index_of_left)
 }
@@ -96,6 +153,12 @@ pub fn insert_in_edge(triangulation: &mut Triangulation2,
         let second_neighbor = element.get_neighor_for_nodes(first_second_nodes.1, last_node);
         (first_second_nodes.1, last_node, second_neighbor)
     };
+    //both halves of a constrained edge stay constrained.
+    let (split_constrained, second_constrained) = {
+        let element: &Triangle = &triangulation.elements()[element_index.0];
+        (element.is_constrained(edge_index),
+         element.is_constrained_for_nodes(second_node_index, third_node_index))
+    };
 
     let mut new_element = Triangle::new(triangulation.nodes(),
                                         inserted_node_index,
@@ -104,12 +167,17 @@ pub fn insert_in_edge(triangulation: &mut Triangulation2,
 
     new_element.update_neighbor(inserted_node_index, third_node_index, Some(element_index));
     new_element.update_neighbor(second_node_index, third_node_index, second_neighbor_index);
+    new_element.set_constrained_for_nodes(second_node_index, third_node_index, second_constrained);
+    new_element.set_constrained_for_nodes(inserted_node_index,
+                                          second_node_index,
+                                          split_constrained);
 
     {
         let element: &mut Triangle = &mut triangulation.elements_mut()[element_index.0];
 
         element.update_neighbor(second_node_index, third_node_index, Some(new_element_index));
         element.swap_node(second_node_index, inserted_node_index);
+        element.set_constrained_for_nodes(inserted_node_index, third_node_index, false);
     }
 
     triangulation.elements_mut().push(new_element);
@@ -123,6 +191,170 @@ pub fn insert_in_edge(triangulation: &mut Triangulation2,
     (element_index, new_element_index)
 }
 
+//splits both elements sharing the edge. returns the four elements around the inserted node.
+pub fn insert_in_inner_edge(triangulation: &mut Triangulation2,
+                            element_index: T3Index,
+                            inserted_node_index: N2Index,
+                            edge_index: usize)
+                            -> [T3Index; 4] {
+    let (first_node_index, second_node_index, neighbor_index, neighbor_edge_index) = {
+        let element: &Triangle = &triangulation.elements()[element_index.0];
+        let (first, second) = element.get_edge(edge_index);
+        let neighbor_index = element.get_neighbor_from_index(edge_index)
+            .expect("insert_in_inner_edge invoked on a border edge.");
+        let neighbor: &Triangle = &triangulation.elements()[neighbor_index.0];
+
+        (first, second, neighbor_index, neighbor.get_neighbor_index(first, second))
+    };
+
+    //each half is split as if it was a border edge, then the halves are connected again.
+    let (first_half, second_half) =
+        insert_in_edge(triangulation, element_index, inserted_node_index, edge_index);
+    let (second_neighbor_half, first_neighbor_half) =
+        insert_in_edge(triangulation, neighbor_index, inserted_node_index, neighbor_edge_index);
+
+    link_elements(triangulation,
+                  first_half,
+                  first_neighbor_half,
+                  first_node_index,
+                  inserted_node_index);
+    link_elements(triangulation,
+                  second_half,
+                  second_neighbor_half,
+                  second_node_index,
+                  inserted_node_index);
+
+    [first_half, second_half, second_neighbor_half, first_neighbor_half]
+}
+
+//this can only be invoked when the node lies on the outer side of the element's border edge.
+pub fn insert_outside(triangulation: &mut Triangulation2,
+                      element_index: T3Index,
+                      new_node_index: N2Index,
+                      edge_index: usize)
+                      -> Vec<T3Index> {
+    let (first_node, second_node) = triangulation.elements()[element_index.0].get_edge(edge_index);
+    let first_new_element = add_border_element(triangulation,
+                                               element_index,
+                                               first_node,
+                                               second_node,
+                                               new_node_index);
+    let mut new_elements = vec![first_new_element];
+
+    //the node may see more than one border edge, walk the border both ways covering all of them.
+    let (mut border_element, mut from_node, mut to_node) = (element_index, first_node, second_node);
+    let mut last_new_element = first_new_element;
+    loop {
+        let (next_element, next_node) =
+            find_border_edge_around(triangulation, border_element, to_node, from_node);
+
+        if side_of_line(&triangulation.nodes()[to_node.0],
+                        &triangulation.nodes()[next_node.0],
+                        &triangulation.nodes()[new_node_index.0]) !=
+           PointLiesOnLineSide::Left {
+            break;
+        }
+
+        let new_element =
+            add_border_element(triangulation, next_element, to_node, next_node, new_node_index);
+        link_elements(triangulation, new_element, last_new_element, to_node, new_node_index);
+        new_elements.push(new_element);
+
+        last_new_element = new_element;
+        border_element = next_element;
+        from_node = to_node;
+        to_node = next_node;
+    }
+
+    let (mut border_element, mut from_node, mut to_node) = (element_index, first_node, second_node);
+    let mut last_new_element = first_new_element;
+    loop {
+        let (previous_element, previous_node) =
+            find_border_edge_around(triangulation, border_element, from_node, to_node);
+
+        if side_of_line(&triangulation.nodes()[previous_node.0],
+                        &triangulation.nodes()[from_node.0],
+                        &triangulation.nodes()[new_node_index.0]) !=
+           PointLiesOnLineSide::Left {
+            break;
+        }
+
+        let new_element = add_border_element(triangulation,
+                                             previous_element,
+                                             previous_node,
+                                             from_node,
+                                             new_node_index);
+        link_elements(triangulation, new_element, last_new_element, from_node, new_node_index);
+        new_elements.push(new_element);
+
+        last_new_element = new_element;
+        border_element = previous_element;
+        to_node = from_node;
+        from_node = previous_node;
+    }
+
+    new_elements
+}
+
+//rotates around border node, starting at the element which has a border edge between border node
+//and other_border_node. returns the element and node of the second border edge of border node.
+fn find_border_edge_around(triangulation: &Triangulation2,
+                           element_index: T3Index,
+                           border_node: N2Index,
+                           other_border_node: N2Index)
+                           -> (T3Index, N2Index) {
+    let mut current_index = element_index;
+    let mut other_node = triangulation.elements()[element_index.0]
+        .get_other_last_node(border_node, other_border_node);
+
+    loop {
+        let current: &Triangle = &triangulation.elements()[current_index.0];
+
+        match current.get_neighor_for_nodes(border_node, other_node) {
+            None => return (current_index, other_node),
+            Some(neighbor_index) => {
+                let neighbor: &Triangle = &triangulation.elements()[neighbor_index.0];
+                other_node = neighbor.get_other_last_node(border_node, other_node);
+                current_index = neighbor_index;
+            }
+        }
+    }
+}
+
+fn add_border_element(triangulation: &mut Triangulation2,
+                      border_element_index: T3Index,
+                      n1: N2Index,
+                      n2: N2Index,
+                      new_node_index: N2Index)
+                      -> T3Index {
+    let new_element_index = T3Index(triangulation.elements().len());
+
+    let mut new_element = Triangle::new(triangulation.nodes(), n1, n2, new_node_index);
+    new_element.update_neighbor(n1, n2, Some(border_element_index));
+    new_element.set_constrained_for_nodes(n1,
+                                          n2,
+                                          triangulation.elements()[border_element_index.0]
+                                              .is_constrained_for_nodes(n1, n2));
+    triangulation.elements_mut().push(new_element);
+
+    update_neighborhood(triangulation,
+                        Some(border_element_index),
+                        n1,
+                        n2,
+                        new_element_index);
+
+    new_element_index
+}
+
+fn link_elements(triangulation: &mut Triangulation2,
+                 e1: T3Index,
+                 e2: T3Index,
+                 n1: N2Index,
+                 n2: N2Index) {
+    update_neighborhood(triangulation, Some(e1), n1, n2, e2);
+    update_neighborhood(triangulation, Some(e2), n1, n2, e1);
+}
+
 #[cfg(test)]
 mod tests {
     use types::*;
@@ -201,4 +433,72 @@ mod tests {
                                        [None, Some(T3Index(1)), Some(T3Index(0))]),
                    triangulation.elements()[3]);
     }
+
+    #[test]
+    fn insertion_in_inner_edge_test() {
+        let nodes: Vec<Point2> = vec![Point2::new(0., 0.),
+                                      Point2::new(0., 2.),
+                                      Point2::new(2., 2.),
+                                      Point2::new(2., 0.),
+                                      Point2::new(1., 1.)];
+
+        let eles: Vec<Triangle> = vec![Triangle::new(&nodes, N2Index(0), N2Index(1), N2Index(2)),
+                                       Triangle::new(&nodes, N2Index(0), N2Index(2), N2Index(3))];
+
+        let mut triangulation = Triangulation2::new_from_prebuilt_triangulation(nodes, eles);
+        let edge_index = triangulation.elements()[0].get_neighbor_index(N2Index(0), N2Index(2));
+
+        let new_elements = insert_in_inner_edge(&mut triangulation, T3Index(0), N2Index(4), edge_index);
+
+        assert_eq!(4, triangulation.elements().len());
+        assert_eq!([T3Index(0), T3Index(2), T3Index(1), T3Index(3)], new_elements);
+
+        for (index, ele) in triangulation.elements().iter().enumerate() {
+            ele.assert_order(triangulation.nodes());
+            assert!(ele.nodes().contains(&N2Index(4)));
+
+            let (outer1, outer2) = ele.get_others_two_nodes(N2Index(4));
+            assert_eq!(None, ele.get_neighor_for_nodes(outer1, outer2));
+
+            for &outer in [outer1, outer2].iter() {
+                let neighbor = ele.get_neighor_for_nodes(outer, N2Index(4))
+                    .expect("inner edges have neighbors on both sides.");
+                assert_eq!(Some(T3Index(index)),
+                           triangulation.elements()[neighbor.0]
+                               .get_neighor_for_nodes(outer, N2Index(4)));
+            }
+        }
+    }
+
+    #[test]
+    fn insertion_outside_test() {
+        let nodes: Vec<Point2> = vec![Point2::new(0., 0.),
+                                      Point2::new(0., 2.),
+                                      Point2::new(2., 2.),
+                                      Point2::new(2., 0.),
+                                      Point2::new(3., 1.),
+                                      Point2::new(1., -5.)];
+
+        let eles: Vec<Triangle> = vec![Triangle::new(&nodes, N2Index(0), N2Index(1), N2Index(2)),
+                                       Triangle::new(&nodes, N2Index(0), N2Index(2), N2Index(3))];
+
+        let mut triangulation = Triangulation2::new_from_prebuilt_triangulation(nodes, eles);
+
+        assert_eq!(vec![T3Index(2)],
+                   insert_outside(&mut triangulation, T3Index(1), N2Index(4), 1));
+        assert_eq!(Triangle::new_exact([N2Index(2), N2Index(4), N2Index(3)],
+                                       [None, None, Some(T3Index(1))]),
+                   triangulation.elements()[2]);
+        assert_eq!(Some(T3Index(2)), triangulation.elements()[1].get_neighbor_from_index(1));
+
+        //sees the bottom edge and the lower edge of the previously inserted node.
+        assert_eq!(vec![T3Index(3), T3Index(4)],
+                   insert_outside(&mut triangulation, T3Index(1), N2Index(5), 2));
+        assert_eq!(Triangle::new_exact([N2Index(3), N2Index(5), N2Index(0)],
+                                       [Some(T3Index(4)), None, Some(T3Index(1))]),
+                   triangulation.elements()[3]);
+        assert_eq!(Triangle::new_exact([N2Index(4), N2Index(5), N2Index(3)],
+                                       [None, Some(T3Index(3)), Some(T3Index(2))]),
+                   triangulation.elements()[4]);
+    }
 }
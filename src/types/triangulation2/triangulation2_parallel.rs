@@ -0,0 +1,339 @@
+use std::thread;
+use std::collections::BTreeMap;
+
+use super::Triangulation2;
+
+use types::*;
+use algorithms2::element_locators::*;
+
+//strips with less nodes are not worth a thread.
+const MIN_STRIP_NODES: usize = 1000;
+
+//builds the triangulation of nodes sorted by x in vertical strips, each in its own thread.
+//elements whose circumcircle lies inside of their strip are safe, they are elements of the
+//whole triangulation as nodes of the other strips lie outside of it. the rest is triangulated
+//again from the nodes of the other elements and of the borders of the strips. node lying on a
+//circumcircle is decided by node indexes, so the strips keep the order of the indexes and the
+//result has the same elements as the one built by a single thread. none when the nodes can not
+//be split or a strip can not be triangulated, the caller then builds the triangulation itself.
+pub fn build(nodes: &[Point2],
+             sorted_indexes: &[usize],
+             options: &BuildOptions)
+             -> Option<Triangulation2> {
+    let strips = split_into_strips(nodes, sorted_indexes, options.threads);
+    if strips.len() < 2 {
+        return None;
+    }
+
+    let strip_options = BuildOptions { threads: 1, ..*options };
+    let handles: Vec<_> = strips.iter()
+        .map(|strip| {
+            let strip_nodes: Vec<Point2> = strip.indexes.iter().map(|i| nodes[*i]).collect();
+            let (left, right) = (strip.left, strip.right);
+            thread::spawn(move || {
+                Triangulation2::try_new_with_options(&strip_nodes, &strip_options)
+                    .map(|triangulation| {
+                        let safe = safe_elements(&triangulation, left, right);
+                        (triangulation, safe)
+                    })
+            })
+        })
+        .collect();
+
+    let mut triangulations = Vec::new();
+    let mut safe = Vec::new();
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok((triangulation, strip_safe))) => {
+                triangulations.push(triangulation);
+                safe.push(strip_safe);
+            }
+            _ => return None,
+        }
+    }
+
+    //safe elements keep their neighbors, edges towards the other elements are linked later.
+    let mut global_indexes = Vec::new();
+    let mut count = 0;
+    for strip_safe in safe.iter() {
+        global_indexes.push(strip_safe.iter()
+            .map(|&is_safe| if is_safe {
+                count += 1;
+                Some(T3Index(count - 1))
+            } else {
+                None
+            })
+            .collect::<Vec<_>>());
+    }
+
+    let mut elements = Vec::with_capacity(count);
+    let mut open_edges = BTreeMap::new();
+    let mut remaining_nodes = vec![false; nodes.len()];
+    for (strip_index, triangulation) in triangulations.iter().enumerate() {
+        let indexes = &strips[strip_index].indexes;
+        let strip_global_indexes = &global_indexes[strip_index];
+
+        for (local_index, ele) in triangulation.elements().iter().enumerate() {
+            let v = ele.nodes();
+            if strip_global_indexes[local_index].is_none() {
+                for n in v.iter() {
+                    remaining_nodes[indexes[n.0]] = true;
+                }
+                continue;
+            }
+
+            let mut neighbors = [None; 3];
+            for edge_index in 0..3 {
+                neighbors[edge_index] = ele.get_neighbor_from_index(edge_index)
+                    .and_then(|n| strip_global_indexes[n.0]);
+            }
+            let ele_index = T3Index(elements.len());
+            elements.push(Triangle::new_exact([N2Index(indexes[v[0].0]),
+                                               N2Index(indexes[v[1].0]),
+                                               N2Index(indexes[v[2].0])],
+                                              neighbors));
+
+            for edge_index in 0..3 {
+                if neighbors[edge_index].is_none() {
+                    let (n1, n2) = elements[ele_index.0].get_edge(edge_index);
+                    remaining_nodes[n1.0] = true;
+                    remaining_nodes[n2.0] = true;
+                    if !link(&mut elements, &mut open_edges, ele_index, edge_index) {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    //indexes are taken in increasing order, as in the strips.
+    let remaining: Vec<usize> = (0..nodes.len()).filter(|i| remaining_nodes[*i]).collect();
+    let remaining_points: Vec<Point2> = remaining.iter().map(|i| nodes[*i]).collect();
+    let rest = match Triangulation2::try_new_with_options(&remaining_points, &strip_options) {
+        Ok(rest) => rest,
+        Err(_) => return None,
+    };
+
+    //elements of the rest lying inside of the safe elements are not elements of the whole
+    //triangulation, the others fill the space between them.
+    let mut hints = vec![T3Index(0); strips.len()];
+    for ele in rest.elements() {
+        let center = ele.create_center_point(rest.nodes());
+        let strip_index = strips.iter()
+            .position(|s| center.x < s.right)
+            .unwrap_or(strips.len() - 1);
+        let triangulation = &triangulations[strip_index];
+
+        let covered = match locate_element_containing(hints[strip_index],
+                                                      triangulation.elements(),
+                                                      triangulation.nodes(),
+                                                      &center) {
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnEdge(ele_index, _) |
+            LocationResult::OnVertex(ele_index, _) => {
+                hints[strip_index] = ele_index;
+                safe[strip_index][ele_index.0]
+            }
+            LocationResult::Outside(ele_index, _) => {
+                hints[strip_index] = ele_index;
+                false
+            }
+        };
+        if covered {
+            continue;
+        }
+
+        let v = ele.nodes();
+        let ele_index = T3Index(elements.len());
+        elements.push(Triangle::new_exact([N2Index(remaining[v[0].0]),
+                                           N2Index(remaining[v[1].0]),
+                                           N2Index(remaining[v[2].0])],
+                                          [None; 3]));
+        for edge_index in 0..3 {
+            if !link(&mut elements, &mut open_edges, ele_index, edge_index) {
+                return None;
+            }
+        }
+    }
+
+    //only the edges of the convex hull stay open and the elements have its area, so they do
+    //not overlap and leave no holes.
+    let border_edges: usize = rest.elements()
+        .iter()
+        .map(|e| (0..3).filter(|i| e.get_neighbor_from_index(*i).is_none()).count())
+        .sum();
+    let hull_area = area(rest.nodes(), rest.elements());
+    if open_edges.len() != border_edges ||
+       (area(nodes, &elements) - hull_area).abs() > 1e-9 * hull_area {
+        return None;
+    }
+
+    let mut triangulation = Triangulation2::new_from_prebuilt_triangulation(nodes.to_vec(),
+                                                                            Vec::new());
+    *triangulation.elements_mut() = elements;
+    Some(triangulation)
+}
+
+//links the element with the element having the same edge in the opposite direction. false when
+//another element has the edge in the same direction, the elements overlap then.
+fn link(elements: &mut Vec<Triangle>,
+        open_edges: &mut BTreeMap<(N2Index, N2Index), T3Index>,
+        ele_index: T3Index,
+        edge_index: usize)
+        -> bool {
+    let (n1, n2) = elements[ele_index.0].get_edge(edge_index);
+
+    match open_edges.remove(&(n2, n1)) {
+        Some(neighbor_index) => {
+            elements[ele_index.0].set_neighbor(edge_index, Some(neighbor_index));
+            let neighbor_edge = elements[neighbor_index.0].get_neighbor_index(n2, n1);
+            elements[neighbor_index.0].set_neighbor(neighbor_edge, Some(ele_index));
+            true
+        }
+        None => open_edges.insert((n1, n2), ele_index).is_none(),
+    }
+}
+
+fn area(nodes: &[Point2], elements: &[Triangle]) -> f64 {
+    elements.iter()
+        .map(|ele| {
+            let (a, b, c) = (ele.a(nodes), ele.b(nodes), ele.c(nodes));
+            ((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)).abs() / 2.
+        })
+        .sum()
+}
+
+struct Strip {
+    indexes: Vec<usize>,
+    left: f64,
+    right: f64,
+}
+
+//splits the nodes into strips of about the same number of nodes. nodes with equal x are kept
+//in one strip, so that the strips are divided by lines lying between nodes.
+fn split_into_strips(nodes: &[Point2], sorted_indexes: &[usize], threads: usize) -> Vec<Strip> {
+    let count = threads.min(sorted_indexes.len() / MIN_STRIP_NODES);
+    let mut strips = Vec::new();
+    let mut start = 0;
+    let mut left = ::std::f64::MIN;
+
+    for strip_index in 1..count + 1 {
+        let mut end = if strip_index == count {
+            sorted_indexes.len()
+        } else {
+            (sorted_indexes.len() * strip_index / count).max(start + 1)
+        };
+        while end < sorted_indexes.len() &&
+              nodes[sorted_indexes[end]].x == nodes[sorted_indexes[end - 1]].x {
+            end += 1;
+        }
+        if end == start {
+            break;
+        }
+
+        let right = if end < sorted_indexes.len() {
+            (nodes[sorted_indexes[end - 1]].x + nodes[sorted_indexes[end]].x) / 2.
+        } else {
+            ::std::f64::MAX
+        };
+
+        let mut indexes = sorted_indexes[start..end].to_vec();
+        indexes.sort();
+        strips.push(Strip {
+            indexes: indexes,
+            left: left,
+            right: right,
+        });
+
+        start = end;
+        left = right;
+    }
+
+    strips
+}
+
+//elements whose circumcircle lies between the bounds of the strip. the bounds are kept a bit
+//inside, an element wrongly taken as not safe is only triangulated again.
+fn safe_elements(triangulation: &Triangulation2, left: f64, right: f64) -> Vec<bool> {
+    let nodes = triangulation.nodes();
+    triangulation.elements()
+        .iter()
+        .map(|ele| {
+            let (a, b, c) = (ele.a(nodes), ele.b(nodes), ele.c(nodes));
+            let (bx, by) = (b.x - a.x, b.y - a.y);
+            let (cx, cy) = (c.x - a.x, c.y - a.y);
+            let d = 2. * (bx * cy - by * cx);
+            let center_x = a.x + (cy * (bx * bx + by * by) - by * (cx * cx + cy * cy)) / d;
+            let center_y = a.y + (bx * (cx * cx + cy * cy) - cx * (bx * bx + by * by)) / d;
+            let radius = ((center_x - a.x).powi(2) + (center_y - a.y).powi(2)).sqrt();
+            let margin = 1e-9 * (center_x.abs() + radius);
+
+            center_x - radius - margin > left && center_x + radius + margin < right
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation2_test_utils::assert_neighborhood;
+    use types::triangulation2_test_utils::random_points;
+    use types::triangulation2_test_utils::sorted_elements;
+
+    #[test]
+    fn building_random_nodes_in_parallel() {
+        let nodes = random_points(3, 20000);
+        let options = BuildOptions {
+            threads: 4,
+            insertion_order: InsertionOrder::Hilbert,
+            ..BuildOptions::default()
+        };
+
+        let parallel = super::build(&nodes, &sorted_by_x(&nodes), &options)
+            .expect("random nodes are split into strips.");
+        let sequential = Triangulation2::new(&nodes);
+
+        assert_eq!(sorted_elements(&sequential), sorted_elements(&parallel));
+        assert_neighborhood(&parallel);
+    }
+
+    #[test]
+    fn building_grid_in_parallel() {
+        //nodes of the grid lie on common circles and on the borders of the strips.
+        let mut nodes = Vec::new();
+        for i in 0..4900 {
+            let position = (i * 37) % 4900;
+            nodes.push(Point2::new((position % 70) as f64, (position / 70) as f64));
+        }
+        let options = BuildOptions {
+            threads: 3,
+            ..BuildOptions::default()
+        };
+
+        let parallel = super::build(&nodes, &sorted_by_x(&nodes), &options)
+            .expect("grid is split into strips.");
+        let sequential = Triangulation2::new(&nodes);
+
+        assert_eq!(sorted_elements(&sequential), sorted_elements(&parallel));
+        assert_neighborhood(&parallel);
+    }
+
+    #[test]
+    fn small_sets_are_not_split() {
+        let nodes: Vec<Point2> = (0..1500)
+            .map(|i| Point2::new(i as f64, (i * i % 7) as f64))
+            .collect();
+        let options = BuildOptions {
+            threads: 4,
+            ..BuildOptions::default()
+        };
+
+        assert!(super::build(&nodes, &sorted_by_x(&nodes), &options).is_none());
+    }
+
+    fn sorted_by_x(nodes: &[Point2]) -> Vec<usize> {
+        let mut sorted: Vec<usize> = (0..nodes.len()).collect();
+        sorted.sort_by(|a, b| nodes[*a].x.partial_cmp(&nodes[*b].x).unwrap());
+        sorted
+    }
+}
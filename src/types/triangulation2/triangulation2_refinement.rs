@@ -0,0 +1,499 @@
+use super::Triangulation2;
+use super::triangulation2_insertion;
+
+use types::*;
+use math::orient2d;
+use math::distance2_squared;
+use algorithms2::element_locators::*;
+use algorithms2::nearest_node::elements_around_node;
+
+//segment each inserted node was put on, as its two input end nodes.
+struct SegmentNodes {
+    input_nodes: usize,
+    segments: Vec<Option<(N2Index, N2Index)>>,
+}
+
+impl SegmentNodes {
+    fn segment_of(&self, node: N2Index) -> Option<(N2Index, N2Index)> {
+        if node.0 < self.input_nodes {
+            None
+        } else {
+            self.segments[node.0 - self.input_nodes]
+        }
+    }
+
+    fn is_input(&self, node: N2Index) -> bool {
+        node.0 < self.input_nodes
+    }
+}
+
+//what to do with an element which is not good enough.
+enum Step {
+    Insert(T3Index, Point2),
+    SplitSegment(T3Index, usize),
+    Skip,
+}
+
+//ruppert's refinement. segments are the constrained edges and the border edges. encroached
+//segments are split, bad elements get their circumcenter inserted unless it would encroach a
+//segment, which is then split instead. size, when given, is the target edge length at a point,
+//elements with a longer edge than the size at their centroid are split too. returns the number of
+//inserted nodes.
+pub fn refine<F>(triangulation: &mut Triangulation2,
+                 options: &RefinementOptions,
+                 size: Option<F>)
+                 -> usize
+    where F: Fn(&Point2) -> f64
+{
+    let mut segment_nodes = SegmentNodes {
+        input_nodes: triangulation.nodes().len(),
+        segments: Vec::new(),
+    };
+    let mut inserted = 0;
+    let mut to_check: Vec<T3Index> = (0..triangulation.elements().len()).map(T3Index).collect();
+
+    while let Some(ele_index) = to_check.pop() {
+        if let Some(max_steiner_nodes) = options.max_steiner_nodes {
+            if inserted >= max_steiner_nodes {
+                break;
+            }
+        }
+
+        let step = match find_encroached_segment(triangulation, ele_index) {
+            Some(edge_index) => Step::SplitSegment(ele_index, edge_index),
+            None if is_bad(triangulation, ele_index, options, &size, &segment_nodes) => {
+                let center = circumcenter(triangulation, ele_index);
+                find_insertion(triangulation, ele_index, &center)
+            }
+            None => Step::Skip,
+        };
+
+        let (new_node, segment) = match step {
+            Step::Insert(containing, center) => {
+                let location_result = locate_element_containing(containing,
+                                                                 triangulation.elements(),
+                                                                 triangulation.nodes(),
+                                                                 &center);
+                (insert_steiner_node(triangulation, center, location_result), None)
+            }
+            Step::SplitSegment(segment_ele_index, edge_index) => {
+                let (first, second) = triangulation.elements()[segment_ele_index.0]
+                    .get_edge(edge_index);
+                let split_at = split_point(triangulation, first, second, &segment_nodes);
+                let segment = segment_nodes.segment_of(first)
+                    .or(segment_nodes.segment_of(second))
+                    .unwrap_or((first, second));
+
+                //node is put on the segment even when rounding moved it off the line.
+                (insert_steiner_node(triangulation,
+                                     split_at,
+                                     LocationResult::OnEdge(segment_ele_index, edge_index)),
+                 Some(segment))
+            }
+            Step::Skip => (None, None),
+        };
+
+        if let Some((node_index, containing)) = new_node {
+            segment_nodes.segments.push(segment);
+            inserted += 1;
+            to_check.push(ele_index);
+            to_check.extend(elements_around_node(containing, triangulation.elements(), node_index));
+        }
+    }
+
+    inserted
+}
+
+fn insert_steiner_node(triangulation: &mut Triangulation2,
+                       p: Point2,
+                       location_result: LocationResult)
+                       -> Option<(N2Index, T3Index)> {
+    let node_index = N2Index(triangulation.nodes().len());
+    triangulation.nodes_mut().push(p);
+
+    match triangulation2_insertion::insert_at_location(triangulation, node_index, location_result) {
+        Ok(containing) => Some((node_index, containing)),
+        Err(_) => {
+            triangulation.nodes_mut().pop();
+            None
+        }
+    }
+}
+
+//segments between two input nodes or two inserted nodes are split in the middle. segment from an
+//input node is split at a power of two distance from it, so that segments meeting at a small
+//angle are split at the same distances and stop encroaching each other.
+fn split_point(triangulation: &Triangulation2,
+               first: N2Index,
+               second: N2Index,
+               segment_nodes: &SegmentNodes)
+               -> Point2 {
+    let nodes = triangulation.nodes();
+    let (a, b) = (&nodes[first.0], &nodes[second.0]);
+
+    if segment_nodes.is_input(first) == segment_nodes.is_input(second) {
+        return Point2::new((a.x + b.x) / 2., (a.y + b.y) / 2.);
+    }
+
+    let (apex, other) = if segment_nodes.is_input(first) { (a, b) } else { (b, a) };
+    let length = distance2_squared(apex, other).sqrt();
+    let shell = 2f64.powf((length / 2.).log2().round());
+    let t = shell / length;
+
+    Point2::new(apex.x + (other.x - apex.x) * t,
+                apex.y + (other.y - apex.y) * t)
+}
+
+#[inline]
+fn is_segment(ele: &Triangle, edge_index: usize) -> bool {
+    ele.is_constrained(edge_index) || ele.get_neighbor_from_index(edge_index).is_none()
+}
+
+//point encroaches a segment when it lies inside of the circle with the segment as diameter.
+#[inline]
+fn encroaches(a: &Point2, b: &Point2, p: &Point2) -> bool {
+    (a.x - p.x) * (b.x - p.x) + (a.y - p.y) * (b.y - p.y) < 0.
+}
+
+//segment of the element encroached by the node opposite to it.
+fn find_encroached_segment(triangulation: &Triangulation2, ele_index: T3Index) -> Option<usize> {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+
+    (0..3).find(|edge_index| {
+        let (first, second) = ele.get_edge(*edge_index);
+        let opposite = ele.get_other_last_node(first, second);
+        is_segment(ele, *edge_index) &&
+        encroaches(&nodes[first.0], &nodes[second.0], &nodes[opposite.0])
+    })
+}
+
+fn angles(triangulation: &Triangulation2, ele_index: T3Index) -> [f64; 3] {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let mut angles = [0.; 3];
+
+    for i in 0..3 {
+        let corner = &nodes[ele.nodes()[i].0];
+        let next = &nodes[ele.nodes()[(i + 1) % 3].0];
+        let previous = &nodes[ele.nodes()[(i + 2) % 3].0];
+        let (ux, uy) = (next.x - corner.x, next.y - corner.y);
+        let (vx, vy) = (previous.x - corner.x, previous.y - corner.y);
+
+        angles[i] = (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy).to_degrees();
+    }
+
+    angles
+}
+
+//small angle between two segments can not be improved, splitting it would never end. neither can
+//an element whose shortest edge joins nodes at the same distance on two segments from one input
+//node, its circumcenter would encroach the segments again closer to that node.
+fn is_bad<F>(triangulation: &Triangulation2,
+             ele_index: T3Index,
+             options: &RefinementOptions,
+             size: &Option<F>,
+             segment_nodes: &SegmentNodes)
+             -> bool
+    where F: Fn(&Point2) -> f64
+{
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let (a, b, c) = (ele.a(nodes), ele.b(nodes), ele.c(nodes));
+
+    if let Some(max_area) = options.max_area {
+        if -orient2d(a, b, c) / 2. > max_area {
+            return true;
+        }
+    }
+
+    if let Some(ref size) = *size {
+        let target = size(&Point2::new((a.x + b.x + c.x) / 3., (a.y + b.y + c.y) / 3.));
+        let longest = distance2_squared(a, b)
+            .max(distance2_squared(b, c))
+            .max(distance2_squared(c, a));
+        if longest > target * target {
+            return true;
+        }
+    }
+
+    let angles = angles(triangulation, ele_index);
+    if angles.iter().any(|angle| *angle > options.max_angle) {
+        return true;
+    }
+
+    let smallest = (0..3)
+        .min_by(|a, b| angles[*a].partial_cmp(&angles[*b]).unwrap_or(::std::cmp::Ordering::Equal))
+        .unwrap_or(0);
+
+    //edges starting and ending in the corner of the smallest angle.
+    angles[smallest] < options.min_angle &&
+    !(is_segment(ele, smallest) && is_segment(ele, (smallest + 2) % 3)) &&
+    !is_on_same_shell(triangulation, ele.get_edge((smallest + 1) % 3), segment_nodes)
+}
+
+fn is_on_same_shell(triangulation: &Triangulation2,
+                    edge: (N2Index, N2Index),
+                    segment_nodes: &SegmentNodes)
+                    -> bool {
+    let (first_segment, second_segment) = match (segment_nodes.segment_of(edge.0),
+                                                 segment_nodes.segment_of(edge.1)) {
+        (Some(first_segment), Some(second_segment)) => (first_segment, second_segment),
+        _ => return false,
+    };
+
+    let apex = if first_segment.0 == second_segment.0 || first_segment.0 == second_segment.1 {
+        first_segment.0
+    } else if first_segment.1 == second_segment.0 || first_segment.1 == second_segment.1 {
+        first_segment.1
+    } else {
+        return false;
+    };
+
+    let nodes = triangulation.nodes();
+    let ratio = distance2_squared(&nodes[apex.0], &nodes[(edge.0).0]) /
+                distance2_squared(&nodes[apex.0], &nodes[(edge.1).0]);
+    first_segment != second_segment && ratio > 0.998 && ratio < 1.002
+}
+
+fn circumcenter(triangulation: &Triangulation2, ele_index: T3Index) -> Point2 {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let (a, b, c) = (ele.a(nodes), ele.b(nodes), ele.c(nodes));
+
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let b_squared = bx * bx + by * by;
+    let c_squared = cx * cx + cy * cy;
+    let d = 2. * (bx * cy - by * cx);
+
+    Point2::new(a.x + (cy * b_squared - by * c_squared) / d,
+                a.y + (bx * c_squared - cx * b_squared) / d)
+}
+
+//walks the elements whose circumcircle contains the point without crossing a segment. those are
+//the elements the point would replace, so their segments are the ones it could encroach. when no
+//such element contains the point, a segment separates it from the bad element.
+fn find_insertion(triangulation: &Triangulation2, bad_ele_index: T3Index, p: &Point2) -> Step {
+    let nodes = triangulation.nodes();
+    let mut visited = vec![bad_ele_index];
+    let mut stack = vec![bad_ele_index];
+    let mut containing = None;
+    let mut separating = None;
+
+    while let Some(ele_index) = stack.pop() {
+        let ele = &triangulation.elements()[ele_index.0];
+        if containing.is_none() && ele.is_point_inside(nodes, p) {
+            containing = Some(ele_index);
+        }
+
+        for edge_index in 0..3 {
+            let (first, second) = ele.get_edge(edge_index);
+
+            if is_segment(ele, edge_index) {
+                if encroaches(&nodes[first.0], &nodes[second.0], p) {
+                    return Step::SplitSegment(ele_index, edge_index);
+                }
+                if separating.is_none() && orient2d(&nodes[first.0], &nodes[second.0], p) > 0. {
+                    separating = Some((ele_index, edge_index));
+                }
+                continue;
+            }
+
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(edge_index) {
+                if !visited.contains(&neighbor_index) &&
+                   triangulation.is_inside_circumcircle(&triangulation.elements()[neighbor_index.0],
+                                                        p) {
+                    visited.push(neighbor_index);
+                    stack.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    match (containing, separating) {
+        (Some(ele_index), _) => Step::Insert(ele_index, *p),
+        (None, Some((ele_index, edge_index))) => Step::SplitSegment(ele_index, edge_index),
+        (None, None) => Step::Skip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use math::orient2d;
+    use math::distance2_squared;
+    use types::triangulation2_test_utils::area;
+    use types::triangulation2_test_utils::assert_constrained_delaunay;
+    use types::triangulation2_test_utils::assert_neighborhood;
+
+    fn smallest_angle(tr: &Triangulation2) -> f64 {
+        let nodes = tr.nodes();
+        let mut smallest = 180.;
+
+        for ele in tr.elements() {
+            for i in 0..3 {
+                let corner = &nodes[ele.nodes()[i].0];
+                let next = &nodes[ele.nodes()[(i + 1) % 3].0];
+                let previous = &nodes[ele.nodes()[(i + 2) % 3].0];
+                let (ux, uy) = (next.x - corner.x, next.y - corner.y);
+                let (vx, vy) = (previous.x - corner.x, previous.y - corner.y);
+                let angle = (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy).to_degrees();
+
+                if angle < smallest {
+                    smallest = angle;
+                }
+            }
+        }
+
+        smallest
+    }
+
+    #[test]
+    fn refining_thin_rectangle() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 0.),
+                                                      Point2::new(10., 0.),
+                                                      Point2::new(10., 1.),
+                                                      Point2::new(0., 1.),
+                                                      Point2::new(5., 0.5)]);
+        assert!(smallest_angle(&triangulation) < 10.);
+
+        let inserted = triangulation.refine(&RefinementOptions::default());
+
+        assert_eq!(triangulation.nodes().len(), 5 + inserted);
+        assert!(smallest_angle(&triangulation) >= 20.);
+        assert!((area(&triangulation) - 10.).abs() < 1e-9);
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn refining_domain_with_hole_and_area_limit() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(4., 0.),
+                     Point2::new(4., 4.),
+                     Point2::new(0., 4.),
+                     Point2::new(1., 1.),
+                     Point2::new(3., 1.),
+                     Point2::new(3., 3.),
+                     Point2::new(1., 3.)];
+        let segments = [(N2Index(0), N2Index(1)),
+                        (N2Index(1), N2Index(2)),
+                        (N2Index(2), N2Index(3)),
+                        (N2Index(3), N2Index(0)),
+                        (N2Index(4), N2Index(5)),
+                        (N2Index(5), N2Index(6)),
+                        (N2Index(6), N2Index(7)),
+                        (N2Index(7), N2Index(4))];
+        let mut triangulation =
+            Triangulation2::from_pslg(&nodes, &segments, &[Point2::new(2., 2.)]);
+
+        let options = RefinementOptions {
+            min_angle: 25.,
+            max_area: Some(0.1),
+            ..RefinementOptions::default()
+        };
+        triangulation.refine(&options);
+
+        assert!(smallest_angle(&triangulation) >= 25.);
+        assert!(triangulation.elements()
+            .iter()
+            .all(|e| {
+                -orient2d(e.a(triangulation.nodes()),
+                          e.b(triangulation.nodes()),
+                          e.c(triangulation.nodes())) / 2. <= 0.1
+            }));
+        assert!((area(&triangulation) - 12.).abs() < 1e-9);
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+
+        //nothing is inserted into the hole.
+        assert!(triangulation.nodes()
+            .iter()
+            .all(|n| n.x <= 1. || n.x >= 3. || n.y <= 1. || n.y >= 3.));
+    }
+
+    #[test]
+    fn refining_to_size_field() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 0.),
+                                                      Point2::new(10., 0.),
+                                                      Point2::new(10., 10.),
+                                                      Point2::new(0., 10.)]);
+        //elements are concentrated around the corner at the origin.
+        let size = |p: &Point2| 0.2 + 0.2 * (p.x * p.x + p.y * p.y).sqrt();
+
+        triangulation.refine_with_size(&RefinementOptions::default(), size);
+
+        let nodes = triangulation.nodes();
+        let mut near = 0;
+        let mut far = 0;
+        for ele in triangulation.elements() {
+            let (a, b, c) = (ele.a(nodes), ele.b(nodes), ele.c(nodes));
+            let centroid = Point2::new((a.x + b.x + c.x) / 3., (a.y + b.y + c.y) / 3.);
+            let longest = distance2_squared(a, b)
+                .max(distance2_squared(b, c))
+                .max(distance2_squared(c, a))
+                .sqrt();
+            assert!(longest <= size(&centroid));
+
+            if centroid.x < 2. && centroid.y < 2. {
+                near += 1;
+            } else if centroid.x > 8. && centroid.y > 8. {
+                far += 1;
+            }
+        }
+
+        assert!(near > 4 * far);
+        assert!(smallest_angle(&triangulation) >= 20.);
+        assert!((area(&triangulation) - 100.).abs() < 1e-9);
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn refining_to_unreachable_angle_needs_node_budget() {
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(10., 0.),
+                     Point2::new(10., 1.),
+                     Point2::new(0., 1.)];
+        let options = RefinementOptions {
+            min_angle: 40.,
+            ..RefinementOptions::default()
+        };
+
+        let mut triangulation = Triangulation2::new(&nodes);
+        match triangulation.try_refine(&options) {
+            Err(DelaunayError::UnboundedRefinement(_)) => (),
+            other => panic!("expected unbounded refinement, got {:?}", other),
+        }
+        assert_eq!(4, triangulation.nodes().len());
+
+        let options = RefinementOptions {
+            max_steiner_nodes: Some(50),
+            ..options
+        };
+        assert_eq!(50, triangulation.try_refine(&options).unwrap());
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+
+    #[test]
+    fn refining_stops_at_node_budget() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 0.),
+                                                      Point2::new(10., 0.),
+                                                      Point2::new(10., 1.),
+                                                      Point2::new(0., 1.)]);
+        let options = RefinementOptions {
+            min_angle: 30.,
+            max_angle: 120.,
+            max_area: Some(0.01),
+            max_steiner_nodes: Some(7),
+            ..RefinementOptions::default()
+        };
+
+        assert_eq!(7, triangulation.refine(&options));
+        assert_eq!(11, triangulation.nodes().len());
+        assert_neighborhood(&triangulation);
+        assert_constrained_delaunay(&triangulation);
+    }
+}
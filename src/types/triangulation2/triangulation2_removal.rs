@@ -0,0 +1,327 @@
+use super::Triangulation2;
+use super::triangulation2_utilities;
+
+use types::*;
+use math::orient2d;
+use algorithms2::nearest_node::elements_around_node;
+use algorithms2::lawson_flipping;
+
+//node on the border of the hole left by the removed elements, with the element behind the border
+//edge starting at the node.
+struct BorderNode {
+    node: N2Index,
+    outside: Option<T3Index>,
+    constrained: bool,
+}
+
+//removes the elements around the node and fills the hole with the delaunay triangles of the nodes
+//on its border, clipping one ear at a time. new elements reuse the freed slots, the rest of the
+//slots are compacted. returns an element next to the removed node. border of a triangulation
+//restricted to a domain is not convex, so its nodes can not be removed.
+pub fn remove_node(triangulation: &mut Triangulation2,
+                   element_index: T3Index,
+                   node: N2Index,
+                   restricted: bool)
+                   -> Result<T3Index, DelaunayError> {
+    let mut star = elements_around_node(element_index, triangulation.elements(), node);
+    let (mut border, closed) = find_border(triangulation, &star, node);
+
+    if restricted && !closed {
+        return Err(DelaunayError::NodeOnDomainBorder(node.0));
+    }
+
+    if star.len() == triangulation.elements().len() {
+        let border_indexes: Vec<usize> = border.iter().map(|b| b.node.0).collect();
+        if triangulation2_utilities::find_first_not_collinear(triangulation.nodes(),
+                                                              &border_indexes)
+            .is_none() {
+            return Err(DelaunayError::CollinearNodes);
+        }
+    }
+
+    let border_nodes: Vec<N2Index> = border.iter().map(|b| b.node).collect();
+    star.sort();
+    let mut free_slots = star.into_iter();
+    let mut near_element = None;
+    let mut new_elements = Vec::new();
+
+    loop {
+        if closed && border.len() == 3 {
+            let slot = free_slots.next().expect("Hole has more elements than was removed.");
+            add_element(triangulation,
+                        slot,
+                        [border[0].node, border[1].node, border[2].node],
+                        [border[0].outside, border[1].outside, border[2].outside],
+                        [border[0].constrained, border[1].constrained, border[2].constrained]);
+            near_element = Some(slot);
+            new_elements.push(slot);
+            border.clear();
+            break;
+        }
+
+        let ear = match find_ear(triangulation, &border, closed, &border_nodes) {
+            Some(ear) => ear,
+            None => break,
+        };
+
+        let previous = (ear + border.len() - 1) % border.len();
+        let next = (ear + 1) % border.len();
+
+        let slot = free_slots.next().expect("Hole has more elements than was removed.");
+        add_element(triangulation,
+                    slot,
+                    [border[previous].node, border[ear].node, border[next].node],
+                    [border[previous].outside, border[ear].outside, None],
+                    [border[previous].constrained, border[ear].constrained, false]);
+        near_element = Some(slot);
+        new_elements.push(slot);
+
+        border[previous].outside = Some(slot);
+        border[previous].constrained = false;
+        border.remove(ear);
+    }
+
+    //what is left of an open border became a part of the convex hull.
+    for position in 1..border.len() {
+        if let Some(outside) = border[position - 1].outside {
+            triangulation.elements_mut()[outside.0]
+                .update_neighbor(border[position - 1].node, border[position].node, None);
+            near_element = near_element.or(Some(outside));
+        }
+    }
+
+    lawson_flipping::flip_until_delaunay(triangulation, new_elements);
+
+    let mut near_element = near_element.expect("Removal left no elements.");
+    let mut unused_slots: Vec<T3Index> = free_slots.collect();
+    unused_slots.sort_by(|a, b| b.cmp(a));
+
+    for slot in unused_slots {
+        let moved = remove_element(triangulation, slot);
+        if moved == Some(near_element) {
+            near_element = slot;
+        }
+    }
+
+    Ok(near_element)
+}
+
+//border nodes ordered so that the hole lies right of every border edge, like inside of elements.
+//border is closed when the node was not on the convex hull.
+fn find_border(triangulation: &Triangulation2,
+               star: &[T3Index],
+               node: N2Index)
+               -> (Vec<BorderNode>, bool) {
+    let mut edges = Vec::with_capacity(star.len());
+    for ele_index in star.iter() {
+        let ele: &Triangle = &triangulation.elements()[ele_index.0];
+        let (n1, n2) = ele.get_others_two_nodes(node);
+        edges.push((n1,
+                    n2,
+                    ele.get_neighor_for_nodes(n1, n2),
+                    ele.is_constrained_for_nodes(n1, n2)));
+    }
+
+    let first = edges.iter()
+        .position(|e| !edges.iter().any(|other| other.1 == e.0));
+    let closed = first.is_none();
+
+    let mut border = Vec::with_capacity(edges.len() + 1);
+    let start = first.unwrap_or(0);
+    let mut current = start;
+
+    loop {
+        let (n1, n2, outside, constrained) = edges[current];
+        border.push(BorderNode {
+            node: n1,
+            outside: outside,
+            constrained: constrained,
+        });
+
+        match edges.iter().position(|e| e.0 == n2) {
+            Some(next) if next != start => current = next,
+            _ => {
+                if !closed {
+                    border.push(BorderNode {
+                        node: n2,
+                        outside: None,
+                        constrained: false,
+                    });
+                }
+                break;
+            }
+        }
+    }
+
+    (border, closed)
+}
+
+//ear is a convex corner of the border whose circumcircle contains no other border node. constrained
+//edges may hide nodes behind them, so when there is no such corner any convex corner with no border
+//node inside is taken, and the new edges are flipped later.
+fn find_ear(triangulation: &Triangulation2,
+            border: &[BorderNode],
+            closed: bool,
+            border_nodes: &[N2Index])
+            -> Option<usize> {
+    let candidates = if closed {
+        0..border.len()
+    } else {
+        1..border.len().saturating_sub(1)
+    };
+
+    let mut empty_ear = None;
+
+    for ear in candidates {
+        let previous = border[(ear + border.len() - 1) % border.len()].node;
+        let current = border[ear].node;
+        let next = border[(ear + 1) % border.len()].node;
+
+        let nodes = triangulation.nodes();
+        if orient2d(&nodes[previous.0], &nodes[current.0], &nodes[next.0]) >= 0. {
+            continue;
+        }
+
+        let candidate = Triangle::new_exact([previous, current, next], [None, None, None]);
+        let others = || {
+            border_nodes.iter().filter(|n| **n != previous && **n != current && **n != next)
+        };
+
+        if others().all(|n| !triangulation.is_node_inside_circumcircle(&candidate, *n)) {
+            return Some(ear);
+        }
+
+        if empty_ear.is_none() &&
+           others().all(|n| !candidate.is_point_inside(nodes, &nodes[n.0])) {
+            empty_ear = Some(ear);
+        }
+    }
+
+    empty_ear
+}
+
+fn add_element(triangulation: &mut Triangulation2,
+               slot: T3Index,
+               v: [N2Index; 3],
+               n: [Option<T3Index>; 3],
+               c: [bool; 3]) {
+    triangulation.elements_mut()[slot.0] = Triangle::new_exact(v, n);
+
+    for i in 0..3 {
+        triangulation.elements_mut()[slot.0].set_constrained(i, c[i]);
+
+        if let Some(neighbor_index) = n[i] {
+            triangulation.elements_mut()[neighbor_index.0]
+                .update_neighbor(v[i], v[(i + 1) % 3], Some(slot));
+        }
+    }
+}
+
+//moves the last element into the slot. returns the previous index of the moved element.
+pub fn remove_element(triangulation: &mut Triangulation2, slot: T3Index) -> Option<T3Index> {
+    let last = T3Index(triangulation.elements().len() - 1);
+    triangulation.elements_mut().swap_remove(slot.0);
+
+    if slot == last {
+        return None;
+    }
+
+    let neighbors = *triangulation.elements()[slot.0].neighbors();
+    for neighbor_index in neighbors.iter().filter_map(|n| *n) {
+        let neighbor: &mut Triangle = &mut triangulation.elements_mut()[neighbor_index.0];
+        for i in 0..3 {
+            if neighbor.get_neighbor_from_index(i) == Some(last) {
+                neighbor.set_neighbor(i, Some(slot));
+            }
+        }
+    }
+
+    Some(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation2_test_utils::assert_neighborhood;
+    use types::triangulation2_test_utils::sorted_elements;
+    use types::triangulation2_test_utils::sorted_renumbered_elements;
+
+    #[test]
+    fn removing_inner_node_of_square() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 1.),
+                                                      Point2::new(1., 1.),
+                                                      Point2::new(0., 0.),
+                                                      Point2::new(1., 0.),
+                                                      Point2::new(0.4, 0.6)]);
+        assert_eq!(4, triangulation.elements().len());
+
+        triangulation.remove_node(N2Index(4));
+
+        let square = Triangulation2::new(&triangulation.nodes()[..4]);
+        assert_eq!(5, triangulation.nodes().len());
+        assert_eq!(sorted_elements(&square),
+                   sorted_elements(&triangulation));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_nodes_of_grid_gives_delaunay_triangulation_of_the_rest() {
+        //grid has many cocircular nodes, removed are corners, border and inner nodes.
+        let mut grid = Vec::new();
+        for i in 0..36 {
+            let position = (i * 7) % 36;
+            grid.push(Point2::new((position % 6) as f64, (position / 6) as f64));
+        }
+
+        let mut triangulation = Triangulation2::new(&grid);
+        let mut removed = vec![false; grid.len()];
+
+        for node in [0, 5, 14, 3, 27, 31, 8, 17, 1, 22].iter() {
+            triangulation.remove_node(N2Index(*node));
+            removed[*node] = true;
+            assert_neighborhood(&triangulation);
+
+            let mut renumbered = vec![0; grid.len()];
+            let mut rest = Vec::new();
+            for index in 0..grid.len() {
+                if !removed[index] {
+                    renumbered[index] = rest.len();
+                    rest.push(grid[index]);
+                }
+            }
+
+            let rebuilt = Triangulation2::new(&rest);
+            assert_eq!(sorted_elements(&rebuilt),
+                       sorted_renumbered_elements(&triangulation, &renumbered));
+        }
+
+        assert_eq!(N2Index(36),
+                   triangulation.insert_node(&Point2::new(2.5, 2.5)));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_node_which_is_not_in_triangulation() {
+        let mut triangulation = Triangulation2::new(&[Point2::new(0., 1.),
+                                                      Point2::new(1., 1.),
+                                                      Point2::new(0., 0.),
+                                                      Point2::new(1., 0.)]);
+
+        triangulation.remove_node(N2Index(3));
+        assert_eq!(1, triangulation.elements().len());
+
+        match triangulation.try_remove_node(N2Index(3)) {
+            Err(DelaunayError::UnknownNode(3)) => (),
+            other => panic!("expected unknown node, got {:?}", other),
+        }
+        match triangulation.try_remove_node(N2Index(7)) {
+            Err(DelaunayError::UnknownNode(7)) => (),
+            other => panic!("expected unknown node, got {:?}", other),
+        }
+        match triangulation.try_remove_node(N2Index(0)) {
+            Err(DelaunayError::CollinearNodes) => (),
+            other => panic!("expected collinear nodes, got {:?}", other),
+        }
+        assert_eq!(1, triangulation.elements().len());
+    }
+}
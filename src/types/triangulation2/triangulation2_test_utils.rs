@@ -0,0 +1,109 @@
+use types::Triangulation2;
+use types::TriangulationNeighborhood;
+use math::orient2d;
+
+#[cfg(test)]
+use types::Point2;
+#[cfg(test)]
+use types::N2Index;
+#[cfg(test)]
+use rand::distributions::{IndependentSample, Range};
+#[cfg(test)]
+use rand::{SeedableRng, StdRng};
+
+//nodes of the elements, each element and the list sorted, so triangulations built in a different
+//order can be compared.
+pub fn sorted_elements(tr: &Triangulation2) -> Vec<Vec<usize>> {
+    let identity: Vec<usize> = (0..tr.nodes().len()).collect();
+    sorted_renumbered_elements(tr, &identity)
+}
+
+//same as sorted_elements, with node i replaced by renumbered[i].
+pub fn sorted_renumbered_elements(tr: &Triangulation2, renumbered: &[usize]) -> Vec<Vec<usize>> {
+    let mut elements: Vec<Vec<usize>> = tr.elements()
+        .iter()
+        .map(|e| {
+            let mut v: Vec<usize> = e.nodes().iter().map(|n| renumbered[n.0]).collect();
+            v.sort();
+            v
+        })
+        .collect();
+    elements.sort();
+    elements
+}
+
+//neighbors are the ones found again from the nodes of the elements, both sides of an edge agree
+//on its constraint and elements are kept clockwise.
+pub fn assert_neighborhood(tr: &Triangulation2) {
+    let mut relearned = tr.elements().clone();
+    TriangulationNeighborhood::teach_triangles_of_neighborhood(&mut relearned);
+
+    for (ele, relearned) in tr.elements().iter().zip(relearned.iter()) {
+        assert_eq!(ele.neighbors(), relearned.neighbors());
+        ele.assert_order(tr.nodes());
+
+        for edge_index in 0..3 {
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(edge_index) {
+                let (n1, n2) = ele.get_edge(edge_index);
+                assert_eq!(ele.is_constrained(edge_index),
+                           tr.elements()[neighbor_index.0].is_constrained_for_nodes(n1, n2));
+            }
+        }
+    }
+}
+
+//every element is delaunay towards the neighbors it is not separated from by a constraint.
+pub fn assert_constrained_delaunay(tr: &Triangulation2) {
+    for ele in tr.elements() {
+        for edge_index in 0..3 {
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(edge_index) {
+                if ele.is_constrained(edge_index) {
+                    continue;
+                }
+
+                let (n1, n2) = ele.get_edge(edge_index);
+                let opposite = tr.elements()[neighbor_index.0].get_other_last_node(n1, n2);
+                assert!(!tr.is_node_inside_circumcircle(ele, opposite));
+            }
+        }
+    }
+}
+
+//sum of the areas of the elements, which are clockwise.
+pub fn area(tr: &Triangulation2) -> f64 {
+    tr.elements()
+        .iter()
+        .map(|e| -orient2d(e.a(tr.nodes()), e.b(tr.nodes()), e.c(tr.nodes())) / 2.)
+        .sum()
+}
+
+//count points in the unit square, the same ones for the same seed.
+#[cfg(test)]
+pub fn random_points(seed: usize, count: usize) -> Vec<Point2> {
+    let seed: &[_] = &[seed];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let between = Range::new(0., 1.);
+
+    (0..count)
+        .map(|_| Point2::new(between.ind_sample(&mut rng), between.ind_sample(&mut rng)))
+        .collect()
+}
+
+//300 random nodes in a square of size 100 with constraints between random pairs of them, the
+//ones which do not cross earlier constraints are kept. such a triangulation is not delaunay.
+#[cfg(test)]
+pub fn randomly_constrained(seed: usize) -> Triangulation2 {
+    let nodes: Vec<Point2> = random_points(seed, 300)
+        .iter()
+        .map(|p| Point2::new(100. * p.x, 100. * p.y))
+        .collect();
+    let mut triangulation = Triangulation2::new(&nodes);
+
+    for pair in random_points(seed + 1, 2000) {
+        let (first, last) = ((pair.x * 300.) as usize, (pair.y * 300.) as usize);
+        if first != last {
+            let _ = triangulation.try_insert_constraint(N2Index(first), N2Index(last));
+        }
+    }
+    triangulation
+}
@@ -1,33 +1,52 @@
 use types::Point2;
+use types::InsertionOrder;
+use math::side_of_line;
+use math::PointLiesOnLineSide;
+use math::brio_order;
+use math::hilbert_index;
+use math::morton_index;
+use math::to_curve_coordinate;
 
-pub fn find_corner_nodes(nodes: &[Point2]) -> (usize, usize, usize, usize) {
-    let (mut top_left_index, mut top_right_index, mut bottom_left_index, mut bottom_right_index) =
-        (0usize, 0usize, 0usize, 0usize);
+//returns position in sorted_indexes of the first node making a triangle with the first two nodes.
+pub fn find_first_not_collinear(nodes: &[Point2], sorted_indexes: &[usize]) -> Option<usize> {
+    if sorted_indexes.len() < 3 {
+        return None;
+    }
 
-    for i in 1..nodes.len() {
-        let candidate = nodes[i];
-        let current_top_left = nodes[top_left_index];
-        if current_top_left.x > candidate.x || current_top_left.y < candidate.y {
-            top_left_index = i;
-        }
+    let first = &nodes[sorted_indexes[0]];
+    let second = &nodes[sorted_indexes[1]];
 
-        let current_top_right = nodes[top_right_index];
-        if current_top_right.x < candidate.x || current_top_right.y < candidate.y {
-            top_right_index = i;
+    for position in 2..sorted_indexes.len() {
+        let candidate = &nodes[sorted_indexes[position]];
+        if side_of_line(first, second, candidate) != PointLiesOnLineSide::OnLine {
+            return Some(position);
         }
+    }
 
-        let current_bottom_left = nodes[bottom_left_index];
-        if current_bottom_left.x > candidate.x || current_bottom_left.y > candidate.y {
-            bottom_left_index = i;
-        }
+    None
+}
 
-        let current_bottom_right = nodes[bottom_right_index];
-        if current_bottom_right.x < candidate.x || current_bottom_right.y > candidate.y {
-            bottom_right_index = i;
-        }
+//indexes of the nodes in a biased randomized insertion order along the curve of the order.
+pub fn curve_order(nodes: &[Point2], order: InsertionOrder) -> Vec<usize> {
+    let (mut min, mut max) = (Point2::new(::std::f64::MAX, ::std::f64::MAX),
+                              Point2::new(::std::f64::MIN, ::std::f64::MIN));
+    for p in nodes.iter() {
+        min = Point2::new(min.x.min(p.x), min.y.min(p.y));
+        max = Point2::new(max.x.max(p.x), max.y.max(p.y));
     }
 
-    (top_left_index, top_right_index, bottom_left_index, bottom_right_index)
+    let curve_indexes: Vec<u64> = nodes.iter()
+        .map(|p| {
+            let coordinates = [to_curve_coordinate(p.x, min.x, max.x),
+                               to_curve_coordinate(p.y, min.y, max.y)];
+            match order {
+                InsertionOrder::Morton => morton_index(&coordinates),
+                _ => hilbert_index(&coordinates),
+            }
+        })
+        .collect();
+
+    brio_order(&curve_indexes)
 }
 
 #[cfg(test)]
@@ -37,12 +56,15 @@ mod tests {
 
     #[test]
     fn test() {
-        let nodes = [Point2::new(5., 5.),
-                     Point2::new(0., 0.),
-                     Point2::new(0., 10.),
-                     Point2::new(10., 10.),
-                     Point2::new(10., 0.)];
+        let nodes = [Point2::new(0., 0.),
+                     Point2::new(1., 1.),
+                     Point2::new(2., 2.),
+                     Point2::new(3., 3.),
+                     Point2::new(2., 0.)];
 
-        assert_eq!((2, 3, 1, 4), find_corner_nodes(&nodes));
+        assert_eq!(Some(2), find_first_not_collinear(&nodes, &[0, 1, 4, 2, 3]));
+        assert_eq!(Some(4), find_first_not_collinear(&nodes, &[0, 1, 2, 3, 4]));
+        assert_eq!(None, find_first_not_collinear(&nodes, &[0, 1, 2, 3]));
+        assert_eq!(None, find_first_not_collinear(&nodes, &[0, 4]));
     }
 }
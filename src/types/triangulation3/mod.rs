@@ -1,9 +1,14 @@
 mod triangulation3;
 mod triangulation3_neighborhood;
-mod triangulation3_insertion;
 mod triangulation3_bw_insertion;
+mod triangulation3_flips;
+mod triangulation3_removal;
+mod triangulation3_plc;
+mod triangulation3_refinement;
+mod triangulation3_slivers;
 mod triangulation3_utilities;
-mod triangulation3_fake_nodes;
+#[cfg(feature = "parallel")]
+mod triangulation3_parallel;
 
 pub mod triangulation3_initiation;
 
@@ -2,12 +2,28 @@ use types::Point3;
 use types::Tetrahedron;
 use types::N3Index;
 use types::T4Index;
+use types::DelaunayError;
+use types::DuplicatePolicy;
+use types::InsertionAlgorithm;
+use types::Refinement3Options;
+use types::BuildOptions;
+use types::InsertionOrder;
+use types::DihedralHistogram;
+use types::SliverRemoval;
+use types::LocationStatistics;
 use algorithms3::element_locators::*;
-use super::triangulation3_insertion;
+use algorithms3::nearest_node::find_nearest_node;
+use algorithms3::nearest_node::find_k_nearest_nodes;
+use algorithms3::nearest_node::find_nodes_within_radius;
 use super::triangulation3_bw_insertion;
-use super::triangulation3_utilities::find_corner_nodes3;
-use super::triangulation3_initiation::create_initial_tetra_set;
-use super::triangulation3_fake_nodes::*;
+use super::triangulation3_flips;
+use super::triangulation3_removal;
+use super::triangulation3_plc;
+use super::triangulation3_refinement;
+use super::triangulation3_slivers;
+use super::triangulation3_utilities;
+#[cfg(feature = "parallel")]
+use super::triangulation3_parallel;
 
 use super::triangulation3_neighborhood::Triangulation3Neighborhood;
 
@@ -15,6 +31,10 @@ pub struct Triangulation3 {
     nodes: Vec<Point3>,
     elements: Vec<Tetrahedron>,
     last_added_element_index: T4Index,
+    duplicate_policy: DuplicatePolicy,
+    insertion_algorithm: InsertionAlgorithm,
+    //elements outside of the domain were deleted, so the border is not convex.
+    restricted: bool,
 }
 
 impl Triangulation3 {
@@ -26,6 +46,9 @@ impl Triangulation3 {
             nodes: nodes,
             elements: elements,
             last_added_element_index: T4Index(0),
+            duplicate_policy: DuplicatePolicy::default(),
+            insertion_algorithm: InsertionAlgorithm::default(),
+            restricted: false,
         };
 
         Triangulation3Neighborhood::teach_triangles_of_neighborhood(&mut tr.elements);
@@ -34,21 +57,38 @@ impl Triangulation3 {
 
     #[inline]
     pub fn new(nodes: &[Point3]) -> Triangulation3 {
-        println!("got here.");
-        let mut nodes = Vec::from(nodes);
-        let fake_indices = add_fake_nodes(&mut nodes);
-        let corner_nodes: [usize; 8] = find_corner_nodes3(&nodes);
-        let mut indices_except_corner: Vec<usize> = Vec::new();
+        match Triangulation3::try_new(nodes) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_new(nodes: &[Point3]) -> Result<Triangulation3, DelaunayError> {
+        Triangulation3::try_new_with_options(nodes, &BuildOptions::default())
+    }
 
-        for i in 0..nodes.len() {
-            if !corner_nodes.iter().any(|n| *n == i) {
-                indices_except_corner.push(i);
+    #[inline]
+    pub fn new_with_options(nodes: &[Point3], options: &BuildOptions) -> Triangulation3 {
+        match Triangulation3::try_new_with_options(nodes, options) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn try_new_with_options(nodes: &[Point3],
+                                options: &BuildOptions)
+                                -> Result<Triangulation3, DelaunayError> {
+        for (index, node) in nodes.iter().enumerate() {
+            if !node.x.is_finite() || !node.y.is_finite() || !node.z.is_finite() {
+                return Err(DelaunayError::InvalidCoordinates(index));
             }
         }
 
-        indices_except_corner.sort_by(|a, b| if nodes[*a].x < nodes[*b].x {
-                                          ::std::cmp::Ordering::Less
-                                      } else if nodes[*a].x > nodes[*b].x {
+        let mut sorted_indexes: Vec<usize> = (0..nodes.len()).collect();
+
+        sorted_indexes.sort_by(|a, b| if nodes[*a].x < nodes[*b].x {
+                                   ::std::cmp::Ordering::Less
+                               } else if nodes[*a].x > nodes[*b].x {
             ::std::cmp::Ordering::Greater
         } else {
             if nodes[*a].y < nodes[*b].y {
@@ -61,29 +101,109 @@ impl Triangulation3 {
                 } else if nodes[*a].z > nodes[*b].z {
                     ::std::cmp::Ordering::Greater
                 } else {
-                    panic!("Triangulation received equal nodes. node a: {:?}, node b: {:?}", nodes[*a], nodes[*b]);
+                    a.cmp(b)
                 }
             }
         });
 
+        for pair in sorted_indexes.windows(2) {
+            if nodes[pair[0]] == nodes[pair[1]] {
+                return Err(DelaunayError::DuplicateNodes(pair[0], pair[1]));
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            if options.threads > 1 {
+                if let Some(triangulation) =
+                    triangulation3_parallel::build(nodes, &sorted_indexes, options) {
+                    return Ok(triangulation);
+                }
+            }
+        }
+
+        let sorted_indexes = match options.insertion_order {
+            InsertionOrder::Lexicographic => sorted_indexes,
+            order => triangulation3_utilities::curve_order(nodes, order),
+        };
+
+        let (third_position, fourth_position) =
+            match triangulation3_utilities::find_first_not_coplanar(nodes, &sorted_indexes) {
+                Some(positions) => positions,
+                None => return Err(DelaunayError::CoplanarNodes),
+            };
+
         let nodes = Vec::from(nodes);
-        let mut eles = create_initial_tetra_set(&corner_nodes, &nodes);
+        let eles = vec![Tetrahedron::new(&nodes,
+                                         N3Index(sorted_indexes[0]),
+                                         N3Index(sorted_indexes[1]),
+                                         N3Index(sorted_indexes[third_position]),
+                                         N3Index(sorted_indexes[fourth_position]))];
 
-        Triangulation3Neighborhood::teach_triangles_of_neighborhood(&mut eles);
         let mut triangulation = Triangulation3 {
             elements: eles,
             last_added_element_index: T4Index(0),
+            duplicate_policy: DuplicatePolicy::default(),
+            insertion_algorithm: InsertionAlgorithm::default(),
+            restricted: false,
             nodes: nodes,
         };
 
-        for index in indices_except_corner.into_iter() {
-            println!("Invokking that...");
-            triangulation.insert_into_triangulation(N3Index(index));
+        //in lexicographic order every node is inserted outside of the current hull, as if the
+        //hull was connected to a node in infinity. this way no helper geometry has to be removed
+        //at the end. curve orders insert most nodes inside and keep the walks short.
+        for (position, index) in sorted_indexes.into_iter().enumerate() {
+            if position < 2 || position == third_position || position == fourth_position {
+                continue;
+            }
+
+            let location_result = triangulation.locate(&triangulation.nodes[index], None).0;
+            triangulation.insert_into_triangulation(N3Index(index), location_result)?;
         }
 
-        remove_fake_nodes(&mut triangulation, &fake_indices);
+        Ok(triangulation)
+    }
 
-        triangulation
+    //facets are triangles given by three node indexes, polygonal facets have to be split into
+    //triangles first.
+    #[inline]
+    pub fn from_plc(nodes: &[Point3], facets: &[[N3Index; 3]]) -> Triangulation3 {
+        match Triangulation3::try_from_plc(nodes, facets) {
+            Ok(triangulation) => triangulation,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //triangulation of the solid bounded by the closed surface made of the facets. the surface is
+    //recovered by inserting nodes on its edges and facets, which end up divided into edges and
+    //faces of the elements. elements outside of the surface are deleted, surfaces inside of the
+    //solid bound voids. later insertions outside of the solid are rejected.
+    pub fn try_from_plc(nodes: &[Point3],
+                        facets: &[[N3Index; 3]])
+                        -> Result<Triangulation3, DelaunayError> {
+        for facet in facets.iter() {
+            for i in 0..3 {
+                if facet[i].0 >= nodes.len() {
+                    return Err(DelaunayError::UnknownNode(facet[i].0));
+                }
+                if facet[i] == facet[(i + 1) % 3] {
+                    return Err(DelaunayError::DuplicateNodes(facet[i].0, facet[i].0));
+                }
+            }
+
+            let (a, b, c) = (nodes[facet[0].0], nodes[facet[1].0], nodes[facet[2].0]);
+            if (b - a).cross(c - a) == ::cgmath::Vector3::new(0., 0., 0.) {
+                return Err(DelaunayError::CollinearNodes);
+            }
+        }
+
+        let mut triangulation = Triangulation3::try_new(nodes)?;
+        let subfaces = triangulation3_plc::recover(&mut triangulation, facets)?;
+
+        triangulation3_plc::remove_outside_of_surface(&mut triangulation, &subfaces)?;
+        triangulation.last_added_element_index = T4Index(0);
+        triangulation.restricted = true;
+        Ok(triangulation)
     }
 
     #[inline]
@@ -107,47 +227,582 @@ impl Triangulation3 {
     }
 
     #[inline]
-    pub fn insert_node(&mut self, p: &Point3) {
+    pub fn is_restricted(&self) -> bool {
+        self.restricted
+    }
+
+    #[inline]
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    //applies to the nodes inserted after the call, nodes given to new are always rejected.
+    #[inline]
+    pub fn set_duplicate_policy(&mut self, duplicate_policy: DuplicatePolicy) {
+        self.duplicate_policy = duplicate_policy;
+    }
+
+    #[inline]
+    pub fn insertion_algorithm(&self) -> InsertionAlgorithm {
+        self.insertion_algorithm
+    }
+
+    //applies to the nodes inserted after the call, new always uses bowyer-watson.
+    #[inline]
+    pub fn set_insertion_algorithm(&mut self, insertion_algorithm: InsertionAlgorithm) {
+        self.insertion_algorithm = insertion_algorithm;
+    }
+
+    #[inline]
+    pub fn insert_node(&mut self, p: &Point3) -> N3Index {
+        match self.try_insert_node(p) {
+            Ok(node_index) => node_index,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //returns the index the node ended up as, which is an existing node when the duplicate
+    //policy merged them. on error the triangulation is left as it was before the call.
+    pub fn try_insert_node(&mut self, p: &Point3) -> Result<N3Index, DelaunayError> {
+        let new_node_index = N3Index(self.nodes.len());
+        if !p.x.is_finite() || !p.y.is_finite() || !p.z.is_finite() {
+            return Err(DelaunayError::InvalidCoordinates(new_node_index.0));
+        }
+
+        let location_result = self.locate(p, None).0;
+
+        if let Some(existing_node_index) = self.find_duplicate(&location_result, p) {
+            return match self.duplicate_policy {
+                DuplicatePolicy::Reject => {
+                    Err(DelaunayError::DuplicateNodes(existing_node_index.0, new_node_index.0))
+                }
+                DuplicatePolicy::Merge |
+                DuplicatePolicy::MergeWithin(_) => Ok(existing_node_index),
+            };
+        }
+
+        if let LocationResult::Outside(_, _) = location_result {
+            if self.restricted {
+                return Err(DelaunayError::OutsideOfDomain);
+            }
+        }
+
         self.nodes.push(*p);
-        let new_node_index = N3Index(self.nodes.len() - 1);
+        match self.insert_into_triangulation(new_node_index, location_result) {
+            Ok(()) => Ok(new_node_index),
+            Err(error) => {
+                self.nodes.pop();
+                Err(error)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove_node(&mut self, node_index: N3Index) {
+        if let Err(error) = self.try_remove_node(node_index) {
+            panic!("{}", error);
+        }
+    }
+
+    //removed node keeps its coordinates in nodes, so indexes of the other nodes do not change.
+    //elements are compacted and some of them get a new index. on error the triangulation is left
+    //as it was before the call.
+    pub fn try_remove_node(&mut self, node_index: N3Index) -> Result<(), DelaunayError> {
+        if node_index.0 >= self.nodes.len() {
+            return Err(DelaunayError::UnknownNode(node_index.0));
+        }
+
+        let ele_index = match self.locate(&self.nodes[node_index.0], None).0 {
+            LocationResult::OnVertex(ele_index, found) if found == node_index => ele_index,
+            _ => return Err(DelaunayError::UnknownNode(node_index.0)),
+        };
+
+        self.last_added_element_index =
+            triangulation3_removal::remove_node(self, ele_index, node_index, self.restricted)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn flip23(&mut self, ele_index: T4Index, face_index: usize) -> [T4Index; 3] {
+        match self.try_flip23(ele_index, face_index) {
+            Ok(new_elements) => new_elements,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //replaces the element and its neighbor across the face by three elements around the edge
+    //between their other nodes, which has to cross the face. elements are compacted, so indexes
+    //of other elements can change as well. on error the triangulation is left as it was.
+    pub fn try_flip23(&mut self,
+                      ele_index: T4Index,
+                      face_index: usize)
+                      -> Result<[T4Index; 3], DelaunayError> {
+        let flip = triangulation3_flips::flip23(self, ele_index, face_index)?;
+        self.last_added_element_index = flip.elements[0];
+        Ok([flip.elements[0], flip.elements[1], flip.elements[2]])
+    }
+
+    #[inline]
+    pub fn flip32(&mut self, ele_index: T4Index, edge: (N3Index, N3Index)) -> [T4Index; 2] {
+        match self.try_flip32(ele_index, edge) {
+            Ok(new_elements) => new_elements,
+            Err(error) => panic!("{}", error),
+        }
+    }
 
-        println!("new node index is: {:?} {:?} len now is:",
-                 new_node_index,
-                 self.nodes.len());
-        self.insert_into_triangulation(new_node_index);
+    //replaces the three elements around the edge of the element by two elements sharing the
+    //face between their other nodes, which the edge has to cross.
+    pub fn try_flip32(&mut self,
+                      ele_index: T4Index,
+                      edge: (N3Index, N3Index))
+                      -> Result<[T4Index; 2], DelaunayError> {
+        let flip = triangulation3_flips::flip32(self, ele_index, edge)?;
+        self.last_added_element_index = flip.elements[0];
+        Ok([flip.elements[0], flip.elements[1]])
     }
 
     #[inline]
-    fn insert_into_triangulation(&mut self, new_node_index: N3Index) {
-        let location_result = locate_element_containing(self.last_added_element_index,
-                                                        &self.elements,
-                                                        &self.nodes,
-                                                        &self.nodes[new_node_index.0]);
+    pub fn flip44(&mut self,
+                  ele_index: T4Index,
+                  edge: (N3Index, N3Index),
+                  new_edge: (N3Index, N3Index))
+                  -> [T4Index; 4] {
+        match self.try_flip44(ele_index, edge, new_edge) {
+            Ok(new_elements) => new_elements,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    //replaces the four elements around the edge of the element by four elements around the new
+    //edge, which joins two opposite nodes of the ring around the edge and crosses it in its
+    //plane.
+    pub fn try_flip44(&mut self,
+                      ele_index: T4Index,
+                      edge: (N3Index, N3Index),
+                      new_edge: (N3Index, N3Index))
+                      -> Result<[T4Index; 4], DelaunayError> {
+        let flip = triangulation3_flips::flip44(self, ele_index, edge, new_edge)?;
+        self.last_added_element_index = flip.elements[0];
+        Ok([flip.elements[0], flip.elements[1], flip.elements[2], flip.elements[3]])
+    }
+
+    //element containing the point, walking from the hint or from the last added element when
+    //there is none. the walk stops at a concave border of a restricted triangulation, then the
+    //element containing the point is searched among all of them, which is counted as scanned.
+    pub fn locate(&self,
+                  p: &Point3,
+                  hint: Option<T4Index>)
+                  -> (LocationResult, LocationStatistics) {
+        let start = match hint {
+            Some(hint) if hint.0 < self.elements.len() => hint,
+            _ => self.last_added_element_index,
+        };
+        let (location_result, visited) =
+            locate_counting_visited(start, &self.elements, &self.nodes, p);
+        let mut statistics = LocationStatistics {
+            visited: visited,
+            scanned: 0,
+        };
 
         match location_result {
-            LocationResult::InElement(ele_index) => {
-                self.last_added_element_index = ele_index;
-                triangulation3_bw_insertion::insert_into_element_bw(self,
-                                                                    ele_index,
-                                                                    new_node_index);
-                //let (t1_index, t2_index, t3_index, t4_index) =
-                //triangulation3_insertion::insert_into_element(self, ele_index, new_node_index);
-                //lawson_flipping::propagating_flip(self, new_node_index, t1_index);
-                //lawson_flipping::propagating_flip(self, new_node_index, t2_index);
-                //lawson_flipping::propagating_flip(self, new_node_index, t3_index);
-            }
-            LocationResult::OnFace(ele_index, _) => {
-                self.last_added_element_index = ele_index;
-                triangulation3_bw_insertion::insert_into_element_bw(self,
-                                                                    ele_index,
-                                                                    new_node_index);
-            }
-            LocationResult::OnFaces(ele_index, _, _) => {
-                self.last_added_element_index = ele_index;
-                triangulation3_bw_insertion::insert_into_element_bw(self,
-                                                                    ele_index,
-                                                                    new_node_index);
+            LocationResult::Outside(_, _) if self.restricted => {
+                let (location_result, scanned) = locate_by_scan(&self.elements, &self.nodes, p);
+                statistics.scanned = scanned;
+                (location_result, statistics)
+            }
+            _ => (location_result, statistics),
+        }
+    }
+
+    //node nearest to the point, searched over the elements by their distance to the point from
+    //the element containing it. the elements do not have to be delaunay, so flipped elements and
+    //recovered facets are fine.
+    pub fn nearest_node(&self, p: &Point3) -> N3Index {
+        match self.locate(p, None).0 {
+            LocationResult::OnVertex(_, node_index) => node_index,
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnFace(ele_index, _) |
+            LocationResult::OnEdge(ele_index, _, _) |
+            LocationResult::Outside(ele_index, _) => {
+                find_nearest_node(ele_index, &self.elements, &self.nodes, p, self.restricted)
+            }
+        }
+    }
+
+    //at most k nodes nearest to the point, nearest first.
+    pub fn k_nearest(&self, p: &Point3, k: usize) -> Vec<N3Index> {
+        let ele_index = self.locate_element(p);
+        find_k_nearest_nodes(ele_index, &self.elements, &self.nodes, p, k, self.restricted)
+    }
+
+    //nodes not further from the point than radius, nearest first.
+    pub fn nodes_within_radius(&self, p: &Point3, radius: f64) -> Vec<N3Index> {
+        let ele_index = self.locate_element(p);
+        find_nodes_within_radius(ele_index,
+                                 &self.elements,
+                                 &self.nodes,
+                                 p,
+                                 radius,
+                                 self.restricted)
+    }
+
+    //inserts circumcenters of elements whose circumradius is too large compared to their shortest
+    //edge, see Refinement3Options. border faces and the edges where the border bends are split
+    //instead of being encroached, so the border keeps its shape. returns the number of inserted
+    //nodes.
+    pub fn refine(&mut self, options: &Refinement3Options) -> usize {
+        let inserted = triangulation3_refinement::refine(self, options);
+        self.last_added_element_index = T4Index(0);
+        inserted
+    }
+
+    pub fn dihedral_histogram(&self) -> DihedralHistogram {
+        triangulation3_slivers::dihedral_histogram(self)
+    }
+
+    //flips slivers, elements with a smaller dihedral angle than options.min_dihedral_angle, to
+    //better elements. no nodes are inserted, so the node budget of the options does not apply.
+    pub fn remove_slivers(&mut self, options: &Refinement3Options) -> SliverRemoval {
+        let before = self.dihedral_histogram();
+        let flips = triangulation3_slivers::remove_slivers(self, options);
+        self.last_added_element_index = T4Index(0);
+
+        SliverRemoval {
+            flips: flips,
+            before: before,
+            after: self.dihedral_histogram(),
+        }
+    }
+
+    //element containing the point, touching it or nearest to it at the border.
+    fn locate_element(&self, p: &Point3) -> T4Index {
+        match self.locate(p, None).0 {
+            LocationResult::OnVertex(ele_index, _) |
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnFace(ele_index, _) |
+            LocationResult::OnEdge(ele_index, _, _) |
+            LocationResult::Outside(ele_index, _) => ele_index,
+        }
+    }
+
+    //existing node which the point at the location should be merged with or rejected as.
+    fn find_duplicate(&self, location_result: &LocationResult, p: &Point3) -> Option<N3Index> {
+        let ele_index = match *location_result {
+            LocationResult::OnVertex(_, existing_node_index) => return Some(existing_node_index),
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnFace(ele_index, _) |
+            LocationResult::OnEdge(ele_index, _, _) |
+            LocationResult::Outside(ele_index, _) => ele_index,
+        };
+
+        if let DuplicatePolicy::MergeWithin(tolerance) = self.duplicate_policy {
+            return find_nodes_within_radius(ele_index,
+                                            &self.elements,
+                                            &self.nodes,
+                                            p,
+                                            tolerance,
+                                            self.restricted)
+                .first()
+                .cloned();
+        }
+
+        None
+    }
+
+    #[inline]
+    fn insert_into_triangulation(&mut self,
+                                 new_node_index: N3Index,
+                                 location_result: LocationResult)
+                                 -> Result<(), DelaunayError> {
+        self.last_added_element_index = match location_result {
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnFace(ele_index, _) |
+            LocationResult::OnEdge(ele_index, _, _) => {
+                match self.insertion_algorithm {
+                    InsertionAlgorithm::BowyerWatson => {
+                        triangulation3_bw_insertion::insert_into_element_bw(self,
+                                                                            ele_index,
+                                                                            new_node_index)
+                    }
+                    InsertionAlgorithm::Flipping => {
+                        triangulation3_flips::insert_into_element_by_flips(self,
+                                                                           ele_index,
+                                                                           new_node_index)
+                    }
+                }
+            }
+            LocationResult::Outside(ele_index, face_index) => {
+                match self.insertion_algorithm {
+                    InsertionAlgorithm::BowyerWatson => {
+                        triangulation3_bw_insertion::insert_outside_bw(self,
+                                                                       ele_index,
+                                                                       face_index,
+                                                                       new_node_index)
+                    }
+                    InsertionAlgorithm::Flipping => {
+                        triangulation3_flips::insert_outside_by_flips(self,
+                                                                      ele_index,
+                                                                      face_index,
+                                                                      new_node_index)
+                    }
+                }
+            }
+            LocationResult::OnVertex(_, existing_node_index) => {
+                return Err(DelaunayError::DuplicateNodes(existing_node_index.0,
+                                                         new_node_index.0));
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use algorithms3::element_locators::LocationResult;
+    use math::side_of_plane;
+    use math::SideOfPlane;
+    use cgmath::InnerSpace;
+    use types::triangulation3_test_utils::random_points;
+    use types::triangulation3_test_utils::sorted_elements;
+
+    #[test]
+    fn testing_creation_covers_convex_hull() {
+        //node 8 lies on the side of the cube, node 9 is outside of it.
+        let mut nodes = triangulation3_test_utils::get_example_initial_point_set();
+        nodes.push(Point3::new(0.4, 0., 0.5));
+        nodes.push(Point3::new(2., 0.5, 0.5));
+
+        let triangulation = Triangulation3::new(&nodes);
+
+        let mut volume = 0.;
+        for (index, ele) in triangulation.elements().iter().enumerate() {
+            let (a, b, c, d) = (ele.a(&nodes), ele.b(&nodes), ele.c(&nodes), ele.d(&nodes));
+            volume += (b - a).cross(c - a).dot(d - a).abs() / 6.;
+
+            for (face_index, face) in ele.faces_as_points_tuples(&nodes).iter().enumerate() {
+                match ele.get_neighbor_from_index(face_index) {
+                    Some(neighbor) => {
+                        let (n1, n2, n3) = ele.faces_as_indices_tuples()[face_index];
+                        assert_eq!(Some(T4Index(index)),
+                                   triangulation.elements()[neighbor.0]
+                                       .get_neighbor_for_indices(n1, n2, n3));
+                    }
+                    None => {
+                        for p in nodes.iter() {
+                            assert!(side_of_plane(face.0, face.1, face.2, p) !=
+                                    SideOfPlane::Left);
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!((volume - 4. / 3.).abs() < 1e-10);
+        for node_index in 0..nodes.len() {
+            assert!(triangulation.elements()
+                .iter()
+                .any(|e| e.nodes().iter().any(|n| n.0 == node_index)));
+        }
+    }
+
+    #[test]
+    fn testing_grid_does_not_depend_on_insertion_order() {
+        //every tetra of a regular grid has more nodes on its circumsphere.
+        let mut grid = Vec::new();
+        for i in 0..27 {
+            let position = (i * 7) % 27;
+            grid.push(Point3::new((position % 3) as f64,
+                                  ((position / 3) % 3) as f64,
+                                  (position / 9) as f64));
+        }
+
+        let all_at_once = Triangulation3::new(&grid);
+
+        let mut one_by_one = Triangulation3::new(&grid[..6]);
+        for node in grid[6..].iter() {
+            one_by_one.insert_node(node);
+        }
+
+        assert_eq!(sorted_elements(&all_at_once), sorted_elements(&one_by_one));
+
+        let mut volume = 0.;
+        for ele in all_at_once.elements() {
+            let (a, b, c, d) = (ele.a(&grid), ele.b(&grid), ele.c(&grid), ele.d(&grid));
+            assert_eq!(SideOfPlane::Right, side_of_plane(a, b, c, d));
+            volume += (b - a).cross(c - a).dot(d - a).abs() / 6.;
+        }
+
+        assert!((volume - 8.).abs() < 1e-10);
+    }
+
+    #[test]
+    fn testing_curve_insertion_orders() {
+        let nodes = random_points(5, 500);
+
+        let lexicographic = Triangulation3::new(&nodes);
+        for &insertion_order in [InsertionOrder::Hilbert, InsertionOrder::Morton].iter() {
+            let options = BuildOptions {
+                insertion_order: insertion_order,
+                ..BuildOptions::default()
+            };
+            let triangulation = Triangulation3::new_with_options(&nodes, &options);
+
+            assert_eq!(sorted_elements(&lexicographic), sorted_elements(&triangulation));
+        }
+    }
+
+    #[test]
+    fn testing_errors_on_invalid_input() {
+        let nodes = triangulation3_test_utils::get_example_initial_point_set();
+
+        let mut with_duplicate = nodes.clone();
+        with_duplicate.push(nodes[5]);
+        match Triangulation3::try_new(&with_duplicate) {
+            Err(DelaunayError::DuplicateNodes(5, 8)) => (),
+            other => panic!("expected duplicate nodes, got {:?}", other.err()),
+        }
+
+        let mut with_nan = nodes.clone();
+        with_nan[2].z = ::std::f64::NAN;
+        match Triangulation3::try_new(&with_nan) {
+            Err(DelaunayError::InvalidCoordinates(2)) => (),
+            other => panic!("expected invalid coordinates, got {:?}", other.err()),
+        }
+
+        match Triangulation3::try_new(&nodes[..4]) {
+            Err(DelaunayError::CoplanarNodes) => (),
+            other => panic!("expected coplanar nodes, got {:?}", other.err()),
+        }
+
+        let mut triangulation = Triangulation3::try_new(&nodes).unwrap();
+        let elements = triangulation.elements().clone();
+
+        match triangulation.try_insert_node(&nodes[6]) {
+            Err(DelaunayError::DuplicateNodes(6, 8)) => (),
+            other => panic!("expected duplicate nodes, got {:?}", other),
+        }
+        match triangulation.try_insert_node(&Point3::new(0.5, 0.5, ::std::f64::NAN)) {
+            Err(DelaunayError::InvalidCoordinates(8)) => (),
+            other => panic!("expected invalid coordinates, got {:?}", other),
+        }
+
+        assert_eq!(8, triangulation.nodes().len());
+        assert_eq!(elements, *triangulation.elements());
+
+        assert_eq!(N3Index(8),
+                   triangulation.try_insert_node(&Point3::new(0.5, 0.5, 0.4)).unwrap());
+    }
+
+    #[test]
+    fn testing_duplicate_policies() {
+        let nodes = triangulation3_test_utils::get_example_initial_point_set();
+        let mut triangulation = Triangulation3::new(&nodes);
+        assert_eq!(DuplicatePolicy::Reject, triangulation.duplicate_policy());
+
+        triangulation.set_duplicate_policy(DuplicatePolicy::Merge);
+        assert_eq!(N3Index(6), triangulation.insert_node(&nodes[6]));
+        assert_eq!(N3Index(8), triangulation.insert_node(&Point3::new(0.5, 0.5, 0.4)));
+        assert_eq!(N3Index(9), triangulation.insert_node(&Point3::new(0.5, 0.505, 0.4)));
+
+        triangulation.set_duplicate_policy(DuplicatePolicy::MergeWithin(0.01));
+        assert_eq!(N3Index(9), triangulation.insert_node(&Point3::new(0.5, 0.51, 0.4)));
+        assert_eq!(N3Index(8), triangulation.insert_node(&Point3::new(0.5, 0.5, 0.399)));
+        assert_eq!(N3Index(6),
+                   triangulation.insert_node(&Point3::new(1.005, 1.005, 1.005)));
+        assert_eq!(N3Index(10), triangulation.insert_node(&Point3::new(0.5, 0.5, 0.8)));
+
+        assert_eq!(11, triangulation.nodes().len());
+        for ele in triangulation.elements() {
+            let pts = triangulation.nodes();
+            assert_eq!(SideOfPlane::Right,
+                       side_of_plane(ele.a(pts), ele.b(pts), ele.c(pts), ele.d(pts)));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn testing_creation_from_coplanar_nodes() {
+        Triangulation3::new(&[Point3::new(0., 0., 0.),
+                              Point3::new(1., 0., 0.),
+                              Point3::new(0., 1., 0.),
+                              Point3::new(1., 1., 0.)]);
+    }
+
+    #[test]
+    fn testing_nearest_nodes() {
+        let nodes = random_points(40, 200);
+        let triangulation = Triangulation3::new(&nodes);
+        let distance = |p: &Point3, n: &N3Index| ::math::distance3_squared(p, &nodes[n.0]);
+
+        let mut points: Vec<Point3> = random_points(41, 50)
+            .iter()
+            .map(|q| Point3::new(3. * q.x - 1., 3. * q.y - 1., 3. * q.z - 1.))
+            .collect();
+        points.push(nodes[42]);
+        for p in points.iter() {
+            let mut brute_force: Vec<N3Index> = (0..nodes.len()).map(N3Index).collect();
+            brute_force.sort_by(|a, b| distance(p, a).partial_cmp(&distance(p, b)).unwrap());
+            assert_eq!(brute_force[0], triangulation.nearest_node(p));
+            assert_eq!(brute_force[..6].to_vec(), triangulation.k_nearest(p, 6));
+
+            let radius = 0.5 * (distance(p, &brute_force[10]).sqrt() +
+                                distance(p, &brute_force[11]).sqrt());
+            assert_eq!(brute_force[..11].to_vec(), triangulation.nodes_within_radius(p, radius));
+        }
+        assert_eq!(N3Index(42), triangulation.nearest_node(&nodes[42]));
+    }
+
+    #[test]
+    fn testing_locate_with_hint() {
+        let mut nodes = Vec::new();
+        for i in 0..216 {
+            nodes.push(Point3::new((i % 6) as f64 + ((i * 7) % 11) as f64 / 64.,
+                                   ((i / 6) % 6) as f64 + ((i * 3) % 13) as f64 / 64.,
+                                   (i / 36) as f64 + ((i * 5) % 17) as f64 / 64.));
+        }
+        let triangulation = Triangulation3::new(&nodes);
+
+        let p = Point3::new(4.3, 3.6, 4.2);
+        let (location_result, statistics) = triangulation.locate(&p, Some(T4Index(0)));
+        let ele_index = match location_result {
+            LocationResult::InElement(ele_index) => ele_index,
+            other => panic!("expected element, got {:?}", other),
+        };
+        assert!(statistics.visited > 1);
+        assert_eq!(0, statistics.scanned);
+        let one_element = LocationStatistics {
+            visited: 1,
+            scanned: 0,
+        };
+        assert_eq!((LocationResult::InElement(ele_index), one_element),
+                   triangulation.locate(&p, Some(ele_index)));
+
+        match triangulation.locate(&nodes[42], Some(ele_index)).0 {
+            LocationResult::OnVertex(_, N3Index(42)) => (),
+            other => panic!("expected node 42, got {:?}", other),
+        }
+
+        //coordinates are multiples of 1/64, so the middle of an edge lies exactly on it.
+        let ele = &triangulation.elements()[ele_index.0];
+        let (n1, n2) = if ele.index_a() < ele.index_b() {
+            (ele.index_a(), ele.index_b())
+        } else {
+            (ele.index_b(), ele.index_a())
+        };
+        let middle = Point3::new((nodes[n1.0].x + nodes[n2.0].x) / 2.,
+                                 (nodes[n1.0].y + nodes[n2.0].y) / 2.,
+                                 (nodes[n1.0].z + nodes[n2.0].z) / 2.);
+        assert_eq!((LocationResult::OnEdge(ele_index, n1, n2), one_element),
+                   triangulation.locate(&middle, Some(ele_index)));
+
+        match triangulation.locate(&Point3::new(-5., 2., 3.), None).0 {
+            LocationResult::Outside(found, face_index) => {
+                let ele = &triangulation.elements()[found.0];
+                assert_eq!(None, ele.get_neighbor_from_index(face_index));
             }
+            other => panic!("expected outside, got {:?}", other),
         }
     }
 }
@@ -4,29 +4,82 @@ use types::N3Index;
 use types::Tetrahedron;
 use types::Point3;
 use types::triangulation3::triangulation3_neighborhood::Triangulation3Neighborhood;
-use math::SphereSide;
+use math::side_of_plane;
+use math::SideOfPlane;
 use algorithms3::sort_3::sort_3;
+use cgmath::InnerSpace;
 
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
+//node closer to the plane of a border face than this part of its longest edge lies on the face.
+const FLATNESS: f64 = 1e-10;
+
+//elements whose circumsphere contains the new node together with the border faces which see it.
+//border faces play the role of tetrahedra connected to a node in infinity, so the same
+//cavity search handles nodes inserted inside and outside of the triangulation. in a delaunay
+//triangulation the new node sees every face of the cavity, so it can be filled directly.
+struct Cavity {
+    elements: Vec<T4Index>,
+    border_faces: BTreeMap<(N3Index, N3Index, N3Index), (T4Index, usize)>,
+}
+
 pub fn insert_into_element_bw(triangulation: &mut Triangulation3,
                               element_index: T4Index,
-                              new_node_index: N3Index) {
-    let elements_to_remove = find(triangulation,
-                                  element_index,
-                                  &triangulation.nodes()[new_node_index.0]);
-    let faces_with_neighbors = select_faces_which_exist_only_once(triangulation,
-                                                                  &elements_to_remove);
+                              new_node_index: N3Index)
+                              -> T4Index {
+    let cavity = find(triangulation, element_index, new_node_index);
+    replace_cavity(triangulation, cavity, new_node_index)
+}
 
-//    println!("faces_with_neighbors.len() {:?}, elements_to_remove.len() {:?}", faces_with_neighbors.len(),elements_to_remove.len());
-//    assert!(faces_with_neighbors.len() >= elements_to_remove.len());
+pub fn insert_outside_bw(triangulation: &mut Triangulation3,
+                         element_index: T4Index,
+                         face_index: usize,
+                         new_node_index: N3Index)
+                         -> T4Index {
+    let cavity = find_from_border_face(triangulation, element_index, face_index, new_node_index);
+    replace_cavity(triangulation, cavity, new_node_index)
+}
+
+//inserts a node lying on the border face, which splits the face. the node may be rounded off the
+//plane a bit, so the border is kept fixed like in a restricted triangulation.
+pub fn insert_on_border_bw(triangulation: &mut Triangulation3,
+                           element_index: T4Index,
+                           face_index: usize,
+                           new_node_index: N3Index)
+                           -> T4Index {
+    let mut search = CavitySearch::new(true);
+    search.add_element(element_index);
+    search.add_border_face(triangulation, element_index, face_index);
+    let mut cavity = search.grow(triangulation, new_node_index);
+    cavity.shrink_to_star(triangulation, new_node_index);
+    replace_cavity(triangulation, cavity, new_node_index)
+}
+
+fn replace_cavity(triangulation: &mut Triangulation3,
+                  cavity: Cavity,
+                  new_node_index: N3Index)
+                  -> T4Index {
+    let mut faces_with_neighbors = select_faces_which_exist_only_once(triangulation,
+                                                                      &cavity.elements);
+
+    //border faces seeing the new node are inside of the cavity, they get no tetra.
+    faces_with_neighbors.retain(|&(face, neighbor)| {
+        neighbor.is_some() || !cavity.border_faces.contains_key(&face)
+    });
+
+    let cavity_elements: BTreeSet<T4Index> = cavity.elements.iter().cloned().collect();
+    for (face, &(element_index, _)) in cavity.border_faces.iter() {
+        if !cavity_elements.contains(&element_index) {
+            faces_with_neighbors.push((*face, Some(element_index)));
+        }
+    }
 
     let mut new_tetras = Vec::new();
     let mut tetras_which_have_to_be_teached = Vec::new();
 
-    for (index, &((n1, n2, n3), neighbor)) in faces_with_neighbors.iter().enumerate() {
-        let mut new_tetra = Tetrahedron::new(triangulation.nodes(), n1, n2, n3, new_node_index);
+    for &((n1, n2, n3), neighbor) in faces_with_neighbors.iter() {
+        let new_tetra = Tetrahedron::new(triangulation.nodes(), n1, n2, n3, new_node_index);
 
         if let Some(neighbor) = neighbor {
             tetras_which_have_to_be_teached.push(neighbor);
@@ -35,64 +88,350 @@ pub fn insert_into_element_bw(triangulation: &mut Triangulation3,
         new_tetras.push(new_tetra);
     }
 
-    let mut index = 0;
-    for original_element_index in elements_to_remove {
-        triangulation.elements_mut()[original_element_index.0] = new_tetras[index].clone();
-        index += 1;
+    let mut new_tetras = new_tetras.into_iter();
+    let mut elements_left_empty = Vec::new();
 
-        if index >= faces_with_neighbors.len() {
-            break;
+    for original_element_index in cavity.elements {
+        if let Some(new_tetra) = new_tetras.next() {
+            triangulation.elements_mut()[original_element_index.0] = new_tetra;
+            tetras_which_have_to_be_teached.push(original_element_index);
+        } else {
+            elements_left_empty.push(original_element_index);
         }
-
-        tetras_which_have_to_be_teached.push(original_element_index);
     }
 
-    for index in index..new_tetras.len() {
+    for new_tetra in new_tetras {
         tetras_which_have_to_be_teached.push(T4Index(triangulation.elements().len()));
-        triangulation.elements_mut().push(new_tetras[index].clone());
+        triangulation.elements_mut().push(new_tetra);
     }
 
     Triangulation3Neighborhood::teach_selected_elements_of_neighborhood(&tetras_which_have_to_be_teached, triangulation.elements_mut());
+
+    let last_new_tetra = *tetras_which_have_to_be_teached.last()
+        .expect("Insertion did not create any element.");
+
+    //cavity can hold more elements than the star which replaces it.
+    remove_elements(triangulation, elements_left_empty, last_new_tetra)
 }
 
-fn find(tr: &Triangulation3, starting_element: T4Index, node: &Point3) -> Vec<T4Index> {
-    assert!(tr.elements()[starting_element.0].is_point_in_circumsphere(node, tr.nodes()));
+//removes elements by moving the last elements into their places. returns the new index of
+//the tracked element.
+fn remove_elements(tr: &mut Triangulation3,
+                   mut indices: Vec<T4Index>,
+                   mut tracked: T4Index)
+                   -> T4Index {
+    indices.sort();
 
-    let mut checked_elements = BTreeSet::new();
-    let mut elements_to_check = Vec::new();
-    let mut elements_containing_point_in_circum = Vec::new();
+    for index in indices.into_iter().rev() {
+        let last_index = T4Index(tr.elements().len() - 1);
+        tr.elements_mut().swap_remove(index.0);
 
-    checked_elements.insert(starting_element);
-    elements_to_check.push(starting_element);
-    elements_containing_point_in_circum.push(starting_element);
+        if index == last_index {
+            continue;
+        }
 
-    loop {
-        let ele_index = elements_to_check.pop().expect("Failed to pop elements_to_check");
-        let ele: &Tetrahedron = &tr.elements()[ele_index.0];
-        for n in ele.neighbors().iter() {
-            if let Some(n_index) = *n {
-                if checked_elements.contains(&n_index) {
-                    continue;
+        if tracked == last_index {
+            tracked = index;
+        }
+
+        let moved_neighbors = *tr.elements()[index.0].neighbors();
+        for neighbor in moved_neighbors.iter() {
+            if let Some(neighbor) = *neighbor {
+                let neighbor: &mut Tetrahedron = &mut tr.elements_mut()[neighbor.0];
+                for face_index in 0..4 {
+                    if neighbor.get_neighbor_from_index(face_index) == Some(last_index) {
+                        neighbor.set_neighbor(face_index, Some(index));
+                    }
                 }
+            }
+        }
+    }
+
+    tracked
+}
+
+fn find(tr: &Triangulation3, starting_element: T4Index, node: N3Index) -> Cavity {
+    assert!(tr.elements()[starting_element.0].is_node_in_circumsphere(node, tr.nodes()));
 
-                checked_elements.insert(n_index);
+    let mut search = CavitySearch::new(tr.is_restricted());
+    search.add_element(starting_element);
+    let mut cavity = search.grow(tr, node);
+    cavity.shrink_to_star(tr, node);
+    cavity
+}
 
-                let neighbor: &Tetrahedron = &tr.elements()[n_index.0];
+fn find_from_border_face(tr: &Triangulation3,
+                         starting_element: T4Index,
+                         starting_face: usize,
+                         node: N3Index)
+                         -> Cavity {
+    let mut search = CavitySearch::new(tr.is_restricted());
+    search.add_border_face(tr, starting_element, starting_face);
+    search.grow(tr, node)
+}
 
-                if neighbor.is_point_in_circumsphere(node, tr.nodes()) {
-                    elements_to_check.push(n_index);
-                    elements_containing_point_in_circum.push(n_index);
+impl Cavity {
+    //the predicates are exact, but a restricted triangulation is not delaunay: recovered facets
+    //and border splits keep elements whose circumsphere holds nodes across the border. such
+    //elements can have the node in their circumsphere without seeing it, so they are left out
+    //until every face of the cavity sees the node. the first element contains the node and
+    //always stays. nodes on the planes of concave faces need it, see the plc tests.
+    fn shrink_to_star(&mut self, tr: &Triangulation3, node: N3Index) {
+        loop {
+            let hidden = {
+                let in_cavity: BTreeSet<T4Index> = self.elements.iter().cloned().collect();
+                let border_faces = &self.border_faces;
+
+                self.elements.iter().skip(1).position(|ele_index| {
+                    let ele: &Tetrahedron = &tr.elements()[ele_index.0];
+                    let faces = ele.faces_as_points_tuples(tr.nodes());
+
+                    (0..4).any(|face_index| {
+                        let is_cavity_face = match ele.get_neighbor_from_index(face_index) {
+                            Some(neighbor_index) => !in_cavity.contains(&neighbor_index),
+                            None => {
+                                !border_faces.contains_key(&sorted_face(tr, *ele_index, face_index))
+                            }
+                        };
+                        let face = faces[face_index];
+
+                        is_cavity_face &&
+                        side_of_plane(face.0, face.1, face.2, &tr.nodes()[node.0]) !=
+                        SideOfPlane::Right
+                    })
+                })
+            };
+
+            match hidden {
+                Some(position) => {
+                    self.elements.remove(position + 1);
+                    self.keep_connected_to_first(tr);
                 }
+                None => break,
             }
         }
+    }
+
+    fn keep_connected_to_first(&mut self, tr: &Triangulation3) {
+        let in_cavity: BTreeSet<T4Index> = self.elements.iter().cloned().collect();
+        let mut connected = vec![self.elements[0]];
+        let mut position = 0;
 
+        while position < connected.len() {
+            let ele: &Tetrahedron = &tr.elements()[connected[position].0];
+            position += 1;
 
-        if elements_to_check.len() == 0 {
-            break;
+            for neighbor_index in ele.neighbors().iter().filter_map(|n| *n) {
+                if in_cavity.contains(&neighbor_index) && !connected.contains(&neighbor_index) {
+                    connected.push(neighbor_index);
+                }
+            }
         }
+
+        self.elements.retain(|ele_index| connected.contains(ele_index));
+        self.border_faces.retain(|_, &mut (face_ele_index, _)| {
+            connected.contains(&face_ele_index)
+        });
     }
+}
 
-    elements_containing_point_in_circum
+struct CavitySearch {
+    checked_elements: BTreeSet<T4Index>,
+    checked_faces: BTreeSet<(N3Index, N3Index, N3Index)>,
+    elements_to_check: Vec<T4Index>,
+    faces_to_check: Vec<(T4Index, usize)>,
+    cavity: Cavity,
+    //border of a restricted triangulation bounds the domain, it is not a hull to extend. border
+    //faces join the cavity only when the node nearly lies on them, instead of getting a flat
+    //tetra. the plane of a concave face runs through the domain, so lying on the plane is enough
+    //only next to a border face which already sees the node.
+    fixed_border: bool,
+}
+
+impl CavitySearch {
+    fn new(fixed_border: bool) -> CavitySearch {
+        CavitySearch {
+            checked_elements: BTreeSet::new(),
+            checked_faces: BTreeSet::new(),
+            elements_to_check: Vec::new(),
+            faces_to_check: Vec::new(),
+            cavity: Cavity {
+                elements: Vec::new(),
+                border_faces: BTreeMap::new(),
+            },
+            fixed_border: fixed_border,
+        }
+    }
+
+    fn add_element(&mut self, element_index: T4Index) {
+        self.checked_elements.insert(element_index);
+        self.elements_to_check.push(element_index);
+        self.cavity.elements.push(element_index);
+    }
+
+    fn add_border_face(&mut self, tr: &Triangulation3, element_index: T4Index, face_index: usize) {
+        let key = sorted_face(tr, element_index, face_index);
+        self.checked_faces.insert(key);
+        self.faces_to_check.push((element_index, face_index));
+        self.cavity.border_faces.insert(key, (element_index, face_index));
+    }
+
+    fn check_element(&mut self, tr: &Triangulation3, element_index: T4Index, node: N3Index) {
+        if self.checked_elements.contains(&element_index) {
+            return;
+        }
+
+        self.checked_elements.insert(element_index);
+
+        if tr.elements()[element_index.0].is_node_in_circumsphere(node, tr.nodes()) {
+            self.add_element(element_index);
+        }
+    }
+
+    fn check_border_face(&mut self,
+                         tr: &Triangulation3,
+                         element_index: T4Index,
+                         face_index: usize,
+                         node: N3Index,
+                         next_to_seen_face: bool) {
+        let key = sorted_face(tr, element_index, face_index);
+        if self.checked_faces.contains(&key) {
+            return;
+        }
+
+        let sees = border_face_sees_point(tr,
+                                          element_index,
+                                          face_index,
+                                          node,
+                                          self.fixed_border,
+                                          next_to_seen_face);
+
+        //fixed face rejected from an element may still be seen across the edge of a seen face.
+        if sees || !self.fixed_border || next_to_seen_face {
+            self.checked_faces.insert(key);
+        }
+
+        if sees {
+            self.faces_to_check.push((element_index, face_index));
+            self.cavity.border_faces.insert(key, (element_index, face_index));
+        }
+    }
+
+    fn grow(mut self, tr: &Triangulation3, node: N3Index) -> Cavity {
+        loop {
+            if let Some(ele_index) = self.elements_to_check.pop() {
+                let ele: &Tetrahedron = &tr.elements()[ele_index.0];
+                for (face_index, n) in ele.neighbors().iter().enumerate() {
+                    match *n {
+                        Some(n_index) => self.check_element(tr, n_index, node),
+                        None => self.check_border_face(tr, ele_index, face_index, node, false),
+                    }
+                }
+            } else if let Some((ele_index, face_index)) = self.faces_to_check.pop() {
+                self.check_element(tr, ele_index, node);
+
+                let face = tr.elements()[ele_index.0].faces_as_indices_tuples()[face_index];
+                for &(n1, n2) in [(face.0, face.1), (face.1, face.2), (face.2, face.0)].iter() {
+                    let (other_ele_index, other_face_index) =
+                        find_border_face_across_edge(tr, ele_index, face_index, n1, n2);
+                    self.check_border_face(tr, other_ele_index, other_face_index, node, true);
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.cavity
+    }
+}
+
+//a border face sees the point when the point lies in front of it. a point lying on its plane
+//is seen when it is inside of the circumsphere, otherwise a flat tetra would be created.
+fn border_face_sees_point(tr: &Triangulation3,
+                          element_index: T4Index,
+                          face_index: usize,
+                          node: N3Index,
+                          fixed_border: bool,
+                          next_to_seen_face: bool)
+                          -> bool {
+    let ele: &Tetrahedron = &tr.elements()[element_index.0];
+    let face = ele.faces_as_points_tuples(tr.nodes())[face_index];
+
+    if fixed_border {
+        let p = &tr.nodes()[node.0];
+        let is_on_face = is_nearly_on_plane(face.0, face.1, face.2, p) &&
+                         (next_to_seen_face || is_inside_of_face(face.0, face.1, face.2, p));
+        return is_on_face && ele.is_node_in_circumsphere(node, tr.nodes());
+    }
+
+    match side_of_plane(face.0, face.1, face.2, &tr.nodes()[node.0]) {
+        SideOfPlane::Left => true,
+        SideOfPlane::OnPlane => ele.is_node_in_circumsphere(node, tr.nodes()),
+        SideOfPlane::Right => false,
+    }
+}
+
+#[inline]
+fn is_nearly_on_plane(a: &Point3, b: &Point3, c: &Point3, p: &Point3) -> bool {
+    let normal = (b - a).cross(c - a);
+    let longest = (b - a).magnitude2().max((c - b).magnitude2()).max((a - c).magnitude2());
+    let distance = normal.dot(p - a) / normal.magnitude();
+
+    distance * distance <= FLATNESS * FLATNESS * longest
+}
+
+//projection of the point lies inside of the face, its edges get the same tolerance as the plane.
+fn is_inside_of_face(a: &Point3, b: &Point3, c: &Point3, p: &Point3) -> bool {
+    let normal = (b - a).cross(c - a);
+    let tolerance = -FLATNESS * normal.magnitude2();
+
+    [(a, b), (b, c), (c, a)]
+        .iter()
+        .all(|&(from, to)| (to - from).cross(p - from).dot(normal) >= tolerance)
+}
+
+//rotates around the border edge n1-n2 until the other border face containing it is found.
+pub fn find_border_face_across_edge(tr: &Triangulation3,
+                                element_index: T4Index,
+                                face_index: usize,
+                                n1: N3Index,
+                                n2: N3Index)
+                                -> (T4Index, usize) {
+    let mut ele_index = element_index;
+    let mut entered_through = face_index;
+
+    loop {
+        let ele: &Tetrahedron = &tr.elements()[ele_index.0];
+        let faces = ele.faces_as_indices_tuples();
+
+        let next_face = (0..4)
+            .find(|&f| f != entered_through && face_contains(faces[f], n1, n2))
+            .expect("Element does not contain the rotation edge.");
+
+        match ele.get_neighbor_from_index(next_face) {
+            None => return (ele_index, next_face),
+            Some(neighbor_index) => {
+                let (f1, f2, f3) = faces[next_face];
+                entered_through = tr.elements()[neighbor_index.0].get_neighbor_index(f1, f2, f3);
+                ele_index = neighbor_index;
+            }
+        }
+    }
+}
+
+#[inline]
+fn face_contains(face: (N3Index, N3Index, N3Index), n1: N3Index, n2: N3Index) -> bool {
+    let contains = |n| face.0 == n || face.1 == n || face.2 == n;
+    contains(n1) && contains(n2)
+}
+
+#[inline]
+fn sorted_face(tr: &Triangulation3,
+               element_index: T4Index,
+               face_index: usize)
+               -> (N3Index, N3Index, N3Index) {
+    let face = tr.elements()[element_index.0].faces_as_indices_tuples()[face_index];
+    sort_3(face.0, face.1, face.2)
 }
 
 fn select_faces_which_exist_only_once(tr: &Triangulation3,
@@ -128,13 +467,14 @@ mod bw_insertion {
     fn testing_find_using_example_set() {
         let example_set = get_example_initial_point_set();
         let example_tr = create_initial_tetra_set(&[0, 1, 2, 3, 4, 5, 6, 7], &example_set);
-        let tr = Triangulation3::new_from_prebuilt_triangulation(example_set.clone(),
-                                                                 example_tr.clone());
 
         for (index, tetra) in example_tr.iter().enumerate() {
             let tetra: &Tetrahedron = tetra;
-            let center = tetra.create_center_point(&example_set);
-            assert_eq!(5, find(&tr, T4Index(index), &center).len());
+            let mut nodes = example_set.clone();
+            nodes.push(tetra.create_center_point(&example_set));
+
+            let tr = Triangulation3::new_from_prebuilt_triangulation(nodes, example_tr.clone());
+            assert_eq!(5, find(&tr, T4Index(index), N3Index(8)).elements.len());
         }
     }
 
@@ -145,7 +485,9 @@ mod bw_insertion {
                          Point3::new(1., 0., 0.),
                          Point3::new(0., 1., 0.),
                          Point3::new(0.3, 0.3, 2.),
-                         Point3::new(0.3, 0.3, 1.)];
+                         Point3::new(0.3, 0.3, 1.),
+                         Point3::new(0.3, 0.3, 0.5),
+                         Point3::new(0.3, 0.3, 1.5)];
 
         let eles = vec![Tetrahedron::new(&nodes, N3Index(0), N3Index(1), N3Index(2), N3Index(3)),
                         Tetrahedron::new(&nodes, N3Index(0), N3Index(1), N3Index(2), N3Index(4))];
@@ -153,10 +495,10 @@ mod bw_insertion {
         let tr = Triangulation3::new_from_prebuilt_triangulation(nodes.clone(), eles.clone());
 
         //first point which belongs inside two tetras
-        assert_eq!(2, find(&tr, T4Index(0), &Point3::new(0.3, 0.3, 0.5)).len());
+        assert_eq!(2, find(&tr, T4Index(0), N3Index(5)).elements.len());
 
         //this point only lives in the bigger tetra.
-        assert_eq!(1, find(&tr, T4Index(0), &Point3::new(0.3, 0.3, 1.5)).len());
+        assert_eq!(1, find(&tr, T4Index(0), N3Index(6)).elements.len());
     }
 
     #[test]
@@ -0,0 +1,670 @@
+use super::Triangulation3;
+use super::triangulation3_bw_insertion::find_border_face_across_edge;
+use super::triangulation3_neighborhood::Triangulation3Neighborhood;
+use super::triangulation3_removal::remove_element;
+
+use types::*;
+use math::side_of_plane;
+use math::SideOfPlane;
+
+//elements created by a flip and the elements moved into the slots it freed, as pairs of the
+//previous and the new index.
+pub struct Flip {
+    pub elements: Vec<T4Index>,
+    pub moved: Vec<(T4Index, T4Index)>,
+}
+
+//elements a flip would replace and the elements it would create instead, the flip is checked
+//but not done yet.
+pub struct PlannedFlip {
+    pub replaced: Vec<T4Index>,
+    pub elements: Vec<Tetrahedron>,
+}
+
+//replaces the element and its neighbor across the face by three elements around the edge
+//between their other nodes, which has to cross the face.
+pub fn flip23(triangulation: &mut Triangulation3,
+              ele_index: T4Index,
+              face_index: usize)
+              -> Result<Flip, DelaunayError> {
+    let planned = plan_flip23(triangulation, ele_index, face_index)?;
+    Ok(do_flip(triangulation, planned))
+}
+
+//replaces the three elements around the edge by two elements sharing the face between the other
+//nodes, which the edge has to cross.
+pub fn flip32(triangulation: &mut Triangulation3,
+              ele_index: T4Index,
+              edge: (N3Index, N3Index))
+              -> Result<Flip, DelaunayError> {
+    let planned = plan_flip32(triangulation, ele_index, edge)?;
+    Ok(do_flip(triangulation, planned))
+}
+
+//replaces the four elements around the edge by four elements around the new edge, which joins
+//two opposite nodes of the ring around the edge. both edges have to lie in one plane and cross.
+pub fn flip44(triangulation: &mut Triangulation3,
+              ele_index: T4Index,
+              edge: (N3Index, N3Index),
+              new_edge: (N3Index, N3Index))
+              -> Result<Flip, DelaunayError> {
+    let planned = plan_flip44(triangulation, ele_index, edge, new_edge)?;
+    Ok(do_flip(triangulation, planned))
+}
+
+pub fn do_flip(triangulation: &mut Triangulation3, planned: PlannedFlip) -> Flip {
+    replace_elements(triangulation, &planned.replaced, planned.elements)
+}
+
+//every flip which replaces the element: 2-3 flips across its faces, 3-2 flips of its edges and
+//4-4 flips of its edges to both new edges.
+pub fn plan_flips_of_element(triangulation: &Triangulation3,
+                             ele_index: T4Index)
+                             -> Vec<PlannedFlip> {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = *ele.nodes();
+    let mut planned: Vec<PlannedFlip> = (0..4)
+        .filter_map(|face_index| plan_flip23(triangulation, ele_index, face_index).ok())
+        .collect();
+
+    for i in 0..4 {
+        for j in i + 1..4 {
+            let edge = (nodes[i], nodes[j]);
+            planned.extend(plan_flip32(triangulation, ele_index, edge).ok());
+
+            //in a ring of four around the edge, the other node of the element lies opposite of
+            //the node across the face of the edge and the far one.
+            let others: Vec<N3Index> = (0..4)
+                .filter(|&k| k != i && k != j)
+                .map(|k| nodes[k])
+                .collect();
+            for &(near, far) in [(others[0], others[1]), (others[1], others[0])].iter() {
+                if let Some(neighbor_index) = ele.get_neighbor_for_indices(edge.0, edge.1, far) {
+                    let opposite = other_node(&triangulation.elements()[neighbor_index.0],
+                                              &[edge.0, edge.1, far]);
+                    planned.extend(plan_flip44(triangulation, ele_index, edge, (near, opposite))
+                        .ok());
+                }
+            }
+        }
+    }
+
+    planned
+}
+
+fn plan_flip23(triangulation: &Triangulation3,
+               ele_index: T4Index,
+               face_index: usize)
+               -> Result<PlannedFlip, DelaunayError> {
+    let (a, b, c) = triangulation.elements()[ele_index.0].faces_as_indices_tuples()[face_index];
+    let neighbor_index = match triangulation.elements()[ele_index.0]
+        .get_neighbor_from_index(face_index) {
+        Some(neighbor_index) => neighbor_index,
+        None => return Err(DelaunayError::NotFlippable),
+    };
+    let d = other_node(&triangulation.elements()[ele_index.0], &[a, b, c]);
+    let e = other_node(&triangulation.elements()[neighbor_index.0], &[a, b, c]);
+
+    if !crosses_triangle(triangulation, (d, e), (a, b, c)) {
+        return Err(DelaunayError::NotFlippable);
+    }
+
+    let nodes = triangulation.nodes();
+    Ok(PlannedFlip {
+        replaced: vec![ele_index, neighbor_index],
+        elements: vec![Tetrahedron::new(nodes, a, b, d, e),
+                       Tetrahedron::new(nodes, b, c, d, e),
+                       Tetrahedron::new(nodes, c, a, d, e)],
+    })
+}
+
+fn plan_flip32(triangulation: &Triangulation3,
+               ele_index: T4Index,
+               edge: (N3Index, N3Index))
+               -> Result<PlannedFlip, DelaunayError> {
+    let (ring_elements, ring) = match ring_around_edge(triangulation, ele_index, edge) {
+        Some(ring_around) => ring_around,
+        None => return Err(DelaunayError::NotFlippable),
+    };
+
+    if ring.len() != 3 || !crosses_triangle(triangulation, edge, (ring[0], ring[1], ring[2])) {
+        return Err(DelaunayError::NotFlippable);
+    }
+
+    let nodes = triangulation.nodes();
+    Ok(PlannedFlip {
+        replaced: ring_elements,
+        elements: vec![Tetrahedron::new(nodes, ring[0], ring[1], ring[2], edge.0),
+                       Tetrahedron::new(nodes, ring[0], ring[1], ring[2], edge.1)],
+    })
+}
+
+fn plan_flip44(triangulation: &Triangulation3,
+               ele_index: T4Index,
+               edge: (N3Index, N3Index),
+               new_edge: (N3Index, N3Index))
+               -> Result<PlannedFlip, DelaunayError> {
+    let (ring_elements, ring) = match ring_around_edge(triangulation, ele_index, edge) {
+        Some(ring_around) => ring_around,
+        None => return Err(DelaunayError::NotFlippable),
+    };
+
+    if ring.len() != 4 {
+        return Err(DelaunayError::NotFlippable);
+    }
+
+    let first = match (0..4).find(|&i| ring[i] == new_edge.0 || ring[i] == new_edge.1) {
+        Some(first) if first < 2 => first,
+        _ => return Err(DelaunayError::NotFlippable),
+    };
+    let (m, n) = (ring[first], ring[first + 2]);
+    if sorted_pair(m, n) != sorted_pair(new_edge.0, new_edge.1) ||
+       orientation(triangulation, edge.0, edge.1, m, n) != SideOfPlane::OnPlane {
+        return Err(DelaunayError::NotFlippable);
+    }
+
+    //new ring around the new edge has to wind around it like the old one around the old edge.
+    let new_ring = [edge.0, ring[first + 1], edge.1, ring[(first + 3) % 4]];
+    let winding = |i: usize| orientation(triangulation, m, n, new_ring[i], new_ring[(i + 1) % 4]);
+    if winding(0) == SideOfPlane::OnPlane || (1..4).any(|i| winding(i) != winding(0)) {
+        return Err(DelaunayError::NotFlippable);
+    }
+
+    let nodes = triangulation.nodes();
+    Ok(PlannedFlip {
+        replaced: ring_elements,
+        elements: (0..4)
+            .map(|i| Tetrahedron::new(nodes, m, n, new_ring[i], new_ring[(i + 1) % 4]))
+            .collect(),
+    })
+}
+
+//lawson insertion. elements containing the node, one or more when it lies on their faces, are
+//split into a star around it, then faces opposite of the node are flipped until every one of
+//them is locally delaunay. returns an element containing the node.
+pub fn insert_into_element_by_flips(triangulation: &mut Triangulation3,
+                                    ele_index: T4Index,
+                                    new_node_index: N3Index)
+                                    -> T4Index {
+    let containing = elements_containing(triangulation, ele_index, new_node_index);
+    let new_elements = {
+        let nodes = triangulation.nodes();
+        let p = &nodes[new_node_index.0];
+        let mut new_elements = Vec::new();
+
+        for ele_index in containing.iter() {
+            let ele = &triangulation.elements()[ele_index.0];
+            for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+                let inner = ele.get_neighbor_from_index(face_index)
+                    .map_or(false, |n| containing.contains(&n));
+                //node lying on a face of the border splits it, so the face gets no element.
+                if !inner &&
+                   side_of_plane(&nodes[(face.0).0], &nodes[(face.1).0], &nodes[(face.2).0], p) !=
+                   SideOfPlane::OnPlane {
+                    new_elements.push(Tetrahedron::new(nodes,
+                                                       face.0,
+                                                       face.1,
+                                                       face.2,
+                                                       new_node_index));
+                }
+            }
+        }
+        new_elements
+    };
+
+    let split = replace_elements(triangulation, &containing, new_elements);
+    flip_until_delaunay(triangulation, split.elements, new_node_index)
+}
+
+//node outside of the triangulation is connected to the border faces which see it, the faces
+//between them and the old elements are then flipped like after an insertion inside.
+pub fn insert_outside_by_flips(triangulation: &mut Triangulation3,
+                               ele_index: T4Index,
+                               face_index: usize,
+                               new_node_index: N3Index)
+                               -> T4Index {
+    let visible = visible_border_faces(triangulation, ele_index, face_index, new_node_index);
+    let mut to_teach: Vec<T4Index> = visible.iter().map(|&(ele_index, _)| ele_index).collect();
+    let mut new_elements = Vec::new();
+
+    for &(ele_index, face_index) in visible.iter() {
+        let face = triangulation.elements()[ele_index.0].faces_as_indices_tuples()[face_index];
+        let new_element =
+            Tetrahedron::new(triangulation.nodes(), face.0, face.1, face.2, new_node_index);
+        new_elements.push(T4Index(triangulation.elements().len()));
+        triangulation.elements_mut().push(new_element);
+    }
+
+    to_teach.extend(new_elements.iter().cloned());
+    to_teach.sort();
+    to_teach.dedup();
+    let elements_to_teach = triangulation.elements_mut();
+    Triangulation3Neighborhood::teach_selected_elements_of_neighborhood(&to_teach,
+                                                                        elements_to_teach);
+
+    flip_until_delaunay(triangulation, new_elements, new_node_index)
+}
+
+fn flip_until_delaunay(triangulation: &mut Triangulation3,
+                       new_elements: Vec<T4Index>,
+                       node_index: N3Index)
+                       -> T4Index {
+    let mut last = new_elements[0];
+    let mut to_check: Vec<T4Index> = new_elements;
+
+    while let Some(ele_index) = to_check.pop() {
+        let flip = match flip_if_not_delaunay(triangulation, ele_index, node_index) {
+            Some(flip) => flip,
+            None => continue,
+        };
+
+        for &(from, to) in flip.moved.iter() {
+            for checked in to_check.iter_mut().chain(Some(&mut last)) {
+                if *checked == from {
+                    *checked = to;
+                }
+            }
+        }
+
+        for new_index in flip.elements {
+            if triangulation.elements()[new_index.0].nodes().contains(&node_index) {
+                to_check.push(new_index);
+                last = new_index;
+            }
+        }
+    }
+
+    last
+}
+
+//flips the face of the element opposite of the node when the node of the neighbor across it
+//lies in the circumsphere of the element. faces which can not be flipped yet are left for the
+//flips of their neighbors.
+fn flip_if_not_delaunay(triangulation: &mut Triangulation3,
+                        ele_index: T4Index,
+                        node_index: N3Index)
+                        -> Option<Flip> {
+    if ele_index.0 >= triangulation.elements().len() {
+        return None;
+    }
+
+    let (face_index, face, apex) = {
+        let ele = &triangulation.elements()[ele_index.0];
+        let face_index = match ele.nodes().iter().position(|n| *n == node_index) {
+            Some(position) => opposite_face(position),
+            None => return None,
+        };
+        let face = ele.faces_as_indices_tuples()[face_index];
+        let neighbor_index = match ele.get_neighbor_from_index(face_index) {
+            Some(neighbor_index) => neighbor_index,
+            None => return None,
+        };
+        let apex = other_node(&triangulation.elements()[neighbor_index.0],
+                              &[face.0, face.1, face.2]);
+
+        if !ele.is_node_in_circumsphere(apex, triangulation.nodes()) {
+            return None;
+        }
+        (face_index, face, apex)
+    };
+
+    if let Ok(flip) = flip23(triangulation, ele_index, face_index) {
+        return Some(flip);
+    }
+
+    for &edge in [(face.0, face.1), (face.1, face.2), (face.2, face.0)].iter() {
+        if let Ok(flip) = flip32(triangulation, ele_index, edge) {
+            return Some(flip);
+        }
+        if let Ok(flip) = flip44(triangulation, ele_index, edge, (node_index, apex)) {
+            return Some(flip);
+        }
+    }
+
+    None
+}
+
+//elements around the edge in the order of rotation, element i lies between the ring nodes i and
+//i + 1. none when the edge lies on the border or is not an edge of the element.
+fn ring_around_edge(triangulation: &Triangulation3,
+                    ele_index: T4Index,
+                    edge: (N3Index, N3Index))
+                    -> Option<(Vec<T4Index>, Vec<N3Index>)> {
+    let first = &triangulation.elements()[ele_index.0];
+    if edge.0 == edge.1 || !first.nodes().contains(&edge.0) || !first.nodes().contains(&edge.1) {
+        return None;
+    }
+
+    let mut others = first.nodes().iter().filter(|n| **n != edge.0 && **n != edge.1);
+    let start = *others.next().unwrap();
+    let mut shared = *others.next().unwrap();
+    let mut ring = vec![start, shared];
+    let mut elements = vec![ele_index];
+
+    loop {
+        let current = &triangulation.elements()[elements.last().unwrap().0];
+        let next_index = match current.get_neighbor_for_indices(edge.0, edge.1, shared) {
+            Some(next_index) => next_index,
+            None => return None,
+        };
+        if next_index == ele_index {
+            return Some((elements, ring));
+        }
+
+        shared = other_node(&triangulation.elements()[next_index.0],
+                            &[edge.0, edge.1, shared]);
+        elements.push(next_index);
+        if shared != start {
+            ring.push(shared);
+        }
+    }
+}
+
+//elements whose closure contains the node, found by crossing the faces the node lies on.
+fn elements_containing(triangulation: &Triangulation3,
+                       ele_index: T4Index,
+                       node_index: N3Index)
+                       -> Vec<T4Index> {
+    let nodes = triangulation.nodes();
+    let p = &nodes[node_index.0];
+    let mut containing = vec![ele_index];
+    let mut position = 0;
+
+    while position < containing.len() {
+        let ele = &triangulation.elements()[containing[position].0];
+        position += 1;
+
+        for (face_index, face) in ele.faces_as_points_tuples(nodes).iter().enumerate() {
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(face_index) {
+                if !containing.contains(&neighbor_index) &&
+                   side_of_plane(face.0, face.1, face.2, p) == SideOfPlane::OnPlane {
+                    containing.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    containing
+}
+
+//border faces from which the node lies strictly outside, connected to the given one.
+fn visible_border_faces(triangulation: &Triangulation3,
+                        ele_index: T4Index,
+                        face_index: usize,
+                        node_index: N3Index)
+                        -> Vec<(T4Index, usize)> {
+    let nodes = triangulation.nodes();
+    let sees = |ele_index: T4Index, face_index: usize| {
+        let face = triangulation.elements()[ele_index.0].faces_as_points_tuples(nodes)[face_index];
+        side_of_plane(face.0, face.1, face.2, &nodes[node_index.0]) == SideOfPlane::Left
+    };
+
+    let mut visible = vec![(ele_index, face_index)];
+    let mut position = 0;
+
+    while position < visible.len() {
+        let (ele_index, face_index) = visible[position];
+        position += 1;
+
+        let face = triangulation.elements()[ele_index.0].faces_as_indices_tuples()[face_index];
+        for &(n1, n2) in [(face.0, face.1), (face.1, face.2), (face.2, face.0)].iter() {
+            let across = find_border_face_across_edge(triangulation, ele_index, face_index, n1, n2);
+            if !visible.contains(&across) && sees(across.0, across.1) {
+                visible.push(across);
+            }
+        }
+    }
+
+    visible
+}
+
+//new elements take the slots of the old ones, slots left over are filled with the last elements.
+//the old elements have the same border as the new ones, so only their neighbors are relinked.
+fn replace_elements(triangulation: &mut Triangulation3,
+                    old_elements: &[T4Index],
+                    new_elements: Vec<Tetrahedron>)
+                    -> Flip {
+    let mut to_teach = Vec::new();
+    for old_index in old_elements.iter() {
+        let neighbors = triangulation.elements()[old_index.0].neighbors();
+        for neighbor in neighbors.iter().filter_map(|n| *n) {
+            if !old_elements.contains(&neighbor) && !to_teach.contains(&neighbor) {
+                to_teach.push(neighbor);
+            }
+        }
+    }
+
+    let mut elements = Vec::new();
+    for (position, new_element) in new_elements.into_iter().enumerate() {
+        if position < old_elements.len() {
+            triangulation.elements_mut()[old_elements[position].0] = new_element;
+            elements.push(old_elements[position]);
+        } else {
+            elements.push(T4Index(triangulation.elements().len()));
+            triangulation.elements_mut().push(new_element);
+        }
+    }
+
+    to_teach.extend(elements.iter().cloned());
+    let elements_to_teach = triangulation.elements_mut();
+    Triangulation3Neighborhood::teach_selected_elements_of_neighborhood(&to_teach,
+                                                                        elements_to_teach);
+
+    let mut left_over: Vec<T4Index> = old_elements.iter().skip(elements.len()).cloned().collect();
+    left_over.sort();
+    let mut moved = Vec::new();
+    for slot in left_over.into_iter().rev() {
+        if let Some(from) = remove_element(triangulation, slot) {
+            for element in elements.iter_mut() {
+                if *element == from {
+                    *element = slot;
+                }
+            }
+            moved.push((from, slot));
+        }
+    }
+
+    Flip {
+        elements: elements,
+        moved: moved,
+    }
+}
+
+//segment between the nodes passes through the inside of the triangle.
+fn crosses_triangle(triangulation: &Triangulation3,
+                    segment: (N3Index, N3Index),
+                    triangle: (N3Index, N3Index, N3Index))
+                    -> bool {
+    let (p, q) = segment;
+    let (a, b, c) = triangle;
+    let side_of_p = orientation(triangulation, a, b, c, p);
+    let around = orientation(triangulation, a, b, p, q);
+
+    side_of_p != SideOfPlane::OnPlane && around != SideOfPlane::OnPlane &&
+    orientation(triangulation, a, b, c, q) != side_of_p &&
+    orientation(triangulation, a, b, c, q) != SideOfPlane::OnPlane &&
+    orientation(triangulation, b, c, p, q) == around &&
+    orientation(triangulation, c, a, p, q) == around
+}
+
+#[inline]
+fn orientation(triangulation: &Triangulation3,
+               a: N3Index,
+               b: N3Index,
+               c: N3Index,
+               d: N3Index)
+               -> SideOfPlane {
+    let nodes = triangulation.nodes();
+    side_of_plane(&nodes[a.0], &nodes[b.0], &nodes[c.0], &nodes[d.0])
+}
+
+#[inline]
+fn other_node(ele: &Tetrahedron, known: &[N3Index]) -> N3Index {
+    *ele.nodes()
+        .iter()
+        .find(|n| !known.contains(n))
+        .expect("Element is made of the known nodes only.")
+}
+
+//face of an element lying opposite of its node at the position.
+#[inline]
+fn opposite_face(node_position: usize) -> usize {
+    [2, 3, 1, 0][node_position]
+}
+
+#[inline]
+fn sorted_pair(n1: N3Index, n2: N3Index) -> (N3Index, N3Index) {
+    if n1 < n2 { (n1, n2) } else { (n2, n1) }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::volume;
+    use types::triangulation3_test_utils::random_points;
+    use types::triangulation3_test_utils::sorted_elements;
+
+    fn prebuilt(nodes: &[Point3], elements: &[[usize; 4]]) -> Triangulation3 {
+        let elements = elements.iter()
+            .map(|e| {
+                Tetrahedron::new(nodes, N3Index(e[0]), N3Index(e[1]), N3Index(e[2]), N3Index(e[3]))
+            })
+            .collect();
+        Triangulation3::new_from_prebuilt_triangulation(nodes.to_vec(), elements)
+    }
+
+    #[test]
+    fn flipping_two_elements_to_three_and_back() {
+        //edge between nodes 3 and 4 crosses the triangle of the first three.
+        let nodes = [Point3::new(0., 0., 0.),
+                     Point3::new(2., 0., 0.),
+                     Point3::new(0., 2., 0.),
+                     Point3::new(0.5, 0.5, 1.),
+                     Point3::new(0.5, 0.5, -1.)];
+        let mut triangulation = prebuilt(&nodes, &[[0, 1, 2, 3], [0, 1, 2, 4]]);
+        let face_index = triangulation.elements()[0].get_neighbor_index(N3Index(0),
+                                                                        N3Index(1),
+                                                                        N3Index(2));
+
+        let new_elements = triangulation.flip23(T4Index(0), face_index);
+
+        assert_eq!(3, triangulation.elements().len());
+        assert_eq!([T4Index(0), T4Index(1), T4Index(2)], new_elements);
+        assert!(triangulation.elements()
+            .iter()
+            .all(|e| e.nodes().contains(&N3Index(3)) && e.nodes().contains(&N3Index(4))));
+        assert!((volume(&triangulation) - 4. / 3.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+
+        let new_elements = triangulation.flip32(T4Index(1), (N3Index(4), N3Index(3)));
+
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![0, 1, 2, 4]],
+                   sorted_elements(&triangulation));
+        assert_eq!([T4Index(1), T4Index(0)], new_elements);
+        assert!((volume(&triangulation) - 4. / 3.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn flipping_octahedron_around_other_diagonal() {
+        //four elements around the edge between the poles, the ring lies on the equator.
+        let nodes = [Point3::new(0., 0., 1.),
+                     Point3::new(0., 0., -1.),
+                     Point3::new(1., 0., 0.),
+                     Point3::new(0., 1., 0.),
+                     Point3::new(-1., 0., 0.),
+                     Point3::new(0., -1., 0.)];
+        let mut triangulation =
+            prebuilt(&nodes, &[[0, 1, 2, 3], [0, 1, 3, 4], [0, 1, 4, 5], [0, 1, 5, 2]]);
+
+        let (edge, not_opposite) = ((N3Index(0), N3Index(1)), (N3Index(2), N3Index(3)));
+        match triangulation.try_flip44(T4Index(0), edge, not_opposite) {
+            Err(DelaunayError::NotFlippable) => (),
+            other => panic!("expected not flippable, got {:?}", other),
+        }
+
+        triangulation.flip44(T4Index(2), (N3Index(0), N3Index(1)), (N3Index(2), N3Index(4)));
+
+        assert_eq!(4, triangulation.elements().len());
+        assert!(triangulation.elements()
+            .iter()
+            .all(|e| e.nodes().contains(&N3Index(2)) && e.nodes().contains(&N3Index(4))));
+        assert!((volume(&triangulation) - 4. / 3.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn errors_of_flips() {
+        //node 4 lies beside the triangle of the first three, so the two elements are not convex.
+        let nodes = [Point3::new(0., 0., 0.),
+                     Point3::new(1., 0., 0.),
+                     Point3::new(0., 1., 0.),
+                     Point3::new(0.2, 0.2, 1.),
+                     Point3::new(2., 2., -1.)];
+        let mut triangulation = prebuilt(&nodes, &[[0, 1, 2, 3], [0, 1, 2, 4]]);
+        let shared = triangulation.elements()[0].get_neighbor_index(N3Index(0),
+                                                                    N3Index(1),
+                                                                    N3Index(2));
+        let border = (shared + 1) % 4;
+
+        for &face_index in [shared, border].iter() {
+            match triangulation.try_flip23(T4Index(0), face_index) {
+                Err(DelaunayError::NotFlippable) => (),
+                other => panic!("expected not flippable, got {:?}", other),
+            }
+        }
+        match triangulation.try_flip32(T4Index(0), (N3Index(0), N3Index(1))) {
+            Err(DelaunayError::NotFlippable) => (),
+            other => panic!("expected not flippable, got {:?}", other),
+        }
+        match triangulation.try_flip32(T4Index(0), (N3Index(0), N3Index(4))) {
+            Err(DelaunayError::NotFlippable) => (),
+            other => panic!("expected not flippable, got {:?}", other),
+        }
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![0, 1, 2, 4]],
+                   sorted_elements(&triangulation));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn inserting_by_flips_gives_delaunay_triangulation() {
+        let nodes = random_points(11, 200);
+
+        let mut triangulation = Triangulation3::new(&nodes[..5]);
+        triangulation.set_insertion_algorithm(InsertionAlgorithm::Flipping);
+        for p in nodes[5..].iter() {
+            triangulation.insert_node(p);
+        }
+
+        assert_eq!(sorted_elements(&Triangulation3::new(&nodes)),
+                   sorted_elements(&triangulation));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn inserting_by_flips_on_faces_and_edges() {
+        let nodes = [Point3::new(0., 0., 0.),
+                     Point3::new(1., 0., 0.),
+                     Point3::new(0., 1., 0.),
+                     Point3::new(0., 0., 1.),
+                     Point3::new(0.9, 0.8, 0.7)];
+        let mut triangulation = Triangulation3::new(&nodes);
+        let hull_volume = volume(&triangulation);
+        triangulation.set_insertion_algorithm(InsertionAlgorithm::Flipping);
+
+        //on the border face, on the border edge, on the face between the two elements.
+        triangulation.insert_node(&Point3::new(0.2, 0.2, 0.));
+        triangulation.insert_node(&Point3::new(0.5, 0., 0.));
+        triangulation.insert_node(&Point3::new(0.3, 0.3, 0.4));
+
+        assert!((volume(&triangulation) - hull_volume).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+        for ele in triangulation.elements() {
+            for node_index in 0..triangulation.nodes().len() {
+                assert!(ele.nodes().contains(&N3Index(node_index)) ||
+                        !ele.is_node_in_circumsphere(N3Index(node_index), triangulation.nodes()));
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::thread;
+use std::collections::BTreeMap;
+
+use super::Triangulation3;
+use super::triangulation3_utilities::tetrahedron_circumcenter;
+
+use types::*;
+use algorithms3::element_locators::*;
+use algorithms3::sort_3::sort_3;
+use cgmath::InnerSpace;
+
+//slabs with less nodes are not worth a thread.
+const MIN_SLAB_NODES: usize = 1000;
+
+//same as the 2d builder: nodes sorted by x are split into slabs triangulated in their own
+//threads. elements whose circumsphere lies inside of their slab are safe, the rest is
+//triangulated again from the nodes of the other elements and of the borders of the slabs. the
+//slabs keep the order of node indexes, so the result has the same elements as the one built by
+//a single thread. none when the nodes can not be split or a slab can not be triangulated.
+pub fn build(nodes: &[Point3],
+             sorted_indexes: &[usize],
+             options: &BuildOptions)
+             -> Option<Triangulation3> {
+    let slabs = split_into_slabs(nodes, sorted_indexes, options.threads);
+    if slabs.len() < 2 {
+        return None;
+    }
+
+    let slab_options = BuildOptions { threads: 1, ..*options };
+    let handles: Vec<_> = slabs.iter()
+        .map(|slab| {
+            let slab_nodes: Vec<Point3> = slab.indexes.iter().map(|i| nodes[*i]).collect();
+            let (left, right) = (slab.left, slab.right);
+            thread::spawn(move || {
+                Triangulation3::try_new_with_options(&slab_nodes, &slab_options)
+                    .map(|triangulation| {
+                        let safe = safe_elements(&triangulation, left, right);
+                        (triangulation, safe)
+                    })
+            })
+        })
+        .collect();
+
+    let mut triangulations = Vec::new();
+    let mut safe = Vec::new();
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok((triangulation, slab_safe))) => {
+                triangulations.push(triangulation);
+                safe.push(slab_safe);
+            }
+            _ => return None,
+        }
+    }
+
+    //safe elements keep their neighbors, faces towards the other elements are linked later.
+    let mut global_indexes = Vec::new();
+    let mut count = 0;
+    for slab_safe in safe.iter() {
+        global_indexes.push(slab_safe.iter()
+            .map(|&is_safe| if is_safe {
+                count += 1;
+                Some(T4Index(count - 1))
+            } else {
+                None
+            })
+            .collect::<Vec<_>>());
+    }
+
+    let mut elements = Vec::with_capacity(count);
+    let mut open_faces = BTreeMap::new();
+    let mut remaining_nodes = vec![false; nodes.len()];
+    for (slab_index, triangulation) in triangulations.iter().enumerate() {
+        let indexes = &slabs[slab_index].indexes;
+        let slab_global_indexes = &global_indexes[slab_index];
+
+        for (local_index, ele) in triangulation.elements().iter().enumerate() {
+            let v = ele.nodes();
+            if slab_global_indexes[local_index].is_none() {
+                for n in v.iter() {
+                    remaining_nodes[indexes[n.0]] = true;
+                }
+                continue;
+            }
+
+            let mut neighbors = [None; 4];
+            for face_index in 0..4 {
+                neighbors[face_index] = ele.get_neighbor_from_index(face_index)
+                    .and_then(|n| slab_global_indexes[n.0]);
+            }
+            let ele_index = T4Index(elements.len());
+            elements.push(Tetrahedron::new_exact([N3Index(indexes[v[0].0]),
+                                                  N3Index(indexes[v[1].0]),
+                                                  N3Index(indexes[v[2].0]),
+                                                  N3Index(indexes[v[3].0])],
+                                                 neighbors));
+
+            for face_index in 0..4 {
+                if neighbors[face_index].is_none() {
+                    let (n1, n2, n3) = elements[ele_index.0].faces_as_indices_tuples()[face_index];
+                    for n in [n1, n2, n3].iter() {
+                        remaining_nodes[n.0] = true;
+                    }
+                    link(&mut elements, &mut open_faces, ele_index, face_index);
+                }
+            }
+        }
+    }
+
+    //indexes are taken in increasing order, as in the slabs.
+    let remaining: Vec<usize> = (0..nodes.len()).filter(|i| remaining_nodes[*i]).collect();
+    let remaining_points: Vec<Point3> = remaining.iter().map(|i| nodes[*i]).collect();
+    let rest = match Triangulation3::try_new_with_options(&remaining_points, &slab_options) {
+        Ok(rest) => rest,
+        Err(_) => return None,
+    };
+
+    //elements of the rest lying inside of the safe elements are not elements of the whole
+    //triangulation, the others fill the space between them.
+    let mut hints = vec![T4Index(0); slabs.len()];
+    for ele in rest.elements() {
+        let center = ele.create_center_point(rest.nodes());
+        let slab_index = slabs.iter()
+            .position(|s| center.x < s.right)
+            .unwrap_or(slabs.len() - 1);
+        let triangulation = &triangulations[slab_index];
+
+        let covered = match locate_element_containing(hints[slab_index],
+                                                      triangulation.elements(),
+                                                      triangulation.nodes(),
+                                                      &center) {
+            LocationResult::InElement(ele_index) |
+            LocationResult::OnFace(ele_index, _) |
+            LocationResult::OnEdge(ele_index, _, _) |
+            LocationResult::OnVertex(ele_index, _) => {
+                hints[slab_index] = ele_index;
+                safe[slab_index][ele_index.0]
+            }
+            LocationResult::Outside(ele_index, _) => {
+                hints[slab_index] = ele_index;
+                false
+            }
+        };
+        if covered {
+            continue;
+        }
+
+        let v = ele.nodes();
+        let ele_index = T4Index(elements.len());
+        elements.push(Tetrahedron::new_exact([N3Index(remaining[v[0].0]),
+                                              N3Index(remaining[v[1].0]),
+                                              N3Index(remaining[v[2].0]),
+                                              N3Index(remaining[v[3].0])],
+                                             [None; 4]));
+        for face_index in 0..4 {
+            link(&mut elements, &mut open_faces, ele_index, face_index);
+        }
+    }
+
+    //only the faces of the convex hull stay open and the elements have its volume, so they do
+    //not overlap and leave no holes.
+    let border_faces: usize = rest.elements()
+        .iter()
+        .map(|e| (0..4).filter(|i| e.get_neighbor_from_index(*i).is_none()).count())
+        .sum();
+    let hull_volume = volume(rest.nodes(), rest.elements());
+    if open_faces.len() != border_faces ||
+       (volume(nodes, &elements) - hull_volume).abs() > 1e-9 * hull_volume {
+        return None;
+    }
+
+    let mut triangulation = Triangulation3::new_from_prebuilt_triangulation(nodes.to_vec(),
+                                                                            Vec::new());
+    *triangulation.elements_mut() = elements;
+    Some(triangulation)
+}
+
+struct Slab {
+    indexes: Vec<usize>,
+    left: f64,
+    right: f64,
+}
+
+//splits the nodes into slabs of about the same number of nodes. nodes with equal x are kept in
+//one slab, so that the slabs are divided by planes lying between nodes.
+fn split_into_slabs(nodes: &[Point3], sorted_indexes: &[usize], threads: usize) -> Vec<Slab> {
+    let count = threads.min(sorted_indexes.len() / MIN_SLAB_NODES);
+    let mut slabs = Vec::new();
+    let mut start = 0;
+    let mut left = ::std::f64::MIN;
+
+    for slab_index in 1..count + 1 {
+        let mut end = if slab_index == count {
+            sorted_indexes.len()
+        } else {
+            (sorted_indexes.len() * slab_index / count).max(start + 1)
+        };
+        while end < sorted_indexes.len() &&
+              nodes[sorted_indexes[end]].x == nodes[sorted_indexes[end - 1]].x {
+            end += 1;
+        }
+        if end == start {
+            break;
+        }
+
+        let right = if end < sorted_indexes.len() {
+            (nodes[sorted_indexes[end - 1]].x + nodes[sorted_indexes[end]].x) / 2.
+        } else {
+            ::std::f64::MAX
+        };
+
+        let mut indexes = sorted_indexes[start..end].to_vec();
+        indexes.sort();
+        slabs.push(Slab {
+            indexes: indexes,
+            left: left,
+            right: right,
+        });
+
+        start = end;
+        left = right;
+    }
+
+    slabs
+}
+
+//elements whose circumsphere lies between the bounds of the slab. the bounds are kept a bit
+//inside, an element wrongly taken as not safe is only triangulated again.
+fn safe_elements(triangulation: &Triangulation3, left: f64, right: f64) -> Vec<bool> {
+    let nodes = triangulation.nodes();
+    triangulation.elements()
+        .iter()
+        .map(|ele| {
+            let (a, b, c, d) = (ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes));
+            let center = tetrahedron_circumcenter(a, b, c, d);
+            let radius = (center - a).magnitude();
+            let margin = 1e-9 * (center.x.abs() + radius);
+
+            center.x - radius - margin > left && center.x + radius + margin < right
+        })
+        .collect()
+}
+
+//links the element with the element having the same face. a face of three elements is linked
+//only twice and stays open for the third one.
+fn link(elements: &mut Vec<Tetrahedron>,
+        open_faces: &mut BTreeMap<(N3Index, N3Index, N3Index), T4Index>,
+        ele_index: T4Index,
+        face_index: usize) {
+    let (n1, n2, n3) = elements[ele_index.0].faces_as_indices_tuples()[face_index];
+
+    match open_faces.remove(&sort_3(n1, n2, n3)) {
+        Some(neighbor_index) => {
+            elements[ele_index.0].set_neighbor(face_index, Some(neighbor_index));
+            elements[neighbor_index.0].update_neighbor(n1, n2, n3, Some(ele_index));
+        }
+        None => {
+            open_faces.insert(sort_3(n1, n2, n3), ele_index);
+        }
+    }
+}
+
+fn volume(nodes: &[Point3], elements: &[Tetrahedron]) -> f64 {
+    elements.iter()
+        .map(|ele| {
+            let (a, b, c, d) = (ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes));
+            (b - a).cross(c - a).dot(d - a).abs() / 6.
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::random_points;
+    use types::triangulation3_test_utils::sorted_elements;
+
+    fn sorted_by_x(nodes: &[Point3]) -> Vec<usize> {
+        let mut sorted: Vec<usize> = (0..nodes.len()).collect();
+        sorted.sort_by(|a, b| nodes[*a].x.partial_cmp(&nodes[*b].x).unwrap());
+        sorted
+    }
+
+    #[test]
+    fn building_random_nodes_in_parallel() {
+        let nodes = random_points(3, 3000);
+        let options = BuildOptions {
+            threads: 3,
+            insertion_order: InsertionOrder::Hilbert,
+            ..BuildOptions::default()
+        };
+
+        let parallel = super::build(&nodes, &sorted_by_x(&nodes), &options)
+            .expect("random nodes are split into slabs.");
+        let sequential = Triangulation3::new(&nodes);
+
+        assert_eq!(sorted_elements(&sequential), sorted_elements(&parallel));
+        assert_neighborhood(&parallel);
+    }
+
+    #[test]
+    fn building_grid_in_parallel() {
+        //nodes of the grid lie on common spheres and on the borders of the slabs.
+        let mut nodes = Vec::new();
+        for i in 0..2197 {
+            let position = (i * 37) % 2197;
+            nodes.push(Point3::new((position % 13) as f64,
+                                   ((position / 13) % 13) as f64,
+                                   (position / 169) as f64));
+        }
+        let options = BuildOptions {
+            threads: 2,
+            insertion_order: InsertionOrder::Hilbert,
+            ..BuildOptions::default()
+        };
+
+        let parallel = super::build(&nodes, &sorted_by_x(&nodes), &options)
+            .expect("grid is split into slabs.");
+        let sequential = Triangulation3::new(&nodes);
+
+        assert_eq!(sorted_elements(&sequential), sorted_elements(&parallel));
+        assert_neighborhood(&parallel);
+    }
+}
@@ -0,0 +1,648 @@
+use super::Triangulation3;
+use super::triangulation3_removal::remove_element;
+use super::triangulation3_utilities::triangle_circumcenter;
+
+use types::*;
+use math::distance3_squared;
+use algorithms3::sort_3::sort_3;
+use algorithms3::nearest_node::elements_around_node;
+use algorithms3::element_locators::LocationResult;
+use cgmath::InnerSpace;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+
+//nodes closer than this part of the size of the surface are not inserted during the recovery.
+const SMALLEST_FEATURE: f64 = 1e-9;
+
+//piece of an input segment, the segments are the edges of the facets.
+struct Subsegment {
+    ends: (N3Index, N3Index),
+    segment: usize,
+}
+
+struct Recovery {
+    facets_of_segment: Vec<Vec<usize>>,
+    segments_of_facet: Vec<[usize; 3]>,
+    subsegments: Vec<Subsegment>,
+    subsegments_of_segment: Vec<Vec<usize>>,
+    subsegment_of_edge: BTreeMap<(N3Index, N3Index), usize>,
+    //nodes lying on each facet, its corners included.
+    facet_nodes: Vec<Vec<N3Index>>,
+    //triangulation of each facet in its plane, none when a node was added to it since.
+    facet_pieces: Vec<Option<Vec<[N3Index; 3]>>>,
+    smallest_length_squared: f64,
+}
+
+//inserts nodes on the segments and facets until every piece of them is an edge or a face of the
+//triangulation. a piece whose smallest sphere is empty is delaunay, so segments are split in the
+//middle and facets in the circumcenters of their pieces, which splits encroached segments instead.
+//returns the faces which the facets were divided into.
+pub fn recover(triangulation: &mut Triangulation3,
+               facets: &[[N3Index; 3]])
+               -> Result<BTreeSet<(N3Index, N3Index, N3Index)>, DelaunayError> {
+    let mut recovery = create_recovery(triangulation, facets);
+
+    'recovery: loop {
+        let missing: Vec<usize> = (0..recovery.subsegments.len())
+            .filter(|s| {
+                let (first, second) = recovery.subsegments[*s].ends;
+                !has_edge(triangulation, first, second)
+            })
+            .collect();
+
+        if !missing.is_empty() {
+            for subsegment in missing {
+                split_subsegment(triangulation, &mut recovery, subsegment)?;
+            }
+            continue;
+        }
+
+        //a split changes the faces of the other facets too, so they are checked again. only the
+        //facets which got a node are triangulated again.
+        let mut subfaces = BTreeSet::new();
+        for facet in 0..facets.len() {
+            if recovery.facet_pieces[facet].is_none() {
+                recovery.facet_pieces[facet] = Some(triangulate_facet(triangulation,
+                                                                      &recovery,
+                                                                      facet)?);
+            }
+
+            let missing = match recovery.facet_pieces[facet] {
+                Some(ref pieces) => {
+                    pieces.iter().cloned().find(|piece| !has_face(triangulation, *piece))
+                }
+                None => None,
+            };
+            if let Some(piece) = missing {
+                split_subface(triangulation, &mut recovery, facet, piece)?;
+                continue 'recovery;
+            }
+
+            for piece in recovery.facet_pieces[facet].iter().flat_map(|pieces| pieces.iter()) {
+                subfaces.insert(sort_3(piece[0], piece[1], piece[2]));
+            }
+        }
+
+        return Ok(subfaces);
+    }
+}
+
+fn create_recovery(triangulation: &Triangulation3, facets: &[[N3Index; 3]]) -> Recovery {
+    let mut segment_indexes = BTreeMap::new();
+    let mut recovery = Recovery {
+        facets_of_segment: Vec::new(),
+        segments_of_facet: Vec::with_capacity(facets.len()),
+        subsegments: Vec::new(),
+        subsegments_of_segment: Vec::new(),
+        subsegment_of_edge: BTreeMap::new(),
+        facet_nodes: facets.iter().map(|f| f.to_vec()).collect(),
+        facet_pieces: vec![None; facets.len()],
+        smallest_length_squared: 0.,
+    };
+
+    for (facet_index, facet) in facets.iter().enumerate() {
+        let mut segments = [0; 3];
+
+        for i in 0..3 {
+            let (first, second) = (facet[i], facet[(i + 1) % 3]);
+            let next_index = segment_indexes.len();
+            let segment = *segment_indexes.entry(sorted_edge(first, second))
+                .or_insert(next_index);
+
+            if segment == recovery.facets_of_segment.len() {
+                recovery.facets_of_segment.push(Vec::new());
+                recovery.subsegments_of_segment.push(vec![segment]);
+                recovery.subsegment_of_edge.insert(sorted_edge(first, second), segment);
+                recovery.subsegments.push(Subsegment {
+                    ends: (first, second),
+                    segment: segment,
+                });
+            }
+
+            recovery.facets_of_segment[segment].push(facet_index);
+            segments[i] = segment;
+        }
+
+        recovery.segments_of_facet.push(segments);
+    }
+
+    let nodes = triangulation.nodes();
+    let mut size_squared: f64 = 0.;
+    for facet in facets.iter() {
+        for node in facet.iter() {
+            size_squared = size_squared.max(distance3_squared(&nodes[facet[0].0], &nodes[node.0]));
+        }
+    }
+    for subsegment in recovery.subsegments.iter() {
+        let (first, second) = subsegment.ends;
+        size_squared = size_squared.max(distance3_squared(&nodes[first.0], &nodes[second.0]));
+    }
+
+    recovery.smallest_length_squared = size_squared * SMALLEST_FEATURE * SMALLEST_FEATURE;
+    recovery
+}
+
+fn split_subsegment(triangulation: &mut Triangulation3,
+                    recovery: &mut Recovery,
+                    subsegment: usize)
+                    -> Result<(), DelaunayError> {
+    let (first, second) = recovery.subsegments[subsegment].ends;
+    let segment = recovery.subsegments[subsegment].segment;
+    let (a, b) = (triangulation.nodes()[first.0], triangulation.nodes()[second.0]);
+
+    if distance3_squared(&a, &b) < recovery.smallest_length_squared {
+        return Err(DelaunayError::UnrecoverableBoundary);
+    }
+
+    let middle = triangulation.try_insert_node(&Point3::new((a.x + b.x) / 2.,
+                                                            (a.y + b.y) / 2.,
+                                                            (a.z + b.z) / 2.))?;
+
+    let new_subsegment = recovery.subsegments.len();
+    recovery.subsegments[subsegment].ends = (first, middle);
+    recovery.subsegments.push(Subsegment {
+        ends: (middle, second),
+        segment: segment,
+    });
+    recovery.subsegments_of_segment[segment].push(new_subsegment);
+    recovery.subsegment_of_edge.remove(&sorted_edge(first, second));
+    recovery.subsegment_of_edge.insert(sorted_edge(first, middle), subsegment);
+    recovery.subsegment_of_edge.insert(sorted_edge(middle, second), new_subsegment);
+
+    for facet in recovery.facets_of_segment[segment].iter() {
+        recovery.facet_nodes[*facet].push(middle);
+        recovery.facet_pieces[*facet] = None;
+    }
+
+    Ok(())
+}
+
+//circumcenter of the piece, or the middle of a segment it encroaches. when rounding put the
+//circumcenter outside of the facet, the longest edge of the piece is split.
+fn split_subface(triangulation: &mut Triangulation3,
+                 recovery: &mut Recovery,
+                 facet: usize,
+                 piece: [N3Index; 3])
+                 -> Result<(), DelaunayError> {
+    let center = {
+        let nodes = triangulation.nodes();
+        triangle_circumcenter(&nodes[piece[0].0], &nodes[piece[1].0], &nodes[piece[2].0])
+    };
+
+    let encroached = recovery.segments_of_facet[facet]
+        .iter()
+        .flat_map(|segment| recovery.subsegments_of_segment[*segment].iter())
+        .cloned()
+        .find(|s| {
+            let (first, second) = recovery.subsegments[*s].ends;
+            (triangulation.nodes()[first.0] - center)
+                .dot(triangulation.nodes()[second.0] - center) < 0.
+        });
+
+    if let Some(subsegment) = encroached {
+        return split_subsegment(triangulation, recovery, subsegment);
+    }
+
+    let corners = {
+        let facet_nodes = &recovery.facet_nodes[facet];
+        (triangulation.nodes()[facet_nodes[0].0],
+         triangulation.nodes()[facet_nodes[1].0],
+         triangulation.nodes()[facet_nodes[2].0])
+    };
+    let facet_normal = (corners.1 - corners.0).cross(corners.2 - corners.0);
+    let inside = (corners.1 - corners.0).cross(center - corners.0).dot(facet_normal) > 0. &&
+                 (corners.2 - corners.1).cross(center - corners.1).dot(facet_normal) > 0. &&
+                 (corners.0 - corners.2).cross(center - corners.2).dot(facet_normal) > 0.;
+
+    let mut longest = (0., 0);
+    for i in 0..3 {
+        let length = distance3_squared(&triangulation.nodes()[piece[i].0],
+                                       &triangulation.nodes()[piece[(i + 1) % 3].0]);
+        if length > longest.0 {
+            longest = (length, i);
+        }
+    }
+
+    if longest.0 < recovery.smallest_length_squared {
+        return Err(DelaunayError::UnrecoverableBoundary);
+    }
+
+    let inserted = if inside {
+        triangulation.try_insert_node(&center)?
+    } else {
+        let (first, second) = (piece[longest.1], piece[(longest.1 + 1) % 3]);
+        let on_segment = recovery.subsegment_of_edge.get(&sorted_edge(first, second)).cloned();
+        if let Some(subsegment) = on_segment {
+            return split_subsegment(triangulation, recovery, subsegment);
+        }
+
+        let (p, q) = (triangulation.nodes()[first.0], triangulation.nodes()[second.0]);
+        triangulation.try_insert_node(&Point3::new((p.x + q.x) / 2.,
+                                                   (p.y + q.y) / 2.,
+                                                   (p.z + q.z) / 2.))?
+    };
+
+    recovery.facet_nodes[facet].push(inserted);
+    recovery.facet_pieces[facet] = None;
+    Ok(())
+}
+
+//delaunay triangulation of the nodes of the facet in its plane, with the pieces of its segments
+//as constraints. nodes on a segment do not lie on a line exactly after the projection, so pieces
+//made of the nodes of one segment are left out, they have no area.
+fn triangulate_facet(triangulation: &Triangulation3,
+                     recovery: &Recovery,
+                     facet: usize)
+                     -> Result<Vec<[N3Index; 3]>, DelaunayError> {
+    let facet_nodes = &recovery.facet_nodes[facet];
+    let nodes = triangulation.nodes();
+    let origin = nodes[facet_nodes[0].0];
+    let u = (nodes[facet_nodes[1].0] - origin).normalize();
+    let normal = u.cross(nodes[facet_nodes[2].0] - origin);
+    let w = normal.cross(u).normalize();
+
+    let projected: Vec<Point2> = facet_nodes.iter()
+        .map(|n| {
+            let p = nodes[n.0] - origin;
+            Point2::new(p.dot(u), p.dot(w))
+        })
+        .collect();
+
+    let mut planar = Triangulation2::try_new(&projected)?;
+    let position = |node: N3Index| facet_nodes.iter().position(|n| *n == node);
+    let mut segment_nodes = Vec::new();
+    for segment in recovery.segments_of_facet[facet].iter() {
+        let mut on_segment = BTreeSet::new();
+        for subsegment in recovery.subsegments_of_segment[*segment].iter() {
+            let (first, second) = recovery.subsegments[*subsegment].ends;
+            if let (Some(i), Some(j)) = (position(first), position(second)) {
+                planar.try_insert_constraint(N2Index(i), N2Index(j))?;
+            }
+            on_segment.insert(first);
+            on_segment.insert(second);
+        }
+        segment_nodes.push(on_segment);
+    }
+
+    Ok(planar.elements()
+        .iter()
+        .map(|e| {
+            let v = e.nodes();
+            [facet_nodes[v[0].0], facet_nodes[v[1].0], facet_nodes[v[2].0]]
+        })
+        .filter(|piece| {
+            !segment_nodes.iter().any(|on_segment| piece.iter().all(|n| on_segment.contains(n)))
+        })
+        .collect())
+}
+
+//elements around the node, starting at the one the walk to the node ends in.
+fn elements_at_node(triangulation: &Triangulation3, node: N3Index) -> Vec<T4Index> {
+    match triangulation.locate(&triangulation.nodes()[node.0], None).0 {
+        LocationResult::OnVertex(ele_index, found) if found == node => {
+            elements_around_node(ele_index, triangulation.elements(), node)
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn has_edge(triangulation: &Triangulation3, first: N3Index, second: N3Index) -> bool {
+    elements_at_node(triangulation, first)
+        .iter()
+        .any(|e| triangulation.elements()[e.0].nodes().contains(&second))
+}
+
+fn has_face(triangulation: &Triangulation3, face: [N3Index; 3]) -> bool {
+    elements_at_node(triangulation, face[0]).iter().any(|e| {
+        let v = triangulation.elements()[e.0].nodes();
+        v.contains(&face[1]) && v.contains(&face[2])
+    })
+}
+
+#[inline]
+fn sorted_edge(first: N3Index, second: N3Index) -> (N3Index, N3Index) {
+    if first < second {
+        (first, second)
+    } else {
+        (second, first)
+    }
+}
+
+//elements are inside when the way to them from the convex hull crosses the surface an odd number
+//of times, so surfaces inside of the solid bound voids.
+pub fn remove_outside_of_surface(triangulation: &mut Triangulation3,
+                                 subfaces: &BTreeSet<(N3Index, N3Index, N3Index)>)
+                                 -> Result<(), DelaunayError> {
+    let elements_count = triangulation.elements().len();
+    let mut crossings: Vec<Option<usize>> = vec![None; elements_count];
+    let mut queue = VecDeque::new();
+
+    for (ele_index, ele) in triangulation.elements().iter().enumerate() {
+        for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+            if ele.get_neighbor_from_index(face_index).is_none() {
+                if subfaces.contains(&sort_3(face.0, face.1, face.2)) {
+                    queue.push_back((T4Index(ele_index), 1));
+                } else {
+                    queue.push_front((T4Index(ele_index), 0));
+                }
+            }
+        }
+    }
+
+    //elements reached without crossing the surface are handled before the others.
+    while let Some((ele_index, count)) = queue.pop_front() {
+        if crossings[ele_index.0].map_or(false, |c| c <= count) {
+            continue;
+        }
+        crossings[ele_index.0] = Some(count);
+
+        let ele = &triangulation.elements()[ele_index.0];
+        for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(face_index) {
+                if subfaces.contains(&sort_3(face.0, face.1, face.2)) {
+                    queue.push_back((neighbor_index, count + 1));
+                } else {
+                    queue.push_front((neighbor_index, count));
+                }
+            }
+        }
+    }
+
+    let outside: Vec<bool> = crossings.iter().map(|c| c.map_or(true, |c| c % 2 == 0)).collect();
+    if outside.iter().all(|o| *o) {
+        return Err(DelaunayError::EmptyDomain);
+    }
+
+    //faces between the solid and the deleted elements become the border.
+    for ele_index in 0..elements_count {
+        if outside[ele_index] {
+            continue;
+        }
+
+        for face_index in 0..4 {
+            let neighbor = triangulation.elements()[ele_index].get_neighbor_from_index(face_index);
+            if let Some(neighbor_index) = neighbor {
+                if outside[neighbor_index.0] {
+                    triangulation.elements_mut()[ele_index].set_neighbor(face_index, None);
+                }
+            }
+        }
+    }
+
+    for ele_index in (0..elements_count).rev() {
+        if outside[ele_index] {
+            remove_element(triangulation, T4Index(ele_index));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use math::distance3_squared;
+    use std::collections::BTreeSet;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::volume;
+    use types::triangulation3_test_utils::random_points;
+    use cgmath::InnerSpace;
+
+    //corner i of the box lies at x = i & 1, y = i & 2, z = i & 4.
+    fn box_nodes(from: f64, to: f64) -> Vec<Point3> {
+        (0..8)
+            .map(|i| {
+                let coordinate = |bit| if i & bit == 0 { from } else { to };
+                Point3::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect()
+    }
+
+    fn box_facets(offset: usize, with_top: bool) -> Vec<[N3Index; 3]> {
+        let mut quads = vec![[0, 2, 6, 4], [1, 3, 7, 5], [0, 1, 5, 4], [2, 3, 7, 6], [0, 1, 3, 2]];
+        if with_top {
+            quads.push([4, 5, 7, 6]);
+        }
+
+        let mut facets = Vec::new();
+        for q in quads.iter() {
+            let n = |i: usize| N3Index(q[i] + offset);
+            facets.push([n(0), n(1), n(2)]);
+            facets.push([n(0), n(2), n(3)]);
+        }
+        facets
+    }
+
+    fn border_area(tr: &Triangulation3) -> f64 {
+        let mut area = 0.;
+        for ele in tr.elements() {
+            for (face_index, face) in ele.faces_as_points_tuples(tr.nodes()).iter().enumerate() {
+                if ele.get_neighbor_from_index(face_index).is_none() {
+                    area += (face.1 - face.0).cross(face.2 - face.0).magnitude() / 2.;
+                }
+            }
+        }
+        area
+    }
+
+    #[test]
+    fn recovering_cube() {
+        let mut nodes = box_nodes(0., 1.);
+        nodes.push(Point3::new(0.3, 0.4, 0.6));
+        nodes.push(Point3::new(0.7, 0.6, 0.2));
+
+        let triangulation = Triangulation3::from_plc(&nodes, &box_facets(0, true));
+
+        assert!((volume(&triangulation) - 1.).abs() < 1e-10);
+        assert!((border_area(&triangulation) - 6.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_outside_of_dented_cube() {
+        //top of the cube is pushed in to a pyramid, node 9 lies above it.
+        let mut nodes = box_nodes(0., 1.);
+        nodes.push(Point3::new(0.5, 0.5, 0.5));
+        nodes.push(Point3::new(0.5, 0.5, 0.9));
+
+        let mut facets = box_facets(0, false);
+        for &(first, second) in [(4, 5), (5, 7), (7, 6), (6, 4)].iter() {
+            facets.push([N3Index(first), N3Index(second), N3Index(8)]);
+        }
+
+        let mut triangulation = Triangulation3::from_plc(&nodes, &facets);
+        let dent_area = 4. * 0.5f64.sqrt() / 2.;
+
+        assert!((volume(&triangulation) - 5. / 6.).abs() < 1e-10);
+        assert!((border_area(&triangulation) - 5. - dent_area).abs() < 1e-10);
+        assert!(triangulation.elements().iter().all(|e| !e.nodes().contains(&N3Index(9))));
+        assert_neighborhood(&triangulation);
+
+        match triangulation.try_insert_node(&Point3::new(0.5, 0.5, 0.8)) {
+            Err(DelaunayError::OutsideOfDomain) => (),
+            other => panic!("expected outside of domain, got {:?}", other),
+        }
+        match triangulation.try_remove_node(N3Index(8)) {
+            Err(DelaunayError::NodeOnDomainBorder(8)) => (),
+            other => panic!("expected node on domain border, got {:?}", other),
+        }
+
+        let inserted = triangulation.insert_node(&Point3::new(0.9, 0.1, 0.8));
+        assert!((volume(&triangulation) - 5. / 6.).abs() < 1e-10);
+        assert!((border_area(&triangulation) - 5. - dent_area).abs() < 1e-10);
+        assert!(triangulation.is_restricted());
+        assert_neighborhood(&triangulation);
+
+        triangulation.remove_node(inserted);
+        assert!((volume(&triangulation) - 5. / 6.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn finding_nearest_nodes_in_dented_cube() {
+        let mut nodes = box_nodes(0., 1.);
+        nodes.push(Point3::new(0.5, 0.5, 0.5));
+        nodes.push(Point3::new(0.5, 0.5, 0.9));
+
+        let mut facets = box_facets(0, false);
+        for &(first, second) in [(4, 5), (5, 7), (7, 6), (6, 4)].iter() {
+            facets.push([N3Index(first), N3Index(second), N3Index(8)]);
+        }
+        let triangulation = Triangulation3::from_plc(&nodes, &facets);
+
+        //node 9 above the dent is not a part of the triangulation.
+        let above_dent = Point3::new(0.5, 0.5, 0.85);
+        assert_eq!(N3Index(8), triangulation.nearest_node(&above_dent));
+        assert!(!triangulation.nodes_within_radius(&above_dent, 0.5).contains(&N3Index(9)));
+
+        let used: BTreeSet<N3Index> = triangulation.elements()
+            .iter()
+            .flat_map(|e| e.nodes().iter().cloned())
+            .collect();
+        let distance = |p: &Point3, n: &N3Index| distance3_squared(p, &triangulation.nodes()[n.0]);
+        for q in random_points(30, 200) {
+            let p = Point3::new(1.4 * q.x - 0.2, 1.4 * q.y - 0.2, 1.4 * q.z - 0.2);
+            let mut brute_force: Vec<N3Index> = used.iter().cloned().collect();
+            brute_force.sort_by(|a, b| distance(&p, a).partial_cmp(&distance(&p, b)).unwrap());
+
+            assert_eq!(distance(&p, &brute_force[0]),
+                       distance(&p, &triangulation.nearest_node(&p)));
+            let nearest = triangulation.k_nearest(&p, 3);
+            assert_eq!(distance(&p, &brute_force[2]), distance(&p, &nearest[2]));
+
+            let radius = 0.5 * (distance(&p, &brute_force[3]).sqrt() +
+                                distance(&p, &brute_force[4]).sqrt());
+            assert_eq!(4, triangulation.nodes_within_radius(&p, radius).len());
+        }
+    }
+
+    #[test]
+    fn inserting_on_planes_of_concave_faces() {
+        //l shaped prism, the planes x = 1 and y = 1 of the notch run through the solid.
+        let corners = [(0., 0.), (2., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 2.)];
+        let mut nodes = Vec::new();
+        for &z in [0., 1.].iter() {
+            nodes.extend(corners.iter().map(|&(x, y)| Point3::new(x, y, z)));
+        }
+
+        let mut facets = Vec::new();
+        for &offset in [0, 6].iter() {
+            for i in 1..5 {
+                facets.push([N3Index(offset), N3Index(offset + i), N3Index(offset + i + 1)]);
+            }
+        }
+        for i in 0..6 {
+            let j = (i + 1) % 6;
+            facets.push([N3Index(i), N3Index(j), N3Index(j + 6)]);
+            facets.push([N3Index(i), N3Index(j + 6), N3Index(i + 6)]);
+        }
+
+        let mut triangulation = Triangulation3::from_plc(&nodes, &facets);
+        assert!((volume(&triangulation) - 3.).abs() < 1e-10);
+
+        for (i, q) in random_points(31, 40).into_iter().enumerate() {
+            let t = 1. - 0.3 * q.x;
+            let p = if i % 2 == 0 { Point3::new(1., t, q.z) } else { Point3::new(t, 1., q.z) };
+            triangulation.insert_node(&p);
+
+            assert!((volume(&triangulation) - 3.).abs() < 1e-10);
+            assert!((border_area(&triangulation) - 14.).abs() < 1e-10);
+        }
+        assert!(triangulation.is_restricted());
+        assert_neighborhood(&triangulation);
+
+        match triangulation.try_insert_node(&Point3::new(1.5, 1.5, 0.5)) {
+            Err(DelaunayError::OutsideOfDomain) => (),
+            other => panic!("expected outside of domain, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn keeping_void_inside_of_box() {
+        let mut nodes = box_nodes(0., 3.);
+        nodes.extend(box_nodes(1., 2.));
+        let mut facets = box_facets(0, true);
+        facets.extend(box_facets(8, true));
+
+        let triangulation = Triangulation3::from_plc(&nodes, &facets);
+
+        assert!((volume(&triangulation) - 26.).abs() < 1e-9);
+        assert!((border_area(&triangulation) - 54. - 6.).abs() < 1e-9);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn recovering_schonhardt_polyhedron() {
+        //twisted prism with reflex side edges can not be split into tetrahedra without steiner
+        //nodes.
+        let mut nodes = Vec::new();
+        for &(z, twist) in [(0., 0.), (1., 0.5)].iter() {
+            for i in 0..3 {
+                let angle = i as f64 * 2. * ::std::f64::consts::PI / 3. + twist;
+                nodes.push(Point3::new(angle.cos(), angle.sin(), z));
+            }
+        }
+
+        let mut facets = vec![[N3Index(0), N3Index(1), N3Index(2)],
+                              [N3Index(3), N3Index(4), N3Index(5)]];
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            facets.push([N3Index(i), N3Index(j), N3Index(3 + j)]);
+            facets.push([N3Index(i), N3Index(3 + j), N3Index(3 + i)]);
+        }
+
+        let surface_area: f64 = facets.iter()
+            .map(|f| {
+                let (a, b, c) = (nodes[f[0].0], nodes[f[1].0], nodes[f[2].0]);
+                (b - a).cross(c - a).magnitude() / 2.
+            })
+            .sum();
+
+        let triangulation = Triangulation3::from_plc(&nodes, &facets);
+
+        assert!(triangulation.nodes().len() > 6);
+        assert!((border_area(&triangulation) - surface_area).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn errors_of_plc() {
+        let nodes = box_nodes(0., 1.);
+
+        match Triangulation3::try_from_plc(&nodes, &[[N3Index(0), N3Index(1), N3Index(8)]]) {
+            Err(DelaunayError::UnknownNode(8)) => (),
+            other => panic!("expected unknown node, got {:?}", other.err()),
+        }
+        match Triangulation3::try_from_plc(&nodes, &[[N3Index(0), N3Index(1), N3Index(1)]]) {
+            Err(DelaunayError::DuplicateNodes(1, 1)) => (),
+            other => panic!("expected duplicate nodes, got {:?}", other.err()),
+        }
+        //open surface does not separate anything from the outside.
+        match Triangulation3::try_from_plc(&nodes, &box_facets(0, false)) {
+            Err(DelaunayError::EmptyDomain) => (),
+            other => panic!("expected empty domain, got {:?}", other.err()),
+        }
+    }
+}
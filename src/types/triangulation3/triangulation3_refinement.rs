@@ -0,0 +1,520 @@
+use super::Triangulation3;
+use super::triangulation3_bw_insertion;
+use super::triangulation3_utilities::triangle_circumcenter;
+use super::triangulation3_utilities::tetrahedron_circumcenter;
+
+use types::*;
+use math::side_of_plane;
+use math::SideOfPlane;
+use math::distance3_squared;
+use algorithms3::nearest_node::elements_around_node;
+use cgmath::InnerSpace;
+
+//border faces meet at a sharp edge when the sine of the angle between their normals is larger.
+const SHARP_EDGE: f64 = 1e-6;
+
+//what to do with an element which is not good enough. a border face is given by an element and
+//the index of the face in it, a segment by such a face and the edge of it.
+enum Step {
+    Insert(T4Index, Point3),
+    SplitFace(T4Index, usize),
+    SplitSegment(T4Index, usize, (N3Index, N3Index)),
+    Skip,
+}
+
+//delaunay refinement of shewchuk. border faces are the boundary and the edges where it bends are
+//its segments. encroached segments and border faces are split, elements with a too large
+//radius-edge ratio get their circumcenter inserted unless it would encroach the boundary, which
+//is then split instead. boundary is not split into pieces smaller than a quarter of the shortest
+//edge it started with, segments and faces meeting at a small angle would encroach each other
+//without end. elements are compacted by the insertions, so they are checked again until nothing
+//changes. returns the number of inserted nodes.
+pub fn refine(triangulation: &mut Triangulation3, options: &Refinement3Options) -> usize {
+    let input_nodes = triangulation.nodes().len();
+    let smallest_split = (0..triangulation.elements().len())
+        .map(|ele_index| shortest_edge_squared(triangulation, T4Index(ele_index)))
+        .fold(::std::f64::MAX, f64::min) / 16.;
+    let mut inserted = 0;
+
+    loop {
+        let inserted_before = inserted;
+        let mut to_check: Vec<T4Index> =
+            (0..triangulation.elements().len()).map(T4Index).collect();
+
+        while let Some(ele_index) = to_check.pop() {
+            if let Some(max_steiner_nodes) = options.max_steiner_nodes {
+                if inserted >= max_steiner_nodes {
+                    return inserted;
+                }
+            }
+
+            if ele_index.0 >= triangulation.elements().len() {
+                continue;
+            }
+
+            let step = match find_encroached(triangulation, ele_index) {
+                Some(step) => step,
+                None if is_bad(triangulation, ele_index, options) => {
+                    find_insertion(triangulation, ele_index)
+                }
+                None => Step::Skip,
+            };
+
+            let new_node = match step {
+                Step::Insert(containing, center) => {
+                    let node_index = N3Index(triangulation.nodes().len());
+                    triangulation.nodes_mut().push(center);
+                    Some((node_index,
+                          triangulation3_bw_insertion::insert_into_element_bw(triangulation,
+                                                                              containing,
+                                                                              node_index)))
+                }
+                Step::SplitFace(face_ele_index, face_index) => {
+                    let (split_ele_index, split_face_index, split_at) =
+                        find_face_split(triangulation, face_ele_index, face_index, input_nodes);
+                    insert_on_border(triangulation,
+                                     split_ele_index,
+                                     split_face_index,
+                                     split_at,
+                                     smallest_split)
+                }
+                Step::SplitSegment(face_ele_index, face_index, edge) => {
+                    let split_at = split_point(triangulation, edge, input_nodes);
+                    insert_on_border(triangulation,
+                                     face_ele_index,
+                                     face_index,
+                                     split_at,
+                                     smallest_split)
+                }
+                Step::Skip => None,
+            };
+
+            if let Some((node_index, near)) = new_node {
+                inserted += 1;
+                to_check.push(ele_index);
+                to_check.extend(elements_around_node(near, triangulation.elements(), node_index));
+            }
+        }
+
+        if inserted == inserted_before {
+            return inserted;
+        }
+    }
+}
+
+//node is put on the border face even when rounding moved it off the plane. it is not inserted
+//closer than the smallest split to the nodes of the elements it would replace, which hold the
+//nearest node.
+fn insert_on_border(triangulation: &mut Triangulation3,
+                    ele_index: T4Index,
+                    face_index: usize,
+                    p: Point3,
+                    smallest_split: f64)
+                    -> Option<(N3Index, T4Index)> {
+    let too_close = elements_replaced_by(triangulation, ele_index, &p).iter().any(|near_index| {
+        triangulation.elements()[near_index.0]
+            .nodes()
+            .iter()
+            .any(|n| distance3_squared(&triangulation.nodes()[n.0], &p) < smallest_split)
+    });
+    if too_close {
+        return None;
+    }
+
+    let node_index = N3Index(triangulation.nodes().len());
+    triangulation.nodes_mut().push(p);
+
+    Some((node_index,
+          triangulation3_bw_insertion::insert_on_border_bw(triangulation,
+                                                           ele_index,
+                                                           face_index,
+                                                           node_index)))
+}
+
+#[inline]
+fn middle(triangulation: &Triangulation3, edge: (N3Index, N3Index)) -> Point3 {
+    let (a, b) = (&triangulation.nodes()[(edge.0).0], &triangulation.nodes()[(edge.1).0]);
+    Point3::new((a.x + b.x) / 2., (a.y + b.y) / 2., (a.z + b.z) / 2.)
+}
+
+//segments between two input nodes or two inserted nodes are split in the middle. segment from an
+//input node is split at a power of two distance from it, so that segments meeting at a small
+//angle are split at the same distances and stop encroaching each other.
+fn split_point(triangulation: &Triangulation3,
+               edge: (N3Index, N3Index),
+               input_nodes: usize)
+               -> Point3 {
+    let (first_is_input, second_is_input) = ((edge.0).0 < input_nodes, (edge.1).0 < input_nodes);
+    if first_is_input == second_is_input {
+        return middle(triangulation, edge);
+    }
+
+    let nodes = triangulation.nodes();
+    let (apex, other) = if first_is_input {
+        (&nodes[(edge.0).0], &nodes[(edge.1).0])
+    } else {
+        (&nodes[(edge.1).0], &nodes[(edge.0).0])
+    };
+    let length = distance3_squared(apex, other).sqrt();
+    let shell = 2f64.powf((length / 2.).log2().round());
+
+    apex + (other - apex) * (shell / length)
+}
+
+//point encroaches a segment when it lies inside of the sphere with the segment as diameter.
+#[inline]
+fn encroaches_segment(a: &Point3, b: &Point3, p: &Point3) -> bool {
+    (a - p).dot(b - p) < 0.
+}
+
+//point encroaches a border face when it lies inside of the smallest sphere through its nodes.
+#[inline]
+fn encroaches_face(a: &Point3, b: &Point3, c: &Point3, p: &Point3) -> bool {
+    let center = triangle_circumcenter(a, b, c);
+    distance3_squared(&center, p) < distance3_squared(&center, a)
+}
+
+fn is_segment(triangulation: &Triangulation3,
+              ele_index: T4Index,
+              face_index: usize,
+              edge: (N3Index, N3Index))
+              -> bool {
+    let (other_ele_index, other_face_index) =
+        triangulation3_bw_insertion::find_border_face_across_edge(triangulation,
+                                                                  ele_index,
+                                                                  face_index,
+                                                                  edge.0,
+                                                                  edge.1);
+    let normal = face_normal(triangulation, ele_index, face_index);
+    let other_normal = face_normal(triangulation, other_ele_index, other_face_index);
+
+    normal.dot(other_normal) <= 0. ||
+    normal.cross(other_normal).magnitude() >
+    SHARP_EDGE * normal.magnitude() * other_normal.magnitude()
+}
+
+#[inline]
+fn face_normal(triangulation: &Triangulation3,
+               ele_index: T4Index,
+               face_index: usize)
+               -> ::cgmath::Vector3<f64> {
+    let face = triangulation.elements()[ele_index.0]
+        .faces_as_points_tuples(triangulation.nodes())[face_index];
+    (face.1 - face.0).cross(face.2 - face.0)
+}
+
+#[inline]
+fn face_edges(face: (N3Index, N3Index, N3Index)) -> [(N3Index, N3Index); 3] {
+    [(face.0, face.1), (face.1, face.2), (face.2, face.0)]
+}
+
+//segment or border face of the element encroached by its other nodes.
+fn find_encroached(triangulation: &Triangulation3, ele_index: T4Index) -> Option<Step> {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let faces = ele.faces_as_indices_tuples();
+    let border_faces: Vec<usize> =
+        (0..4).filter(|f| ele.get_neighbor_from_index(*f).is_none()).collect();
+
+    for &face_index in border_faces.iter() {
+        for &edge in face_edges(faces[face_index]).iter() {
+            let encroached = ele.nodes().iter().any(|n| {
+                *n != edge.0 && *n != edge.1 &&
+                encroaches_segment(&nodes[(edge.0).0], &nodes[(edge.1).0], &nodes[n.0])
+            });
+
+            if encroached && is_segment(triangulation, ele_index, face_index, edge) {
+                return Some(Step::SplitSegment(ele_index, face_index, edge));
+            }
+        }
+    }
+
+    for &face_index in border_faces.iter() {
+        let face = faces[face_index];
+        let opposite = ele.nodes()
+            .iter()
+            .find(|n| **n != face.0 && **n != face.1 && **n != face.2)
+            .expect("Tetrahedron has a node outside of its face.");
+
+        if encroaches_face(&nodes[(face.0).0],
+                           &nodes[(face.1).0],
+                           &nodes[(face.2).0],
+                           &nodes[opposite.0]) {
+            return Some(Step::SplitFace(ele_index, face_index));
+        }
+    }
+
+    None
+}
+
+fn shortest_edge_squared(triangulation: &Triangulation3, ele_index: T4Index) -> f64 {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let mut shortest = ::std::f64::MAX;
+
+    for i in 0..4 {
+        for j in (i + 1)..4 {
+            shortest = shortest.min(distance3_squared(&nodes[ele.nodes()[i].0],
+                                                      &nodes[ele.nodes()[j].0]));
+        }
+    }
+
+    shortest
+}
+
+fn is_bad(triangulation: &Triangulation3,
+          ele_index: T4Index,
+          options: &Refinement3Options)
+          -> bool {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let center = tetrahedron_circumcenter(ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes));
+
+    distance3_squared(&center, ele.a(nodes)) >
+    options.max_radius_edge_ratio * options.max_radius_edge_ratio *
+    shortest_edge_squared(triangulation, ele_index)
+}
+
+//walks the elements whose circumsphere contains the circumcenter of the bad element without
+//crossing the border. those are the elements it would replace, so their border faces and segments
+//are the ones it could encroach. when it lies in front of one of those faces, the face separates
+//it from the bad element. the boundary is split only into pieces longer than the shortest edge of
+//the bad element, smaller ones come from small angles of the input and splitting them would never
+//end.
+fn find_insertion(triangulation: &Triangulation3, bad_ele_index: T4Index) -> Step {
+    let center = {
+        let ele = &triangulation.elements()[bad_ele_index.0];
+        let nodes = triangulation.nodes();
+        tetrahedron_circumcenter(ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes))
+    };
+    let shortest = shortest_edge_squared(triangulation, bad_ele_index);
+
+    let mut containing = None;
+    let mut separating = None;
+
+    for ele_index in elements_replaced_by(triangulation, bad_ele_index, &center) {
+        let ele = &triangulation.elements()[ele_index.0];
+        let nodes = triangulation.nodes();
+
+        if containing.is_none() && !ele.is_point_outside(&center, nodes) {
+            containing = Some(ele_index);
+        }
+
+        for (face_index, face) in ele.faces_as_points_tuples(nodes).iter().enumerate() {
+            if ele.get_neighbor_from_index(face_index).is_some() {
+                continue;
+            }
+
+            let face_nodes = ele.faces_as_indices_tuples()[face_index];
+            for &edge in face_edges(face_nodes).iter() {
+                let (a, b) = (&nodes[(edge.0).0], &nodes[(edge.1).0]);
+                if encroaches_segment(a, b, &center) &&
+                   is_segment(triangulation, ele_index, face_index, edge) {
+                    if distance3_squared(a, b) < 4. * shortest {
+                        return Step::Skip;
+                    }
+                    return Step::SplitSegment(ele_index, face_index, edge);
+                }
+            }
+
+            if encroaches_face(face.0, face.1, face.2, &center) {
+                return split_face_larger_than(face, shortest, ele_index, face_index);
+            }
+            if separating.is_none() &&
+               side_of_plane(face.0, face.1, face.2, &center) == SideOfPlane::Left {
+                separating = Some((ele_index, face_index, *face));
+            }
+        }
+    }
+
+    match (containing, separating) {
+        (_, Some((ele_index, face_index, face))) => {
+            split_face_larger_than(&face, shortest, ele_index, face_index)
+        }
+        (Some(ele_index), None) => Step::Insert(ele_index, center),
+        (None, None) => Step::Skip,
+    }
+}
+
+#[inline]
+fn split_face_larger_than(face: &(&Point3, &Point3, &Point3),
+                          shortest: f64,
+                          ele_index: T4Index,
+                          face_index: usize)
+                          -> Step {
+    let center = triangle_circumcenter(face.0, face.1, face.2);
+    if distance3_squared(&center, face.0) < shortest {
+        Step::Skip
+    } else {
+        Step::SplitFace(ele_index, face_index)
+    }
+}
+
+//circumcenter of the border face, or the middle of a segment it encroaches. when the circumcenter
+//lies outside of the face, its longest edge is split.
+fn find_face_split(triangulation: &Triangulation3,
+                   ele_index: T4Index,
+                   face_index: usize,
+                   input_nodes: usize)
+                   -> (T4Index, usize, Point3) {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    let face = ele.faces_as_points_tuples(nodes)[face_index];
+    let face_nodes = ele.faces_as_indices_tuples()[face_index];
+    let center = triangle_circumcenter(face.0, face.1, face.2);
+
+    let normal = (face.1 - face.0).cross(face.2 - face.0);
+    let inside = (face.1 - face.0).cross(center - face.0).dot(normal) > 0. &&
+                 (face.2 - face.1).cross(center - face.1).dot(normal) > 0. &&
+                 (face.0 - face.2).cross(center - face.2).dot(normal) > 0.;
+
+    if !inside {
+        let longest = face_edges(face_nodes)
+            .iter()
+            .cloned()
+            .max_by(|a, b| {
+                distance3_squared(&nodes[(a.0).0], &nodes[(a.1).0])
+                    .partial_cmp(&distance3_squared(&nodes[(b.0).0], &nodes[(b.1).0]))
+                    .unwrap_or(::std::cmp::Ordering::Equal)
+            })
+            .expect("Face has no edges.");
+        return (ele_index, face_index, middle(triangulation, longest));
+    }
+
+    for near_index in elements_replaced_by(triangulation, ele_index, &center) {
+        let near = &triangulation.elements()[near_index.0];
+        for (near_face_index, near_face) in near.faces_as_indices_tuples().iter().enumerate() {
+            if near.get_neighbor_from_index(near_face_index).is_some() {
+                continue;
+            }
+
+            for &edge in face_edges(*near_face).iter() {
+                if encroaches_segment(&nodes[(edge.0).0], &nodes[(edge.1).0], &center) &&
+                   is_segment(triangulation, near_index, near_face_index, edge) {
+                    return (near_index,
+                            near_face_index,
+                            split_point(triangulation, edge, input_nodes));
+                }
+            }
+        }
+    }
+
+    (ele_index, face_index, center)
+}
+
+//elements whose circumsphere contains the point, reachable from the first one without crossing
+//the border.
+fn elements_replaced_by(triangulation: &Triangulation3,
+                        first: T4Index,
+                        p: &Point3)
+                        -> Vec<T4Index> {
+    let mut visited = vec![first];
+    let mut position = 0;
+
+    while position < visited.len() {
+        let ele = &triangulation.elements()[visited[position].0];
+        position += 1;
+
+        for neighbor_index in ele.neighbors().iter().filter_map(|n| *n) {
+            if !visited.contains(&neighbor_index) &&
+               triangulation.elements()[neighbor_index.0]
+                .is_point_in_circumsphere(p, triangulation.nodes()) {
+                visited.push(neighbor_index);
+            }
+        }
+    }
+
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::volume;
+    use types::triangulation3_test_utils::random_points;
+    use super::super::triangulation3_utilities::tetrahedron_circumcenter;
+    use cgmath::InnerSpace;
+
+    //corner i of the cube lies at x = i & 1, y = i & 2, z = i & 4.
+    fn cube_nodes() -> Vec<Point3> {
+        (0..8)
+            .map(|i| {
+                let coordinate = |bit| if i & bit == 0 { 0. } else { 1. };
+                Point3::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect()
+    }
+
+    fn worst_radius_edge_ratio(tr: &Triangulation3) -> f64 {
+        let nodes = tr.nodes();
+        let mut worst = 0f64;
+        for e in tr.elements() {
+            let points = [e.a(nodes), e.b(nodes), e.c(nodes), e.d(nodes)];
+            let center = tetrahedron_circumcenter(points[0], points[1], points[2], points[3]);
+            let mut shortest = ::std::f64::MAX;
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    shortest = shortest.min((points[i] - points[j]).magnitude());
+                }
+            }
+            worst = worst.max((center - points[0]).magnitude() / shortest);
+        }
+        worst
+    }
+
+    #[test]
+    fn refining_cube() {
+        let mut nodes = cube_nodes();
+        nodes.push(Point3::new(0.3, 0.4, 0.6));
+        let mut triangulation = Triangulation3::new(&nodes);
+
+        let inserted = triangulation.refine(&Refinement3Options::default());
+
+        assert!(inserted > 0);
+        assert_eq!(nodes.len() + inserted, triangulation.nodes().len());
+        assert!(worst_radius_edge_ratio(&triangulation) <= 2.);
+        assert!((volume(&triangulation) - 1.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+
+        //nothing is left to refine.
+        assert_eq!(0, triangulation.refine(&Refinement3Options::default()));
+    }
+
+    #[test]
+    fn refining_dented_cube() {
+        let mut nodes = cube_nodes();
+        nodes.push(Point3::new(0.5, 0.5, 0.5));
+        let mut facets = Vec::new();
+        for q in [[0, 2, 6, 4], [1, 3, 7, 5], [0, 1, 5, 4], [2, 3, 7, 6], [0, 1, 3, 2]].iter() {
+            facets.push([N3Index(q[0]), N3Index(q[1]), N3Index(q[2])]);
+            facets.push([N3Index(q[0]), N3Index(q[2]), N3Index(q[3])]);
+        }
+        for &(first, second) in [(4, 5), (5, 7), (7, 6), (6, 4)].iter() {
+            facets.push([N3Index(first), N3Index(second), N3Index(8)]);
+        }
+        let mut triangulation = Triangulation3::from_plc(&nodes, &facets);
+
+        triangulation.refine(&Refinement3Options::default());
+
+        assert!(worst_radius_edge_ratio(&triangulation) <= 2.);
+        assert!((volume(&triangulation) - 5. / 6.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn refining_stops_at_node_budget() {
+        let mut nodes = cube_nodes();
+        nodes.extend(random_points(7, 50));
+        let mut triangulation = Triangulation3::new(&nodes);
+        let options = Refinement3Options {
+            max_steiner_nodes: Some(10),
+            ..Refinement3Options::default()
+        };
+
+        assert_eq!(10, triangulation.refine(&options));
+        assert_eq!(nodes.len() + 10, triangulation.nodes().len());
+        assert!((volume(&triangulation) - 1.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+}
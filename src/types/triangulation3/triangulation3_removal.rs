@@ -0,0 +1,312 @@
+use super::Triangulation3;
+
+use types::*;
+use math::side_of_plane;
+use algorithms3::nearest_node::elements_around_node;
+use algorithms3::sort_3::sort_3;
+
+use std::collections::BTreeMap;
+
+//removes the elements around the node and fills the hole with the elements of the delaunay
+//triangulation of the nodes around it which lie inside of the hole. with the same perturbation
+//those are exactly the elements missing from the triangulation of the remaining nodes.
+//new elements reuse the freed slots, the rest of the slots are compacted. returns an element next
+//to the removed node. border of a triangulation restricted to a domain is not convex, so its nodes
+//can not be removed.
+pub fn remove_node(triangulation: &mut Triangulation3,
+                   element_index: T4Index,
+                   node: N3Index,
+                   restricted: bool)
+                   -> Result<T4Index, DelaunayError> {
+    let mut star = elements_around_node(element_index, triangulation.elements(), node);
+
+    if restricted &&
+       star.iter().any(|ele_index| {
+        let ele: &Tetrahedron = &triangulation.elements()[ele_index.0];
+        ele.faces_as_indices_tuples()
+            .iter()
+            .enumerate()
+            .any(|(face_index, face)| {
+                (face.0 == node || face.1 == node || face.2 == node) &&
+                ele.get_neighbor_from_index(face_index).is_none()
+            })
+    }) {
+        return Err(DelaunayError::NodeOnDomainBorder(node.0));
+    }
+
+    //faces of the hole, with the element behind them.
+    let mut hole_faces = BTreeMap::new();
+    for ele_index in star.iter() {
+        let ele: &Tetrahedron = &triangulation.elements()[ele_index.0];
+        for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+            if face.0 != node && face.1 != node && face.2 != node {
+                hole_faces.insert(sort_3(face.0, face.1, face.2),
+                                  ele.get_neighbor_from_index(face_index));
+            }
+        }
+    }
+
+    //ascending global indexes keep the perturbation of the local triangulation the same.
+    let mut around: Vec<N3Index> = Vec::new();
+    for face in hole_faces.keys() {
+        around.push(face.0);
+        around.push(face.1);
+        around.push(face.2);
+    }
+    around.sort();
+    around.dedup();
+
+    let around_nodes: Vec<Point3> = around.iter().map(|n| triangulation.nodes()[n.0]).collect();
+    let filling = match Triangulation3::try_new(&around_nodes) {
+        Ok(local) => select_inside_of_hole(triangulation, &local, &around, &hole_faces, node),
+        Err(DelaunayError::CoplanarNodes) if star.len() < triangulation.elements().len() => {
+            Vec::new()
+        }
+        Err(error) => return Err(error),
+    };
+
+    if star.len() == triangulation.elements().len() && filling.is_empty() {
+        return Err(DelaunayError::CoplanarNodes);
+    }
+
+    star.sort();
+    let mut slots = Vec::with_capacity(filling.len());
+    for position in 0..filling.len() {
+        if position < star.len() {
+            slots.push(star[position]);
+        } else {
+            slots.push(T4Index(triangulation.elements().len() + position - star.len()));
+        }
+    }
+
+    for _ in star.len()..filling.len() {
+        triangulation.elements_mut().push(Tetrahedron::new_exact([node; 4], [None; 4]));
+    }
+
+    for (position, &(ref v, ref n)) in filling.iter().enumerate() {
+        let mut neighbors = [None; 4];
+        let faces = Tetrahedron::new_exact(*v, [None; 4]).faces_as_indices_tuples();
+
+        for face_index in 0..4 {
+            let (n1, n2, n3) = faces[face_index];
+            neighbors[face_index] = match n[face_index] {
+                Some(filling_position) => Some(slots[filling_position]),
+                None => {
+                    let outside = hole_faces.remove(&sort_3(n1, n2, n3)).and_then(|o| o);
+                    if let Some(outside) = outside {
+                        triangulation.elements_mut()[outside.0]
+                            .update_neighbor(n1, n2, n3, Some(slots[position]));
+                    }
+                    outside
+                }
+            };
+        }
+
+        triangulation.elements_mut()[slots[position].0] = Tetrahedron::new_exact(*v, neighbors);
+    }
+
+    //faces of the hole which were not filled became a part of the convex hull.
+    let mut near_element = slots.first().cloned();
+    for (face, outside) in hole_faces.into_iter() {
+        if let Some(outside) = outside {
+            triangulation.elements_mut()[outside.0].update_neighbor(face.0, face.1, face.2, None);
+            near_element = near_element.or(Some(outside));
+        }
+    }
+
+    let mut near_element = near_element.expect("Removal left no elements.");
+    let mut unused_slots: Vec<T4Index> = star.into_iter().skip(filling.len()).collect();
+    unused_slots.sort_by(|a, b| b.cmp(a));
+
+    for slot in unused_slots {
+        let moved = remove_element(triangulation, slot);
+        if moved == Some(near_element) {
+            near_element = slot;
+        }
+    }
+
+    Ok(near_element)
+}
+
+//elements of the local triangulation reachable from the inner side of the hole faces without
+//crossing them, as global nodes and positions of the neighbors among the selected elements.
+fn select_inside_of_hole(triangulation: &Triangulation3,
+                         local: &Triangulation3,
+                         around: &[N3Index],
+                         hole_faces: &BTreeMap<(N3Index, N3Index, N3Index), Option<T4Index>>,
+                         node: N3Index)
+                         -> Vec<([N3Index; 4], [Option<usize>; 4])> {
+    let nodes = triangulation.nodes();
+    let global = |local_node: &N3Index| around[local_node.0];
+    let is_hole_face = |face: &(N3Index, N3Index, N3Index)| {
+        hole_faces.contains_key(&sort_3(global(&face.0), global(&face.1), global(&face.2)))
+    };
+
+    let mut selected: Vec<Option<usize>> = vec![None; local.elements().len()];
+    let mut order = Vec::new();
+
+    for (ele_index, ele) in local.elements().iter().enumerate() {
+        for face in ele.faces_as_indices_tuples().iter() {
+            if !is_hole_face(face) {
+                continue;
+            }
+
+            let last = ele.nodes()
+                .iter()
+                .find(|n| **n != face.0 && **n != face.1 && **n != face.2)
+                .expect("Element has less than four nodes.");
+            let (a, b, c) = (&nodes[global(&face.0).0],
+                             &nodes[global(&face.1).0],
+                             &nodes[global(&face.2).0]);
+
+            if side_of_plane(a, b, c, &nodes[global(last).0]) ==
+               side_of_plane(a, b, c, &nodes[node.0]) && selected[ele_index].is_none() {
+                selected[ele_index] = Some(order.len());
+                order.push(T4Index(ele_index));
+            }
+        }
+    }
+
+    let mut position = 0;
+    while position < order.len() {
+        let ele = &local.elements()[order[position].0];
+        position += 1;
+
+        for (face_index, face) in ele.faces_as_indices_tuples().iter().enumerate() {
+            if is_hole_face(face) {
+                continue;
+            }
+
+            if let Some(neighbor_index) = ele.get_neighbor_from_index(face_index) {
+                if selected[neighbor_index.0].is_none() {
+                    selected[neighbor_index.0] = Some(order.len());
+                    order.push(neighbor_index);
+                }
+            }
+        }
+    }
+
+    order.iter()
+        .map(|ele_index| {
+            let ele = &local.elements()[ele_index.0];
+            let v = ele.nodes();
+            let mut n = [None; 4];
+            for face_index in 0..4 {
+                let face = ele.faces_as_indices_tuples()[face_index];
+                if !is_hole_face(&face) {
+                    n[face_index] = ele.get_neighbor_from_index(face_index)
+                        .and_then(|neighbor_index| selected[neighbor_index.0]);
+                }
+            }
+
+            ([global(&v[0]), global(&v[1]), global(&v[2]), global(&v[3])], n)
+        })
+        .collect()
+}
+
+//moves the last element into the slot. returns the previous index of the moved element.
+pub fn remove_element(triangulation: &mut Triangulation3, slot: T4Index) -> Option<T4Index> {
+    let last = T4Index(triangulation.elements().len() - 1);
+    triangulation.elements_mut().swap_remove(slot.0);
+
+    if slot == last {
+        return None;
+    }
+
+    let neighbors = *triangulation.elements()[slot.0].neighbors();
+    for neighbor_index in neighbors.iter().filter_map(|n| *n) {
+        let neighbor: &mut Tetrahedron = &mut triangulation.elements_mut()[neighbor_index.0];
+        for i in 0..4 {
+            if neighbor.get_neighbor_from_index(i) == Some(last) {
+                neighbor.set_neighbor(i, Some(slot));
+            }
+        }
+    }
+
+    Some(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::sorted_elements;
+    use types::triangulation3_test_utils::sorted_renumbered_elements;
+
+    #[test]
+    fn removing_inner_node_of_cube() {
+        let mut nodes = triangulation3_test_utils::get_example_initial_point_set();
+        nodes.push(Point3::new(0.4, 0.6, 0.45));
+
+        let mut triangulation = Triangulation3::new(&nodes);
+        triangulation.remove_node(N3Index(8));
+
+        let cube = Triangulation3::new(&nodes[..8]);
+        assert_eq!(9, triangulation.nodes().len());
+        assert_eq!(sorted_elements(&cube),
+                   sorted_elements(&triangulation));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_nodes_of_grid_gives_delaunay_triangulation_of_the_rest() {
+        //grid has many cospherical nodes, removed are corners, border and inner nodes.
+        let mut grid = Vec::new();
+        for i in 0..27 {
+            let position = (i * 7) % 27;
+            grid.push(Point3::new((position % 3) as f64,
+                                  ((position / 3) % 3) as f64,
+                                  (position / 9) as f64));
+        }
+
+        let mut triangulation = Triangulation3::new(&grid);
+        let mut removed = vec![false; grid.len()];
+
+        for node in [0, 13, 5, 22, 9, 1, 17].iter() {
+            triangulation.remove_node(N3Index(*node));
+            removed[*node] = true;
+            assert_neighborhood(&triangulation);
+
+            let mut renumbered = vec![0; grid.len()];
+            let mut rest = Vec::new();
+            for index in 0..grid.len() {
+                if !removed[index] {
+                    renumbered[index] = rest.len();
+                    rest.push(grid[index]);
+                }
+            }
+
+            let rebuilt = Triangulation3::new(&rest);
+            assert_eq!(sorted_elements(&rebuilt),
+                       sorted_renumbered_elements(&triangulation, &renumbered));
+        }
+
+        assert_eq!(N3Index(27),
+                   triangulation.insert_node(&Point3::new(0.5, 1.5, 0.5)));
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_node_which_is_not_in_triangulation() {
+        let cube = triangulation3_test_utils::get_example_initial_point_set();
+        let nodes = [cube[0], cube[1], cube[3], cube[4], cube[6]];
+        let mut triangulation = Triangulation3::new(&nodes);
+
+        triangulation.remove_node(N3Index(4));
+        assert_eq!(1, triangulation.elements().len());
+
+        match triangulation.try_remove_node(N3Index(4)) {
+            Err(DelaunayError::UnknownNode(4)) => (),
+            other => panic!("expected unknown node, got {:?}", other),
+        }
+        match triangulation.try_remove_node(N3Index(9)) {
+            Err(DelaunayError::UnknownNode(9)) => (),
+            other => panic!("expected unknown node, got {:?}", other),
+        }
+        match triangulation.try_remove_node(N3Index(1)) {
+            Err(DelaunayError::CoplanarNodes) => (),
+            other => panic!("expected coplanar nodes, got {:?}", other),
+        }
+        assert_eq!(1, triangulation.elements().len());
+    }
+}
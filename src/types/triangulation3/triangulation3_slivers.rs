@@ -0,0 +1,163 @@
+use super::Triangulation3;
+use super::triangulation3_flips;
+use super::triangulation3_flips::PlannedFlip;
+use super::triangulation3_utilities::min_dihedral_angle;
+
+use types::*;
+
+pub fn dihedral_histogram(triangulation: &Triangulation3) -> DihedralHistogram {
+    let mut histogram = DihedralHistogram::default();
+    for ele_index in 0..triangulation.elements().len() {
+        histogram.add(element_min_dihedral_angle(triangulation, T4Index(ele_index)));
+    }
+    histogram
+}
+
+//slivers have well placed nodes and a small radius-edge ratio, but their nodes lie near a
+//circle, so they are flat. each sliver is replaced by the 2-3, 3-2 or 4-4 flip whose new
+//elements have the largest smallest dihedral angle, if it is larger than the smallest one of the
+//replaced elements. every flip raises the sorted angles of the triangulation, so the passes end.
+//no nodes are inserted, slivers which no flip improves stay. returns the number of flips.
+pub fn remove_slivers(triangulation: &mut Triangulation3, options: &Refinement3Options) -> usize {
+    let mut flips = 0;
+
+    loop {
+        let flips_before = flips;
+        let mut ele_index = 0;
+
+        while ele_index < triangulation.elements().len() {
+            let angle = element_min_dihedral_angle(triangulation, T4Index(ele_index));
+            if angle < options.min_dihedral_angle {
+                if let Some(planned) = best_flip(triangulation, T4Index(ele_index)) {
+                    triangulation3_flips::do_flip(triangulation, planned);
+                    flips += 1;
+                }
+            }
+            ele_index += 1;
+        }
+
+        if flips == flips_before {
+            return flips;
+        }
+    }
+}
+
+//flip of the sliver giving the best new elements, none when no flip improves them.
+fn best_flip(triangulation: &Triangulation3, sliver_index: T4Index) -> Option<PlannedFlip> {
+    let nodes = triangulation.nodes();
+    let mut best = None;
+    let mut best_angle = 0.;
+
+    for planned in triangulation3_flips::plan_flips_of_element(triangulation, sliver_index) {
+        let replaced_angle = planned.replaced
+            .iter()
+            .map(|ele_index| element_min_dihedral_angle(triangulation, *ele_index))
+            .fold(::std::f64::MAX, f64::min);
+        let angle = planned.elements
+            .iter()
+            .map(|e| min_dihedral_angle(e.a(nodes), e.b(nodes), e.c(nodes), e.d(nodes)))
+            .fold(::std::f64::MAX, f64::min);
+
+        if angle > replaced_angle && angle > best_angle {
+            best_angle = angle;
+            best = Some(planned);
+        }
+    }
+
+    best
+}
+
+fn element_min_dihedral_angle(triangulation: &Triangulation3, ele_index: T4Index) -> f64 {
+    let ele = &triangulation.elements()[ele_index.0];
+    let nodes = triangulation.nodes();
+    min_dihedral_angle(ele.a(nodes), ele.b(nodes), ele.c(nodes), ele.d(nodes))
+}
+
+#[cfg(test)]
+mod tests {
+    use types::*;
+    use types::triangulation3_test_utils::assert_neighborhood;
+    use types::triangulation3_test_utils::volume;
+    use types::triangulation3_test_utils::random_points;
+
+    //corners of the unit cube and random nodes inside of it, refined to a good radius-edge
+    //ratio, which leaves slivers.
+    fn refined_cube() -> Triangulation3 {
+        let mut nodes: Vec<Point3> = (0..8)
+            .map(|i| {
+                let coordinate = |bit| if i & bit == 0 { 0. } else { 1. };
+                Point3::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect();
+        nodes.extend(random_points(7, 50));
+
+        let mut triangulation = Triangulation3::new(&nodes);
+        triangulation.refine(&Refinement3Options::default());
+        triangulation
+    }
+
+    #[test]
+    fn removing_slivers_of_refined_cube() {
+        let mut triangulation = refined_cube();
+        let elements_before = triangulation.elements().len();
+        let nodes_before = triangulation.nodes().len();
+        let options = Refinement3Options::default();
+
+        let removal = triangulation.remove_slivers(&options);
+
+        assert_eq!(elements_before, removal.before.total());
+        assert_eq!(triangulation.elements().len(), removal.after.total());
+        assert_eq!(nodes_before, triangulation.nodes().len());
+        assert!(removal.flips > 0);
+        assert!(removal.before.count_below(options.min_dihedral_angle) > 0);
+        assert!(removal.after.count_below(options.min_dihedral_angle) <
+                removal.before.count_below(options.min_dihedral_angle) / 2);
+        assert_eq!(removal.after, triangulation.dihedral_histogram());
+        assert!((volume(&triangulation) - 1.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn removing_slivers_ends_when_no_flip_improves() {
+        let mut triangulation = refined_cube();
+        let options = Refinement3Options::default();
+        triangulation.remove_slivers(&options);
+
+        let removal = triangulation.remove_slivers(&options);
+
+        assert_eq!(0, removal.flips);
+        assert_eq!(removal.before, removal.after);
+        assert!((volume(&triangulation) - 1.).abs() < 1e-10);
+        assert_neighborhood(&triangulation);
+    }
+
+    #[test]
+    fn flipping_flat_bipyramid() {
+        //two flat elements share the triangle 0-1-2, the 2-3 flip replaces them by three elements
+        //around the edge between the apexes.
+        let mut nodes: Vec<Point3> = (0..3)
+            .map(|i| {
+                let angle = i as f64 * 2. * ::std::f64::consts::PI / 3.;
+                Point3::new(angle.cos(), angle.sin(), 0.)
+            })
+            .collect();
+        nodes.push(Point3::new(0., 0., 0.05));
+        nodes.push(Point3::new(0., 0., -0.05));
+        let elements = [3, 4]
+            .iter()
+            .map(|&apex| {
+                Tetrahedron::new(&nodes, N3Index(0), N3Index(1), N3Index(2), N3Index(apex))
+            })
+            .collect();
+        let mut triangulation = Triangulation3::new_from_prebuilt_triangulation(nodes, elements);
+        let options = Refinement3Options::default();
+
+        let removal = triangulation.remove_slivers(&options);
+
+        assert_eq!(1, removal.flips);
+        assert_eq!(2, removal.before.count_below(options.min_dihedral_angle));
+        assert_eq!(0, removal.after.count_below(options.min_dihedral_angle));
+        assert_eq!(3, triangulation.elements().len());
+        assert_neighborhood(&triangulation);
+    }
+}
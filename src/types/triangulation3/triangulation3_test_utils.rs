@@ -1,4 +1,15 @@
 use types::Point3;
+use types::Tetrahedron;
+use types::Triangulation3;
+use super::triangulation3_neighborhood::Triangulation3Neighborhood;
+use cgmath::InnerSpace;
+
+#[cfg(test)]
+use types::T4Index;
+#[cfg(test)]
+use rand::distributions::{IndependentSample, Range};
+#[cfg(test)]
+use rand::{SeedableRng, StdRng};
 
 pub fn get_example_initial_point_set() -> Vec<Point3> {
     vec![Point3::new(0., 0., 0.),
@@ -10,3 +21,81 @@ pub fn get_example_initial_point_set() -> Vec<Point3> {
          Point3::new(1., 1., 1.),
          Point3::new(1., 0., 1.)]
 }
+
+//nodes of the elements, each element and the list sorted, so triangulations built in a different
+//order can be compared.
+pub fn sorted_elements(tr: &Triangulation3) -> Vec<Vec<usize>> {
+    let identity: Vec<usize> = (0..tr.nodes().len()).collect();
+    sorted_renumbered_elements(tr, &identity)
+}
+
+//same as sorted_elements, with node i replaced by renumbered[i].
+pub fn sorted_renumbered_elements(tr: &Triangulation3, renumbered: &[usize]) -> Vec<Vec<usize>> {
+    let mut elements: Vec<Vec<usize>> = tr.elements()
+        .iter()
+        .map(|e| {
+            let mut v: Vec<usize> = e.nodes().iter().map(|n| renumbered[n.0]).collect();
+            v.sort();
+            v
+        })
+        .collect();
+    elements.sort();
+    elements
+}
+
+//neighbors are the ones found again from the nodes of the elements.
+pub fn assert_neighborhood(tr: &Triangulation3) {
+    let mut relearned: Vec<Tetrahedron> = tr.elements()
+        .iter()
+        .map(|e| Tetrahedron::new_exact(*e.nodes(), [None; 4]))
+        .collect();
+    Triangulation3Neighborhood::teach_triangles_of_neighborhood(&mut relearned);
+
+    assert_eq!(relearned, *tr.elements());
+}
+
+//sum of the volumes of the elements, which is the volume of the domain when they do not overlap.
+pub fn volume(tr: &Triangulation3) -> f64 {
+    let nodes = tr.nodes();
+    tr.elements()
+        .iter()
+        .map(|e| {
+            let (a, b, c, d) = (e.a(nodes), e.b(nodes), e.c(nodes), e.d(nodes));
+            (b - a).cross(c - a).dot(d - a).abs() / 6.
+        })
+        .sum()
+}
+
+//count points in the unit cube, the same ones for the same seed.
+#[cfg(test)]
+pub fn random_points(seed: usize, count: usize) -> Vec<Point3> {
+    let seed: &[_] = &[seed];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let between = Range::new(0., 1.);
+
+    (0..count)
+        .map(|_| {
+            Point3::new(between.ind_sample(&mut rng),
+                        between.ind_sample(&mut rng),
+                        between.ind_sample(&mut rng))
+        })
+        .collect()
+}
+
+//triangulation of random points in the unit cube where faces were flipped wherever they could,
+//so it is not delaunay.
+#[cfg(test)]
+pub fn randomly_flipped(seed: usize, count: usize) -> Triangulation3 {
+    let mut triangulation = Triangulation3::new(&random_points(seed, count));
+
+    let mut ele_index = 0;
+    while ele_index < triangulation.elements().len() {
+        for face_index in 0..4 {
+            if triangulation.try_flip23(T4Index(ele_index), face_index).is_ok() {
+                break;
+            }
+        }
+        ele_index += 3;
+    }
+    triangulation
+}
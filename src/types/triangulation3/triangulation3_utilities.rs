@@ -1,77 +1,113 @@
 use types::Point3;
+use types::InsertionOrder;
+use math::side_of_plane;
+use math::SideOfPlane;
+use math::brio_order;
+use math::hilbert_index;
+use math::morton_index;
+use math::to_curve_coordinate;
+use cgmath::InnerSpace;
 
-pub fn find_corner_nodes3(nodes: &[Point3])
-                          -> [usize;8] {
-    let (mut top_left_front_index,
-         mut top_right_front_index,
-         mut bottom_left_front_index,
-         mut bottom_right_front_index,
-         mut top_left_back_index,
-         mut top_right_back_index,
-         mut bottom_left_back_index,
-         mut bottom_right_back_index) =
-        (0usize, 0usize, 0usize, 0usize, 0usize, 0usize, 0usize, 0usize);
-
-    for (index, candidate) in nodes.iter().enumerate() {
-        let current_top_left_front = nodes[top_left_front_index];
-        if current_top_left_front.x > candidate.x || current_top_left_front.y > candidate.y ||
-           current_top_left_front.z > candidate.z {
-            top_left_front_index = index;
-        }
+//returns positions in sorted_indexes of the first node making a triangle with the first two
+//nodes and of the first node making a tetrahedron with that triangle.
+pub fn find_first_not_coplanar(nodes: &[Point3],
+                               sorted_indexes: &[usize])
+                               -> Option<(usize, usize)> {
+    if sorted_indexes.len() < 4 {
+        return None;
+    }
 
-        let current_top_right_front = nodes[top_right_front_index];
-        if current_top_right_front.x > candidate.x || current_top_right_front.y < candidate.y ||
-           current_top_left_front.z > candidate.z {
-            top_right_front_index = index;
-        }
+    let first = &nodes[sorted_indexes[0]];
+    let second = &nodes[sorted_indexes[1]];
 
-        let current_bottom_left_front = nodes[bottom_left_front_index];
-        if current_bottom_left_front.x < candidate.x || current_bottom_left_front.y < candidate.y ||
-           current_top_left_front.z > candidate.z {
-            bottom_left_front_index = index;
+    let mut third_position = None;
+    for position in 2..sorted_indexes.len() {
+        if !are_collinear(first, second, &nodes[sorted_indexes[position]]) {
+            third_position = Some(position);
+            break;
         }
+    }
 
-        let current_bottom_right_front = nodes[bottom_right_front_index];
-        if current_bottom_right_front.x < candidate.x ||
-           current_bottom_right_front.y > candidate.y ||
-           current_top_left_front.z > candidate.z {
-            bottom_right_front_index = index;
-        }
+    let third_position = match third_position {
+        Some(third_position) => third_position,
+        None => return None,
+    };
 
-        let current_top_left_back = nodes[top_left_back_index];
-        if current_top_left_back.x > candidate.x || current_top_left_back.y > candidate.y ||
-            current_top_left_back.z < candidate.z {
-            top_left_back_index = index;
+    let third = &nodes[sorted_indexes[third_position]];
+    for position in third_position + 1..sorted_indexes.len() {
+        let candidate = &nodes[sorted_indexes[position]];
+        if side_of_plane(first, second, third, candidate) != SideOfPlane::OnPlane {
+            return Some((third_position, position));
         }
+    }
 
-        let current_top_right_back = nodes[top_right_back_index];
-        if current_top_right_back.x > candidate.x || current_top_right_back.y < candidate.y ||
-            current_top_left_back.z < candidate.z {
-            top_right_back_index = index;
-        }
+    None
+}
 
-        let current_bottom_left_back = nodes[bottom_left_back_index];
-        if current_bottom_left_back.x < candidate.x || current_bottom_left_back.y < candidate.y ||
-            current_top_left_back.z < candidate.z {
-            bottom_left_back_index = index;
-        }
+//center of the circle through the points, in their plane.
+pub fn triangle_circumcenter(a: &Point3, b: &Point3, c: &Point3) -> Point3 {
+    let (ab, ac) = (b - a, c - a);
+    let normal = ab.cross(ac);
 
-        let current_bottom_right_back = nodes[bottom_right_back_index];
-        if current_bottom_right_back.x < candidate.x ||
-            current_bottom_right_back.y > candidate.y ||
-            current_top_left_back.z < candidate.z {
-            bottom_right_back_index = index;
-        }
+    a + (normal.cross(ab) * ac.magnitude2() + ac.cross(normal) * ab.magnitude2()) /
+        (2. * normal.magnitude2())
+}
+
+pub fn tetrahedron_circumcenter(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> Point3 {
+    let (ab, ac, ad) = (b - a, c - a, d - a);
+
+    a + (ac.cross(ad) * ab.magnitude2() + ad.cross(ab) * ac.magnitude2() +
+         ab.cross(ac) * ad.magnitude2()) / (2. * ab.dot(ac.cross(ad)))
+}
+
+//smallest angle between two faces of the tetrahedron, in degrees.
+pub fn min_dihedral_angle(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> f64 {
+    let edges = [(a, b, c, d), (a, c, b, d), (a, d, b, c), (b, c, a, d), (b, d, a, c),
+                 (c, d, a, b)];
+    let mut min_angle = ::std::f64::consts::PI;
+
+    for &(p, q, r, s) in edges.iter() {
+        //normals of the two faces at the edge pq, turned the same way around it.
+        let first = (q - p).cross(r - p);
+        let second = (q - p).cross(s - p);
+        let cos = first.dot(second) / (first.magnitude() * second.magnitude());
+        min_angle = min_angle.min(cos.max(-1.).min(1.).acos());
+    }
+
+    min_angle.to_degrees()
+}
+
+#[inline]
+fn are_collinear(a: &Point3, b: &Point3, c: &Point3) -> bool {
+    let ab = b - a;
+    let ac = c - a;
+
+    ab.y * ac.z - ab.z * ac.y == 0. && ab.z * ac.x - ab.x * ac.z == 0. &&
+    ab.x * ac.y - ab.y * ac.x == 0.
+}
+
+//indexes of the nodes in a biased randomized insertion order along the curve of the order.
+pub fn curve_order(nodes: &[Point3], order: InsertionOrder) -> Vec<usize> {
+    let mut min = Point3::new(::std::f64::MAX, ::std::f64::MAX, ::std::f64::MAX);
+    let mut max = Point3::new(::std::f64::MIN, ::std::f64::MIN, ::std::f64::MIN);
+    for p in nodes.iter() {
+        min = Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
     }
 
-    [top_left_front_index,
-     top_right_front_index,
-     bottom_left_front_index,
-     bottom_right_front_index,
-     top_left_back_index,
-     top_right_back_index,
-     bottom_left_back_index,
-     bottom_right_back_index]
+    let curve_indexes: Vec<u64> = nodes.iter()
+        .map(|p| {
+            let coordinates = [to_curve_coordinate(p.x, min.x, max.x),
+                               to_curve_coordinate(p.y, min.y, max.y),
+                               to_curve_coordinate(p.z, min.z, max.z)];
+            match order {
+                InsertionOrder::Morton => morton_index(&coordinates),
+                _ => hilbert_index(&coordinates),
+            }
+        })
+        .collect();
+
+    brio_order(&curve_indexes)
 }
 
 #[cfg(test)]
@@ -81,8 +117,43 @@ mod triangulation3_utilities_test {
 
     #[test]
     fn test() {
-        let nodes =  super::super::triangulation3_test_utils::get_example_initial_point_set();
+        let nodes = vec![Point3::new(0., 0., 0.),
+                         Point3::new(1., 0., 0.),
+                         Point3::new(2., 0., 0.),
+                         Point3::new(0., 1., 0.),
+                         Point3::new(1., 1., 0.),
+                         Point3::new(0., 0., 1.)];
+
+        assert_eq!(Some((3, 5)),
+                   find_first_not_coplanar(&nodes, &[0, 1, 2, 3, 4, 5]));
+        assert_eq!(Some((2, 3)),
+                   find_first_not_coplanar(&nodes, &[0, 1, 3, 5, 2, 4]));
+        assert_eq!(None, find_first_not_coplanar(&nodes, &[0, 1, 2, 3, 4]));
+        assert_eq!(None, find_first_not_coplanar(&nodes, &[0, 1, 2, 5]));
+        assert_eq!(None, find_first_not_coplanar(&nodes, &[0, 1, 5]));
+    }
+
+    #[test]
+    fn dihedral_angles() {
+        let regular = [Point3::new(1., 1., 1.),
+                       Point3::new(1., -1., -1.),
+                       Point3::new(-1., 1., -1.),
+                       Point3::new(-1., -1., 1.)];
+        let corner = [Point3::new(0., 0., 0.),
+                      Point3::new(1., 0., 0.),
+                      Point3::new(0., 1., 0.),
+                      Point3::new(0., 0., 1.)];
+        let sliver = [Point3::new(0., 0., 0.),
+                      Point3::new(1., 0., 0.),
+                      Point3::new(1., 1., 0.01),
+                      Point3::new(0., 1., 0.)];
+
+        let regular_angle = min_dihedral_angle(&regular[0], &regular[1], &regular[2], &regular[3]);
+        let corner_angle = min_dihedral_angle(&corner[0], &corner[1], &corner[2], &corner[3]);
+        let sliver_angle = min_dihedral_angle(&sliver[0], &sliver[1], &sliver[2], &sliver[3]);
 
-        assert_eq!([0, 1, 2, 3, 4, 5, 6, 7], find_corner_nodes3(&nodes));
+        assert!((regular_angle - (1f64 / 3.).acos().to_degrees()).abs() < 1e-10);
+        assert!((corner_angle - (1f64 / 3f64.sqrt()).acos().to_degrees()).abs() < 1e-10);
+        assert!(sliver_angle < 1.);
     }
 }
@@ -16,7 +16,8 @@ fn trivial_tests_1() {
     triangulation.insert_node(&Point2::new(2., 2.));
 
     abaqus_2d_write::write_2d_to_abaqus_format("tests/tests_results/trivial_tests_1.inp",
-                                               &triangulation);
+                                               &triangulation)
+        .unwrap();
 }
 
 #[test]
@@ -36,7 +37,8 @@ fn trivial_tests_2() {
     triangulation.insert_node(&Point2::new(10., 8.));
 
     abaqus_2d_write::write_2d_to_abaqus_format("tests/tests_results/trivial_tests_2.inp",
-                                               &triangulation);
+                                               &triangulation)
+        .unwrap();
 }
 
 #[test]
@@ -64,7 +66,8 @@ fn trivial_tests_3() {
     triangulation.insert_node(&Point2::new(8., 8.));
 
     abaqus_2d_write::write_2d_to_abaqus_format("tests/tests_results/trivial_tests_3.inp",
-                                               &triangulation);
+                                               &triangulation)
+        .unwrap();
 }
 
 
@@ -84,7 +87,8 @@ fn trivial_tests_4() {
     }
 
     abaqus_2d_write::write_2d_to_abaqus_format("tests/tests_results/trivial_tests_4.inp",
-                                               &triangulation);
+                                               &triangulation)
+        .unwrap();
 }
 
 #[test]
@@ -105,5 +109,6 @@ fn trivial_tests_5() {
     }
 
     abaqus_2d_write::write_2d_to_abaqus_format("tests/tests_results/trivial_tests_5.inp",
-                                               &triangulation);
+                                               &triangulation)
+        .unwrap();
 }
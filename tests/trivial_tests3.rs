@@ -17,7 +17,7 @@ fn trivial_test3_1() {
         tr.insert_node(&to_insert);
     }
 
-    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_1.inp", &tr);
+    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_1.inp", &tr).unwrap();
 }
 
 #[test]
@@ -25,7 +25,7 @@ fn trivial_test3_2() {
     let mut nodes = Vec::from(triangulation3_test_utils::get_example_initial_point_set());
 
     let tr = Triangulation3::new(&nodes);
-    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_2.inp", &tr);
+    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_2.inp", &tr).unwrap();
 }
 
 #[test]
@@ -45,5 +45,5 @@ fn trivial_test3_3() {
 
     println!("before tr.");
     let tr = Triangulation3::new(&nodes);
-    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_3.inp", &tr);
+    write_3d_to_abaqus_format("tests/tests_results3/trivial_tests3_3.inp", &tr).unwrap();
 }